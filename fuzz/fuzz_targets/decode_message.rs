@@ -0,0 +1,32 @@
+// 网络消息解码器 fuzz 目标
+//
+// 运行（需要 nightly 和 cargo-fuzz）：
+//   cargo +nightly fuzz run decode_message fuzz/corpus/decode_message
+// 只回归检查语料库中的坏包：
+//   cargo +nightly fuzz run decode_message fuzz/corpus/decode_message -- -runs=0
+
+#![no_main]
+
+use bevy_sniper_duel::map::MapDef;
+use bevy_sniper_duel::protocol::{
+    decode_datagram, encode_message, limits_for, validate_message, MessageLimits, MAX_DATAGRAM_SIZE,
+};
+use bevy_sniper_duel::rules::GameRules;
+use libfuzzer_sys::fuzz_target;
+use std::sync::LazyLock;
+
+/// 经典规则（GameRules::default）和内置经典地图的校验范围，与游戏本体使用同一个 limits_for 构造
+static LIMITS: LazyLock<MessageLimits> = LazyLock::new(|| limits_for(&GameRules::default(), &MapDef::default()));
+
+fuzz_target!(|data: &[u8]| {
+    // 解码不能 panic，也不能按长度前缀无限分配内存
+    let Ok(message) = decode_datagram(data) else {
+        return;
+    };
+    let _ = validate_message(&message, &LIMITS);
+
+    // 能解码的消息必须能在同样的大小限制内重新编码
+    let encoded = encode_message(&message).expect("解码成功的消息应能重新编码");
+    assert!(encoded.len() <= MAX_DATAGRAM_SIZE);
+    assert!(decode_datagram(&encoded).is_ok());
+});
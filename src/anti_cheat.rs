@@ -0,0 +1,567 @@
+// 反作弊：对对方发来的输入做合理性检查
+//
+// 每一端都是对方输入的接收方，也是这些输入的权威方：
+// - 进攻方检查防守方的移动速度、动作冷却，并推算防守方的体力（DefenderState）
+// - 防守方检查准星的移动速度（CrosshairPosition）
+// - 双方都检查对方的射速、剩余子弹、射击者身份和中途换枪（BulletSpawn）
+// - 进攻方检查防守方每回合的道具次数（DefenderUtility）
+// - 进攻方检查防守方每回合的修墙次数和修墙时离砖块的距离（WallRepair）
+// 超出范围的取值在 decode_message 中已经直接丢弃，这里处理"单条消息合法、但连起来不可能"的情况。
+// 速度违规会被截断到允许的距离，其余违规直接拒绝；每次违规都会累加违规分，分数随时间衰减。
+
+use bevy::prelude::*;
+use std::net::SocketAddr;
+use crate::PlayerId;
+use crate::gameplay::{ActionCooldown, DodgeAction};
+use crate::game_rules::GameRules;
+use crate::weapons::{Weapon, WeaponKind};
+
+/// 速度容差：网络抖动会让相邻两条消息的间隔忽长忽短，允许超出理论速度 25%
+const SPEED_TOLERANCE: f32 = 1.25;
+/// 移动预算最多累积多少秒的移动量（容忍消息成批到达）
+const BUDGET_BURST_SECONDS: f32 = 0.5;
+/// 最短射击间隔 = 冷却时间 × 该系数（同样为网络抖动留余量）
+const FIRE_INTERVAL_TOLERANCE: f64 = 0.8;
+/// 修墙距离的容差（防守方位置有网络延迟）
+const REPAIR_REACH_TOLERANCE: f32 = 1.25;
+/// 侧躲所需体力的容差（体力随位置同步发送，和动作可能差一帧）
+const DODGE_STAMINA_TOLERANCE: f32 = 5.0;
+/// 报告的体力高于本机推算的体力多少以内不算违规（浮点误差和帧间差异）
+const STAMINA_TOLERANCE: f32 = 2.0;
+/// 动作冷却的容差（秒）
+const DODGE_COOLDOWN_TOLERANCE: f64 = 0.5;
+/// 换回合后的宽限期（秒），期间上一回合进攻方迟到的子弹只丢弃不计分
+const SWITCH_GRACE_SECONDS: f64 = 1.0;
+/// 违规分每秒衰减量
+const VIOLATION_DECAY_PER_SECOND: f32 = 0.1;
+/// 违规分超过该值时判定对方可疑
+const SUSPICIOUS_SCORE: f32 = 20.0;
+/// 同类违规日志的最短间隔（秒），避免每帧刷屏
+const LOG_INTERVAL_SECONDS: f64 = 1.0;
+
+/// 违规类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    DefenderSpeed,   // 防守方移动过快
+    CrosshairSpeed,  // 准星移动过快
+    FireRate,        // 射速超过冷却
+    NoBullets,       // 子弹已用完仍在射击
+    WrongShooter,    // 非当前进攻方射击
+    DodgeCooldown,   // 动作冷却中仍在躲避
+    WeaponSwap,      // 开火后中途换枪
+    ExtraUtility,    // 道具次数已用完仍在使用
+    ExtraRepair,     // 修墙次数已用完仍在修墙
+    RepairReach,     // 离砖块太远仍在修墙
+    StaminaRegen,    // 报告的体力高于应有的体力
+    Exhausted,       // 体力不足仍在侧躲
+}
+
+impl Violation {
+    const ALL: [Violation; 12] = [
+        Violation::DefenderSpeed,
+        Violation::CrosshairSpeed,
+        Violation::FireRate,
+        Violation::NoBullets,
+        Violation::WrongShooter,
+        Violation::DodgeCooldown,
+        Violation::WeaponSwap,
+        Violation::ExtraUtility,
+        Violation::ExtraRepair,
+        Violation::RepairReach,
+        Violation::StaminaRegen,
+        Violation::Exhausted,
+    ];
+
+    /// 违规权重：移动过快可能只是网络抖动，权重较低；凭空多出的子弹几乎只能是作弊
+    fn weight(self) -> f32 {
+        match self {
+            Violation::DefenderSpeed | Violation::CrosshairSpeed | Violation::StaminaRegen => 0.5,
+            Violation::FireRate | Violation::DodgeCooldown | Violation::RepairReach | Violation::Exhausted => 3.0,
+            Violation::NoBullets | Violation::WrongShooter | Violation::WeaponSwap | Violation::ExtraUtility | Violation::ExtraRepair => 5.0,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Violation::DefenderSpeed => "防守方移动速度超限，已截断",
+            Violation::CrosshairSpeed => "准星移动速度超限，已截断",
+            Violation::FireRate => "射速超过射击冷却，已拒绝",
+            Violation::NoBullets => "本回合子弹已用完仍在射击，已拒绝",
+            Violation::WrongShooter => "非当前进攻方发射子弹，已拒绝",
+            Violation::DodgeCooldown => "动作冷却中仍在躲避，已拒绝",
+            Violation::WeaponSwap => "开火后中途更换武器，已拒绝",
+            Violation::ExtraUtility => "本回合道具次数已用完仍在使用，已拒绝",
+            Violation::ExtraRepair => "本回合修墙次数已用完仍在修墙，已拒绝",
+            Violation::RepairReach => "离砖块太远仍在修墙，已拒绝",
+            Violation::StaminaRegen => "体力高于应有值（恢复过快或冲刺、侧躲没有扣体力），已截断",
+            Violation::Exhausted => "体力不足仍在侧躲，已拒绝",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|v| *v == self).unwrap_or(0)
+    }
+}
+
+/// 移动预算（令牌桶）：按最大速度随时间补充，最多累积 BUDGET_BURST_SECONDS 秒的移动量（体力恢复也按同样的方式限制）
+#[derive(Debug, Default)]
+pub struct MovementBudget {
+    available: f32,
+    last_update: Option<f64>,
+}
+
+impl MovementBudget {
+    /// 按经过的时间补充预算，返回当前允许的最大移动距离
+    /// 第一次调用（回合开始后的第一条消息）不限制，作为起点
+    fn allowance(&mut self, now: f64, speed: f32) -> Option<f32> {
+        let burst = speed * SPEED_TOLERANCE * BUDGET_BURST_SECONDS;
+        let Some(last_update) = self.last_update.replace(now) else {
+            self.available = burst;
+            return None;
+        };
+        let elapsed = (now - last_update).max(0.0) as f32;
+        self.available = (self.available + elapsed * speed * SPEED_TOLERANCE).min(burst);
+        Some(self.available)
+    }
+
+    fn spend(&mut self, distance: f32) {
+        self.available = (self.available - distance).max(0.0);
+    }
+
+    /// 额外增加预算（侧躲会瞬间位移）
+    fn grant(&mut self, distance: f32) {
+        self.available += distance;
+    }
+}
+
+/// 对方输入的监控状态
+#[derive(Resource, Debug, Default)]
+pub struct AntiCheatMonitor {
+    pub violation_score: f32,         // 当前违规分（随时间衰减）
+    pub total_violations: u32,        // 本局累计违规次数
+    flagged: bool,                    // 是否已输出过"可疑"警告
+    tracked_attacker: Option<PlayerId>, // 当前跟踪的回合（进攻方变化即换回合）
+    defender_budget: MovementBudget,
+    crosshair_budget: MovementBudget,
+    stamina_budget: MovementBudget,   // 体力恢复的预算（只在站着不动时累积）
+    walk_budget: MovementBudget,      // 按步行速度允许的移动距离（超出部分算冲刺）
+    remote_stamina: Option<f32>,      // 本机推算的对方防守方体力（回合开始时为满体力）
+    pending_dodge_distance: f32,      // 已接受的侧躲带来的位移（不算冲刺）
+    last_remote_action: DodgeAction,  // 对方上一次报告的动作
+    rejected_action: Option<DodgeAction>, // 因冷却被拒绝、仍在持续的动作
+    last_remote_shot: Option<f64>,    // 对方上一次射击的时间
+    remote_shots_this_round: i32,     // 本回合已接受的对方射击次数
+    remote_weapon: Option<WeaponKind>, // 本回合对方第一枪所用的武器（之后不能再换）
+    remote_utilities_this_round: u32, // 本回合已接受的对方道具次数
+    remote_repairs_this_round: u32,   // 本回合已接受的对方修墙次数
+    round_started_at: Option<f64>,    // 本回合开始（进攻方变化）的时间
+    last_decay: Option<f64>,
+    last_log: [Option<f64>; Violation::ALL.len()],
+}
+
+impl AntiCheatMonitor {
+    /// 进攻方变化（换回合）时重置跟踪状态，违规分保留
+    pub fn sync_round(&mut self, current_attacker: PlayerId, now: f64) {
+        if self.tracked_attacker == Some(current_attacker) {
+            return;
+        }
+        self.tracked_attacker = Some(current_attacker);
+        self.round_started_at = Some(now);
+        self.remote_shots_this_round = 0;
+        self.remote_weapon = None;
+        self.remote_utilities_this_round = 0;
+        self.remote_repairs_this_round = 0;
+        self.defender_budget = MovementBudget::default();
+        self.crosshair_budget = MovementBudget::default();
+        self.stamina_budget = MovementBudget::default();
+        self.walk_budget = MovementBudget::default();
+        self.remote_stamina = None;
+        self.pending_dodge_distance = 0.0;
+        self.last_remote_action = DodgeAction::None;
+        self.rejected_action = None;
+        self.last_remote_shot = None;
+    }
+
+    /// 本机推算的对方防守方体力（还没收到过时按满体力算）
+    fn remote_stamina(&self, rules: &GameRules) -> f32 {
+        self.remote_stamina.unwrap_or(rules.stamina_max)
+    }
+
+    /// 扣除对方防守方的体力（侧躲、站起来、冲刺）
+    fn drain_remote_stamina(&mut self, rules: &GameRules, amount: f32) {
+        self.remote_stamina = Some((self.remote_stamina(rules) - amount).max(0.0));
+    }
+
+    /// 检查防守方的位移，返回截断后的新位置（还有体力时按冲刺速度计算）
+    pub fn check_defender_move(&mut self, rules: &GameRules, now: f64, current: Vec3, reported: Vec3, peer: Option<SocketAddr>) -> Vec3 {
+        let delta = (reported - current).truncate();
+        let sprint = if self.remote_stamina(rules) > 0.0 { rules.sprint_multiplier } else { 1.0 };
+        let Some(allowed) = self.defender_budget.allowance(now, rules.player_move_speed * 0.8 * sprint) else {
+            return reported;
+        };
+        let distance = delta.length();
+        if distance <= allowed {
+            self.defender_budget.spend(distance);
+            return reported;
+        }
+        self.defender_budget.spend(allowed);
+        self.report(Violation::DefenderSpeed, now, peer);
+        let clamped = current.truncate() + delta.clamp_length_max(allowed);
+        clamped.extend(reported.z)
+    }
+
+    /// 检查对方报告的动作，返回是否接受该动作
+    /// 只有动作发生变化且不是 None 时才算一次新的躲避；被拒绝的动作在持续期间一直拒绝
+    pub fn check_dodge(&mut self, rules: &GameRules, now: f64, action: DodgeAction, cooldown: &mut ActionCooldown, peer: Option<SocketAddr>) -> bool {
+        let previous = std::mem::replace(&mut self.last_remote_action, action);
+        if action == DodgeAction::None {
+            // 被接受的下蹲结束时站起来要消耗体力
+            if previous == DodgeAction::Crouch && self.rejected_action != Some(DodgeAction::Crouch) {
+                self.drain_remote_stamina(rules, rules.stand_stamina);
+            }
+            self.rejected_action = None;
+            return true;
+        }
+        if action == previous {
+            return self.rejected_action != Some(action);
+        }
+        // 冷却在本地按同样的规则计算（回合开始时 last_action_time 为 0）
+        if now - cooldown.last_action_time + DODGE_COOLDOWN_TOLERANCE < cooldown.cooldown_duration {
+            self.rejected_action = Some(action);
+            self.report(Violation::DodgeCooldown, now, peer);
+            return false;
+        }
+        // 侧躲要消耗体力，按躲避之前报告的体力检查
+        let side_dodge = matches!(action, DodgeAction::SideLeft | DodgeAction::SideRight);
+        if side_dodge && self.remote_stamina(rules) + DODGE_STAMINA_TOLERANCE < rules.dodge_stamina {
+            self.rejected_action = Some(action);
+            self.report(Violation::Exhausted, now, peer);
+            return false;
+        }
+        self.rejected_action = None;
+        cooldown.last_action_time = now;
+        if side_dodge {
+            self.defender_budget.grant(rules.side_dodge_distance);
+            self.pending_dodge_distance += rules.side_dodge_distance;
+            self.drain_remote_stamina(rules, rules.dodge_stamina);
+        }
+        true
+    }
+
+    /// 检查防守方报告的体力，返回截断后的体力
+    /// 本机按对方的动作和位移推算体力：侧躲、站起来在 check_dodge 中扣除，超出步行速度的位移按冲刺扣除，
+    /// 只有站着不动、也没有在躲避时才恢复。报告的体力不能高于推算值（低于推算值时以报告为准）。
+    /// moved 为本条消息中（截断后）防守方的位移距离，要在 check_dodge 和 check_defender_move 之后调用
+    pub fn check_stamina(&mut self, rules: &GameRules, now: f64, reported: f32, moved: f32, peer: Option<SocketAddr>) -> f32 {
+        // 侧躲的瞬间位移不算冲刺
+        let dodge_distance = self.pending_dodge_distance.min(moved);
+        self.pending_dodge_distance = 0.0;
+        let moved = moved - dodge_distance;
+
+        let walk_allowance = self.walk_budget.allowance(now, rules.player_move_speed * 0.8);
+        let regen_allowance = self.stamina_budget.allowance(now, rules.stamina_regen_per_second);
+        if let Some(walk_allowance) = walk_allowance {
+            self.walk_budget.spend(moved);
+            // 冲刺 t 秒比步行多走 (倍数 - 1) × 步行速度 × t，按多走的距离反推冲刺时间
+            let sprint_gain = (rules.sprint_multiplier - 1.0) * rules.player_move_speed * 0.8;
+            let excess = moved - walk_allowance;
+            if excess > 0.0 && sprint_gain > 0.0 {
+                self.drain_remote_stamina(rules, excess / sprint_gain * rules.sprint_stamina_per_second);
+            }
+        }
+        let still = moved == 0.0 && dodge_distance == 0.0 && self.last_remote_action == DodgeAction::None;
+        if let Some(regen_allowance) = regen_allowance {
+            if still {
+                let expected = self.remote_stamina(rules);
+                let regen = regen_allowance.min(rules.stamina_max - expected).max(0.0);
+                self.stamina_budget.spend(regen);
+                self.remote_stamina = Some(expected + regen);
+            } else {
+                // 移动或躲避时不恢复，也不累积恢复的预算
+                self.stamina_budget.spend(regen_allowance);
+            }
+        }
+
+        let expected = self.remote_stamina(rules);
+        let stamina = if reported > expected + STAMINA_TOLERANCE {
+            self.report(Violation::StaminaRegen, now, peer);
+            expected
+        } else {
+            reported.min(expected)
+        };
+        self.remote_stamina = Some(stamina);
+        stamina
+    }
+
+    /// 检查准星移动，返回截断后的新位置
+    pub fn check_crosshair_move(&mut self, rules: &GameRules, now: f64, current: Vec2, reported: Vec2, peer: Option<SocketAddr>) -> Vec2 {
+        let Some(allowed) = self.crosshair_budget.allowance(now, rules.aim_speed) else {
+            return reported;
+        };
+        let delta = reported - current;
+        let distance = delta.length();
+        if distance <= allowed {
+            self.crosshair_budget.spend(distance);
+            return reported;
+        }
+        self.crosshair_budget.spend(allowed);
+        self.report(Violation::CrosshairSpeed, now, peer);
+        current + delta.clamp_length_max(allowed)
+    }
+
+    /// 检查对方的射击，返回是否接受该子弹
+    /// 子弹数按本回合接受的射击次数计算，不依赖 RoundInfoSync 与 BulletSpawn 的到达顺序；
+    /// 弹匣和射击间隔按这一枪所用的武器计算，开火后本回合不能再换枪
+    pub fn check_shot(&mut self, weapon: &Weapon, now: f64, owner: PlayerId, current_attacker: PlayerId, local_player: PlayerId, peer: Option<SocketAddr>) -> bool {
+        if owner != current_attacker || owner == local_player {
+            // 换回合前最后一发子弹可能在切换之后才到达，宽限期内只丢弃不计分
+            let in_grace = owner != local_player
+                && self.round_started_at.is_some_and(|t| now - t < SWITCH_GRACE_SECONDS);
+            if !in_grace {
+                self.report(Violation::WrongShooter, now, peer);
+            }
+            return false;
+        }
+        if self.remote_weapon.is_some_and(|first| first != weapon.kind) {
+            self.report(Violation::WeaponSwap, now, peer);
+            return false;
+        }
+        if self.remote_shots_this_round >= weapon.magazine_size {
+            self.report(Violation::NoBullets, now, peer);
+            return false;
+        }
+        let min_interval = weapon.cooldown_seconds as f64 * FIRE_INTERVAL_TOLERANCE;
+        if self.last_remote_shot.is_some_and(|last_shot| now - last_shot < min_interval) {
+            self.report(Violation::FireRate, now, peer);
+            return false;
+        }
+        self.last_remote_shot = Some(now);
+        self.remote_shots_this_round += 1;
+        self.remote_weapon = Some(weapon.kind);
+        true
+    }
+
+    /// 检查对方防守方使用的道具，返回是否接受
+    /// 同一次使用会重复发送，charge 小于已接受次数的是重复消息，直接忽略不计分
+    pub fn check_utility(&mut self, rules: &GameRules, now: f64, charge: u32, peer: Option<SocketAddr>) -> bool {
+        if charge < self.remote_utilities_this_round {
+            return false;
+        }
+        if charge >= rules.utility_charges {
+            self.report(Violation::ExtraUtility, now, peer);
+            return false;
+        }
+        self.remote_utilities_this_round = charge + 1;
+        true
+    }
+
+    /// 检查对方防守方修好的砖块，返回是否接受
+    /// distance 为本机看到的防守方位置到砖块的距离；重复消息的处理同 check_utility
+    pub fn check_repair(&mut self, rules: &GameRules, now: f64, repair: u32, distance: f32, peer: Option<SocketAddr>) -> bool {
+        if repair < self.remote_repairs_this_round {
+            return false;
+        }
+        if repair >= rules.repairs_per_round {
+            self.report(Violation::ExtraRepair, now, peer);
+            return false;
+        }
+        if distance > rules.repair_reach * REPAIR_REACH_TOLERANCE {
+            self.report(Violation::RepairReach, now, peer);
+            return false;
+        }
+        self.remote_repairs_this_round = repair + 1;
+        true
+    }
+
+    /// 违规分衰减
+    pub fn decay(&mut self, now: f64) {
+        if let Some(last_decay) = self.last_decay {
+            let elapsed = (now - last_decay).max(0.0) as f32;
+            self.violation_score = (self.violation_score - elapsed * VIOLATION_DECAY_PER_SECOND).max(0.0);
+        }
+        self.last_decay = Some(now);
+        if self.flagged && self.violation_score < SUSPICIOUS_SCORE / 2.0 {
+            self.flagged = false;
+        }
+    }
+
+    fn report(&mut self, violation: Violation, now: f64, peer: Option<SocketAddr>) {
+        self.violation_score += violation.weight();
+        self.total_violations += 1;
+
+        let peer = peer.map(|addr| addr.to_string()).unwrap_or_else(|| "未知".to_string());
+        let last_log = &mut self.last_log[violation.index()];
+        if last_log.is_none_or(|t| now - t >= LOG_INTERVAL_SECONDS) {
+            *last_log = Some(now);
+            eprintln!(
+                "[反作弊] 对方 {}: {}（违规分 {:.1}，累计 {} 次）",
+                peer, violation.description(), self.violation_score, self.total_violations
+            );
+        }
+        if !self.flagged && self.violation_score >= SUSPICIOUS_SCORE {
+            self.flagged = true;
+            eprintln!("[反作弊] 对方 {} 的违规分达到 {:.1}，疑似作弊", peer, self.violation_score);
+        }
+    }
+}
+
+/// 违规分衰减系统
+pub fn anti_cheat_decay_system(time: Res<Time>, mut monitor: ResMut<AntiCheatMonitor>) {
+    monitor.decay(time.elapsed_seconds_f64());
+}
+
+/// 进入游戏时重置监控状态
+pub fn reset_anti_cheat(mut monitor: ResMut<AntiCheatMonitor>) {
+    *monitor = AntiCheatMonitor::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "期望 {}，实际 {}", expected, actual);
+    }
+
+    #[test]
+    fn movement_budget_refills_up_to_burst() {
+        let mut budget = MovementBudget::default();
+        // 第一次调用只作为起点，不限制，预算充满
+        assert_eq!(budget.allowance(0.0, 100.0), None);
+        let burst = 100.0 * SPEED_TOLERANCE * BUDGET_BURST_SECONDS;
+        budget.spend(burst);
+        assert_close(budget.allowance(0.0, 100.0).unwrap(), 0.0);
+        // 按经过的时间补充
+        assert_close(budget.allowance(0.2, 100.0).unwrap(), 0.2 * 100.0 * SPEED_TOLERANCE);
+        // 长时间不动也最多累积 burst
+        assert_close(budget.allowance(10.0, 100.0).unwrap(), burst);
+        // 时间倒退不会补充，也不会扣成负数
+        budget.spend(burst * 2.0);
+        assert_close(budget.allowance(5.0, 100.0).unwrap(), 0.0);
+        // 侧躲额外增加预算
+        budget.grant(30.0);
+        assert_close(budget.allowance(5.0, 100.0).unwrap(), 30.0);
+    }
+
+    #[test]
+    fn crosshair_move_is_clamped_to_budget() {
+        let rules = GameRules::default();
+        let mut monitor = AntiCheatMonitor::default();
+        assert_eq!(monitor.check_crosshair_move(&rules, 0.0, Vec2::ZERO, Vec2::new(500.0, 0.0), None), Vec2::new(500.0, 0.0));
+        let burst = rules.aim_speed * SPEED_TOLERANCE * BUDGET_BURST_SECONDS;
+        let clamped = monitor.check_crosshair_move(&rules, 0.1, Vec2::ZERO, Vec2::new(1000.0, 0.0), None);
+        assert_close(clamped.x, burst);
+        assert_close(monitor.violation_score, Violation::CrosshairSpeed.weight());
+        // 预算已用完，下一条消息只能移动 0.1 秒的距离
+        let clamped = monitor.check_crosshair_move(&rules, 0.2, Vec2::ZERO, Vec2::new(1000.0, 0.0), None);
+        assert_close(clamped.x, 0.1 * rules.aim_speed * SPEED_TOLERANCE);
+    }
+
+    #[test]
+    fn dodge_during_cooldown_is_rejected() {
+        let rules = GameRules::default();
+        let mut monitor = AntiCheatMonitor::default();
+        let mut cooldown = ActionCooldown { last_action_time: 0.0, cooldown_duration: 5.0 };
+        assert!(!monitor.check_dodge(&rules, 1.0, DodgeAction::Crouch, &mut cooldown, None));
+        assert_eq!(monitor.total_violations, 1);
+        // 被拒绝的动作持续期间一直拒绝，但不重复计分
+        assert!(!monitor.check_dodge(&rules, 1.5, DodgeAction::Crouch, &mut cooldown, None));
+        assert_eq!(monitor.total_violations, 1);
+        assert!(monitor.check_dodge(&rules, 2.0, DodgeAction::None, &mut cooldown, None));
+        // 冷却结束（含容差）后接受，并从这次动作开始重新计算冷却
+        assert!(monitor.check_dodge(&rules, 4.6, DodgeAction::Crouch, &mut cooldown, None));
+        assert_eq!(cooldown.last_action_time, 4.6);
+        assert_close(monitor.violation_score, Violation::DodgeCooldown.weight());
+    }
+
+    #[test]
+    fn shot_faster_than_cooldown_is_rejected() {
+        let rules = GameRules::default();
+        let weapon = Weapon::of(WeaponKind::BoltAction, &rules);
+        let mut monitor = AntiCheatMonitor::default();
+        monitor.sync_round(PlayerId::Player2, 0.0);
+        let shoot = |monitor: &mut AntiCheatMonitor, now: f64| monitor.check_shot(&weapon, now, PlayerId::Player2, PlayerId::Player2, PlayerId::Player1, None);
+        assert!(shoot(&mut monitor, 2.0));
+        assert!(!shoot(&mut monitor, 2.1));
+        assert_close(monitor.violation_score, Violation::FireRate.weight());
+        // 被拒绝的那一枪不占用子弹，冷却从上一次接受的射击开始计算
+        assert!(shoot(&mut monitor, 3.0));
+        assert_eq!(monitor.remote_shots_this_round, 2);
+    }
+
+    #[test]
+    fn repeated_or_out_of_order_utility_is_ignored() {
+        let rules = GameRules::default();
+        let mut monitor = AntiCheatMonitor::default();
+        // 第 0 次的消息丢失，先收到第 1 次
+        assert!(monitor.check_utility(&rules, 0.0, 1, None));
+        assert_eq!(monitor.remote_utilities_this_round, 2);
+        // 迟到的第 0 次和重发的第 1 次只忽略，不计分
+        assert!(!monitor.check_utility(&rules, 0.1, 0, None));
+        assert!(!monitor.check_utility(&rules, 0.2, 1, None));
+        assert_eq!(monitor.total_violations, 0);
+        // 超出每回合的次数才算违规
+        assert!(!monitor.check_utility(&rules, 0.3, rules.utility_charges, None));
+        assert_eq!(monitor.total_violations, 1);
+        assert_close(monitor.violation_score, Violation::ExtraUtility.weight());
+    }
+
+    #[test]
+    fn violation_score_accumulates_and_decays() {
+        let mut monitor = AntiCheatMonitor::default();
+        monitor.report(Violation::DefenderSpeed, 0.0, None);
+        monitor.report(Violation::FireRate, 0.0, None);
+        assert_close(monitor.violation_score, 3.5);
+        assert!(!monitor.flagged);
+        for _ in 0..4 {
+            monitor.report(Violation::NoBullets, 0.0, None);
+        }
+        assert_close(monitor.violation_score, 23.5);
+        assert_eq!(monitor.total_violations, 6);
+        assert!(monitor.flagged);
+
+        // 第一次调用只记录时间
+        monitor.decay(0.0);
+        assert_close(monitor.violation_score, 23.5);
+        monitor.decay(10.0);
+        assert_close(monitor.violation_score, 23.5 - 10.0 * VIOLATION_DECAY_PER_SECOND);
+        assert!(monitor.flagged);
+        // 降到可疑分数的一半以下才解除标记，且不会降到负数
+        monitor.decay(1000.0);
+        assert_eq!(monitor.violation_score, 0.0);
+        assert!(!monitor.flagged);
+        assert_eq!(monitor.total_violations, 6);
+    }
+
+    #[test]
+    fn new_round_resets_per_round_counters() {
+        let rules = GameRules::default();
+        let weapon = Weapon::of(WeaponKind::BoltAction, &rules);
+        let mut monitor = AntiCheatMonitor::default();
+        monitor.sync_round(PlayerId::Player2, 0.0);
+        for i in 0..weapon.magazine_size {
+            assert!(monitor.check_shot(&weapon, 2.0 + i as f64 * 2.0, PlayerId::Player2, PlayerId::Player2, PlayerId::Player1, None));
+        }
+        assert!(monitor.check_utility(&rules, 2.0, rules.utility_charges - 1, None));
+        assert!(monitor.check_repair(&rules, 2.0, rules.repairs_per_round - 1, 0.0, None));
+        monitor.report(Violation::FireRate, 2.0, None);
+
+        // 进攻方不变时不重置
+        monitor.sync_round(PlayerId::Player2, 10.0);
+        assert!(!monitor.check_shot(&weapon, 20.0, PlayerId::Player2, PlayerId::Player2, PlayerId::Player1, None));
+        let score = monitor.violation_score;
+
+        // 换回合后本回合的次数和预算都重置，违规分保留
+        monitor.sync_round(PlayerId::Player1, 30.0);
+        assert_eq!(monitor.violation_score, score);
+        assert_eq!(monitor.remote_shots_this_round, 0);
+        assert_eq!(monitor.remote_weapon, None);
+        assert_eq!(monitor.last_remote_shot, None);
+        assert!(monitor.check_utility(&rules, 30.0, 0, None));
+        assert!(monitor.check_repair(&rules, 30.0, 0, 0.0, None));
+        assert_eq!(monitor.check_crosshair_move(&rules, 30.0, Vec2::ZERO, Vec2::new(5000.0, 0.0), None), Vec2::new(5000.0, 0.0));
+        // 换回合后的宽限期内，上一回合进攻方迟到的子弹只丢弃不计分
+        assert!(!monitor.check_shot(&weapon, 30.5, PlayerId::Player2, PlayerId::Player1, PlayerId::Player1, None));
+        assert_eq!(monitor.violation_score, score);
+    }
+}
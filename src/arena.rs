@@ -0,0 +1,298 @@
+// 地图的加载与同步
+//
+// 启动时通过 `--map <地图名或文件>` 参数或环境变量 `SNIPER_MAP` 选择地图：
+// - 地图名（如 classic、mixed）对应 assets/maps/<地图名>.ron
+// - 以 .ron 结尾的参数当作文件路径直接加载
+// - random 表示每次回到主菜单都换一个种子生成新地图，seed:<种子> 用指定的种子生成地图（见 mapgen.rs）
+// 未指定时使用 classic；文件缺失或不合法时退回内置的经典地图。
+//
+// 主菜单和创建房间的界面可以切换地图（在 assets/maps/ 下的地图之间轮换）。
+// 联机时主机把地图放进 StartGame（生成的地图只发种子），客户端收到后改用主机的地图，回到主菜单时恢复自己选择的地图。
+// 地图编辑器（map_editor.rs）保存的地图也放在 assets/maps/ 下，保存后出现在轮换中。
+// 消息校验中的防守方活动范围和瞄准原点取自当前地图（见 network_game::publish_message_limits）。
+// 墙上砖块的行列与世界坐标的换算（brick_center / brick_cell_at）也在这里，生成墙体和地图编辑器共用。
+
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+pub use bevy_sniper_duel::map::{CoverKind, CoverProp, MapDef, MapSource, WallDef};
+use bevy_sniper_duel::mapgen;
+use crate::{BRICK_HEIGHT, BRICK_WIDTH};
+
+/// 地图选择的环境变量名
+pub const MAP_ENV_VAR: &str = "SNIPER_MAP";
+/// 默认地图
+pub const DEFAULT_MAP: &str = "classic";
+/// 地图文件所在目录
+pub const MAP_DIR: &str = "assets/maps";
+/// 每次回到主菜单都重新生成的地图
+pub const RANDOM_MAP: &str = "random";
+/// 指定种子生成地图的前缀（seed:12345）
+const SEED_PREFIX: &str = "seed:";
+
+/// 本局使用的地图（联机时客户端的地图来自主机）
+#[derive(Resource, Default, Clone, PartialEq)]
+pub struct Arena {
+    pub map: MapDef,
+    pub seed: Option<u64>, // 生成地图用的种子（地图来自文件时为 None）
+}
+
+impl Arena {
+    pub fn new(map: MapDef) -> Self {
+        Self { map, seed: None }
+    }
+
+    /// 用种子生成的地图
+    pub fn generated(seed: u64) -> Self {
+        Self { map: mapgen::generate(seed, [BRICK_WIDTH, BRICK_HEIGHT]), seed: Some(seed) }
+    }
+
+    /// 收到主机的 StartGame 时按来源建立地图
+    pub fn from_source(source: MapSource) -> Self {
+        match source {
+            MapSource::Def(map) => Self::new(map),
+            MapSource::Seed(seed) => Self::generated(seed),
+        }
+    }
+
+    /// 发给客户端的地图（生成的地图只发种子）
+    pub fn source(&self) -> MapSource {
+        match self.seed {
+            Some(seed) => MapSource::Seed(seed),
+            None => MapSource::Def(self.map.clone()),
+        }
+    }
+
+    /// 进攻方的位置（Z 与玩家实体一致）
+    pub fn attacker_spawn(&self) -> Vec3 {
+        Vec2::from(self.map.attacker_spawn).extend(1.0)
+    }
+
+    pub fn defender_spawn(&self) -> Vec3 {
+        Vec2::from(self.map.defender_spawn).extend(1.0)
+    }
+
+    /// 子弹和瞄准的起点（进攻方位置固定）
+    pub fn aim_origin(&self) -> Vec2 {
+        Vec2::from(self.map.attacker_spawn)
+    }
+
+    /// 防守方身体中心可以到达的范围（见 MapDef::defender_bounds）
+    pub fn defender_bounds(&self, body_size: Vec2) -> (Vec2, Vec2) {
+        let (min, max) = self.map.defender_bounds(body_size.to_array());
+        (Vec2::from(min), Vec2::from(max))
+    }
+
+    /// 每个视角的砖块总数
+    pub fn brick_count(&self) -> usize {
+        self.map.walls.iter().map(|wall| wall.cols as usize * wall.rows as usize).sum()
+    }
+}
+
+/// 第 row 行第 col 列砖块的中心（墙以 position 为中心排布，奇数行向右错开半块砖）
+pub fn brick_center(wall: &WallDef, row: usize, col: usize) -> Vec2 {
+    let x_offset = (col as f32 - (wall.cols as f32 - 1.0) / 2.0) * BRICK_WIDTH;
+    let y_offset = (row as f32 - (wall.rows as f32 - 1.0) / 2.0) * BRICK_HEIGHT;
+    let row_offset = if row % 2 == 1 { BRICK_WIDTH / 2.0 } else { 0.0 };
+    Vec2::from(wall.position) + Vec2::new(x_offset + row_offset, y_offset)
+}
+
+/// 某个位置上的砖块（行，列），不在墙上时返回 None（brick_center 的逆运算）
+pub fn brick_cell_at(wall: &WallDef, point: Vec2) -> Option<(usize, usize)> {
+    let local = point - Vec2::from(wall.position);
+    let row = (local.y / BRICK_HEIGHT + (wall.rows as f32 - 1.0) / 2.0).round();
+    if row < 0.0 || row >= wall.rows as f32 {
+        return None;
+    }
+    let row_offset = if row as usize % 2 == 1 { BRICK_WIDTH / 2.0 } else { 0.0 };
+    let col = ((local.x - row_offset) / BRICK_WIDTH + (wall.cols as f32 - 1.0) / 2.0).round();
+    if col < 0.0 || col >= wall.cols as f32 {
+        return None;
+    }
+    Some((row as usize, col as usize))
+}
+
+/// 本地选择的地图：assets/maps/ 下可选的地图名（最后是随机生成）和当前选中的地图
+#[derive(Resource, Default)]
+pub struct MapChoice {
+    pub names: Vec<String>,
+    pub selected: usize,
+    pub map: MapDef,
+    pub seed: Option<u64>,
+}
+
+impl MapChoice {
+    pub fn label(&self) -> String {
+        if self.is_random() {
+            format!("随机（{}）", self.map.name)
+        } else {
+            self.map.name.clone()
+        }
+    }
+
+    fn is_random(&self) -> bool {
+        self.names.get(self.selected).is_some_and(|name| name == RANDOM_MAP)
+    }
+
+    /// 随机地图换一个种子
+    fn reroll(&mut self) {
+        let arena = Arena::generated(random_seed());
+        self.map = arena.map;
+        self.seed = arena.seed;
+    }
+
+    /// 当前选择对应的本局地图
+    pub fn arena(&self) -> Arena {
+        Arena { map: self.map.clone(), seed: self.seed }
+    }
+
+    /// 把新保存的地图加入轮换
+    pub fn add_name(&mut self, name: &str) {
+        if !self.names.iter().any(|existing| existing == name) {
+            self.names.push(name.to_string());
+        }
+    }
+
+    /// 切换到下一张地图（加载失败的地图跳过）
+    pub fn select_next(&mut self) {
+        for step in 1..=self.names.len() {
+            let index = (self.selected + step) % self.names.len();
+            match load_selection(&self.names[index]) {
+                Ok((map, seed)) => {
+                    self.selected = index;
+                    self.map = map;
+                    self.seed = seed;
+                    return;
+                }
+                Err(e) => eprintln!("[地图] 跳过地图 {}: {}", self.names[index], e),
+            }
+        }
+    }
+}
+
+/// 地图名或路径对应的地图文件
+fn map_path(selection: &str) -> PathBuf {
+    if selection.ends_with(".ron") {
+        PathBuf::from(selection)
+    } else {
+        PathBuf::from(MAP_DIR).join(format!("{}.ron", selection))
+    }
+}
+
+/// 按地图名加载 assets/maps/ 下的地图（地图编辑器使用，随机地图也可以拿来编辑）
+pub fn load_named_map(name: &str) -> Result<MapDef, String> {
+    load_selection(name).map(|(map, _)| map)
+}
+
+/// 随机地图的种子（取 32 位以内，方便在结束画面上抄下来重玩）
+fn random_seed() -> u64 {
+    rand::random::<u32>() as u64
+}
+
+/// 加载选择的地图：地图文件，或按种子生成（random 取一个新种子），返回地图和种子
+fn load_selection(selection: &str) -> Result<(MapDef, Option<u64>), String> {
+    let seed = if selection == RANDOM_MAP {
+        Some(random_seed())
+    } else if let Some(seed) = selection.strip_prefix(SEED_PREFIX) {
+        Some(seed.parse().map_err(|e| format!("种子不合法 {:?}: {}", seed, e))?)
+    } else {
+        None
+    };
+    match seed {
+        Some(seed) => {
+            let arena = Arena::generated(seed);
+            Ok((arena.map, arena.seed))
+        }
+        None => load_map(&map_path(selection)).map(|map| (map, None)),
+    }
+}
+
+fn load_map(path: &Path) -> Result<MapDef, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("无法读取地图文件 {}: {}", path.display(), e))?;
+    MapDef::from_ron(&text)
+}
+
+/// assets/maps/ 下的地图名（按名字排序，默认地图排在最前）
+fn list_maps() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(MAP_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+                .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort_by_key(|name| (name != DEFAULT_MAP, name.clone()));
+    names.push(RANDOM_MAP.to_string());
+    names
+}
+
+/// 初始化地图选择：优先使用命令行参数，其次使用环境变量，返回要插入的资源
+pub fn init_map_choice(args: &[String]) -> MapChoice {
+    let selection = args
+        .iter()
+        .position(|arg| arg == "--map")
+        .and_then(|index| args.get(index + 1).cloned())
+        .or_else(|| std::env::var(MAP_ENV_VAR).ok())
+        .filter(|selection| !selection.is_empty())
+        .unwrap_or_else(|| DEFAULT_MAP.to_string());
+
+    let mut names = list_maps();
+    let path = map_path(&selection);
+    let (map, seed) = match load_selection(&selection) {
+        Ok((map, seed)) => {
+            eprintln!("[地图] 使用地图: {} ({})", selection, map.name);
+            (map, seed)
+        }
+        Err(e) => {
+            eprintln!("[地图] {}，使用内置经典地图", e);
+            (MapDef::default(), None)
+        }
+    };
+    // 直接指定的地图文件不在目录里时也加入轮换
+    let selected = match names.iter().position(|name| map_path(name) == path) {
+        Some(index) => index,
+        None => {
+            names.insert(0, selection);
+            0
+        }
+    };
+    MapChoice { names, selected, map, seed }
+}
+
+/// 菜单中切换地图：同时修改本地选择和当前地图
+pub fn select_next_map(choice: &mut MapChoice, arena: &mut Arena) {
+    choice.select_next();
+    *arena = choice.arena();
+}
+
+/// 把地图保存为 assets/maps/<地图名>.ron（地图编辑器使用，不能覆盖默认地图）
+pub fn save_map(name: &str, map: &MapDef) -> Result<PathBuf, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("地图名只能包含字母、数字、下划线和连字符: {:?}", name));
+    }
+    if name == DEFAULT_MAP {
+        return Err(format!("不能覆盖默认地图 {}", DEFAULT_MAP));
+    }
+    let map = MapDef { name: name.to_string(), ..map.clone() };
+    map.validate()?;
+    let text = map.to_ron()?;
+    std::fs::create_dir_all(MAP_DIR).map_err(|e| format!("无法创建目录 {}: {}", MAP_DIR, e))?;
+    let path = map_path(name);
+    std::fs::write(&path, format!("// 地图编辑器保存的地图\n{}\n", text))
+        .map_err(|e| format!("无法写入 {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// 回到主菜单时恢复本地选择的地图（联机时可能被主机的地图覆盖过），选择随机地图时换一个种子
+pub fn restore_configured_map(mut choice: ResMut<MapChoice>, mut arena: ResMut<Arena>) {
+    if choice.is_random() {
+        choice.reroll();
+    }
+    let configured = choice.arena();
+    if *arena != configured {
+        // 调试输出已禁用: println!("[地图] 恢复本地选择的地图");
+        *arena = configured;
+    }
+}
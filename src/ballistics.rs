@@ -0,0 +1,114 @@
+// 弹道：子弹下坠和风偏（规则 ballistics: true 时生效）
+//
+// 子弹飞行中受重力（向下）和本回合的风（rules.wind_strength 以内的随机向量）影响，
+// bullet_movement_system 每帧按这个加速度积分子弹速度。
+// 命中和破墙都按子弹的落点判定：落点 = 准星 + ½·加速度·飞行时间²，
+// 进攻方需要根据 HUD 上的风向和距离抬高、偏移准星。
+// 风由本地模式或联机主机在每个进攻回合开始时随机生成，随 RoundInfoSync 同步给客户端；
+// 接收方总是用本机的风算对方子弹的落点（不采用开火方的风），BulletSpawn 只带上开火时的进攻回合号：
+// 主机丢弃回合号与自己不一致的射击，客户端在收到这个进攻回合的 RoundInfoSync 之前先把子弹留在队列里。
+
+use bevy::prelude::*;
+use rand::Rng;
+use crate::game_rules::GameRules;
+use crate::gameplay::RoundInfo;
+use crate::projectile::travel_seconds;
+use crate::{FontResource, RoomInfo};
+
+/// 风的竖直分量相对水平分量的比例（以侧风为主，略带上升/下沉气流）
+const VERTICAL_WIND_RATIO: f32 = 0.25;
+
+/// 风向风速文字（进攻方视角）
+#[derive(Component)]
+pub struct WindText;
+
+/// 子弹受到的加速度（重力 + 风），未开启弹道时为零
+pub fn acceleration(rules: &GameRules, wind: Vec2) -> Vec2 {
+    if !rules.ballistics {
+        return Vec2::ZERO;
+    }
+    wind + Vec2::new(0.0, -rules.gravity)
+}
+
+/// 子弹飞到瞄准点时相对直线弹道的偏移
+fn drift(start_pos: Vec2, aim_pos: Vec2, bullet_speed: f32, rules: &GameRules, wind: Vec2) -> Vec2 {
+    let seconds = travel_seconds(start_pos, aim_pos, bullet_speed);
+    0.5 * acceleration(rules, wind) * seconds * seconds
+}
+
+/// 子弹的落点（命中和破墙都按落点判定）
+pub fn landing_point(start_pos: Vec2, aim_pos: Vec2, bullet_speed: f32, rules: &GameRules, wind: Vec2) -> Vec2 {
+    aim_pos + drift(start_pos, aim_pos, bullet_speed, rules, wind)
+}
+
+/// 想打中 target 时应该瞄准的位置（电脑进攻方用来修正下坠和风偏）
+pub fn hold_over(start_pos: Vec2, target: Vec2, bullet_speed: f32, rules: &GameRules, wind: Vec2) -> Vec2 {
+    target - drift(start_pos, target, bullet_speed, rules, wind)
+}
+
+/// 随机生成本回合的风
+pub fn roll_wind(rules: &GameRules) -> Vec2 {
+    if !rules.ballistics || rules.wind_strength <= 0.0 {
+        return Vec2::ZERO;
+    }
+    let mut rng = rand::thread_rng();
+    let horizontal = rng.gen_range(-rules.wind_strength..=rules.wind_strength);
+    let vertical = rng.gen_range(-1.0..=1.0) * rules.wind_strength * VERTICAL_WIND_RATIO;
+    Vec2::new(horizontal, vertical).clamp_length_max(rules.wind_strength)
+}
+
+/// 回合切换时换风（本地模式和主机；客户端等待 RoundInfoSync）
+pub fn roll_wind_on_switch_system(
+    mut round_info: ResMut<RoundInfo>,
+    rules: Res<GameRules>,
+    room_info: Option<Res<RoomInfo>>,
+) {
+    if crate::scoring::is_match_authority(room_info.as_deref()) {
+        round_info.wind = roll_wind(&rules);
+        // 调试输出已禁用: println!("[弹道] 新回合的风: ({:.0}, {:.0})", round_info.wind.x, round_info.wind.y);
+    }
+}
+
+/// 风向箭头（按 8 个方向取最接近的一个）
+fn wind_arrow(wind: Vec2) -> &'static str {
+    const ARROWS: [&str; 8] = ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"];
+    let octant = (wind.y.atan2(wind.x) / std::f32::consts::FRAC_PI_4).round() as i32;
+    ARROWS[octant.rem_euclid(8) as usize]
+}
+
+/// 创建风向文字（放在飞行时间文字下方）
+pub fn spawn_wind_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(String::new(), TextStyle { font, font_size: 18.0, color: Color::rgb(0.6, 0.85, 1.0) }),
+            style: Style { margin: UiRect::top(Val::Px(3.0)), ..default() },
+            ..default()
+        },
+        WindText,
+        crate::AttackerUI,
+    ));
+}
+
+/// 更新风向文字：开启弹道时显示风向、风速和重力，未开启时不显示
+pub fn update_wind_display_system(
+    round_info: Res<RoundInfo>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<WindText>>,
+) {
+    let summary = if !rules.ballistics {
+        String::new()
+    } else if round_info.wind.length() < 1.0 {
+        format!("无风  |  重力 {:.0}", rules.gravity)
+    } else {
+        format!("风 {} {:.0}  |  重力 {:.0}", wind_arrow(round_info.wind), round_info.wind.length(), rules.gravity)
+    };
+    for mut text in text_query.iter_mut() {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value == summary {
+            continue;
+        }
+        section.value = summary.clone();
+        section.style.font = font_resource.font.clone();
+    }
+}
@@ -0,0 +1,318 @@
+// 会合/中继服务器：帮助不在同一局域网的主机和客户端建立 UDP 连接
+//
+// 流程：
+// 1. 主机创建房间后向服务器发送 Register，服务器记录主机的公网地址，并在 Registered 中返回房间密钥
+//    （之后主机带着密钥定期重发作为保活；其他地址只有带上正确的密钥才能接管房间，例如主机的 NAT 映射变了）
+// 2. 客户端输入房间号后发送 Connect，服务器把双方的公网地址通过 PeerInfo 告诉对方
+//    （房间已经有客户端时回复 RoomTaken，不会把主机地址告诉第二个客户端）
+// 3. 双方互发 Punch 打洞，打通后直接通信，服务器不再参与
+// 4. 打洞超时后客户端发送 RelayRequest，服务器回复 RelayReady，
+//    之后双方把游戏报文发给服务器，服务器原样转发给对方
+//
+// 用法：
+//   cargo run --bin rendezvous_server -- [--bind 0.0.0.0:3478]
+// 本地测试时绑定 127.0.0.1 即可，游戏端通过 `--rendezvous 127.0.0.1:3478` 使用。
+
+use bevy_sniper_duel::protocol::{
+    decode_rendezvous, encode_rendezvous, is_rendezvous_datagram, RendezvousMessage, MAX_DATAGRAM_SIZE,
+};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// 默认监听地址
+const DEFAULT_BIND_ADDR: &str = "0.0.0.0:3478";
+/// 房间在多长时间内没有收到主机保活后过期
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+/// 清理过期房间的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 一个登记的房间
+struct Session {
+    host: SocketAddr,
+    secret: u64,        // 房间密钥（只发给登记房间的主机）
+    client: Option<SocketAddr>,
+    relayed: bool,      // 是否已切换到中继
+    last_seen: Instant, // 最近一次收到任一方报文的时间
+}
+
+struct RendezvousServer {
+    socket: UdpSocket,
+    sessions: HashMap<String, Session>,
+}
+
+impl RendezvousServer {
+    fn send(&self, message: &RendezvousMessage, addr: SocketAddr) {
+        match encode_rendezvous(message) {
+            Ok(data) => {
+                if let Err(e) = self.socket.send_to(&data, addr) {
+                    eprintln!("[中继] 发送到 {} 失败: {}", addr, e);
+                }
+            }
+            Err(e) => eprintln!("[中继] 序列化控制报文失败: {}", e),
+        }
+    }
+
+    fn handle_control(&mut self, message: RendezvousMessage, from: SocketAddr) {
+        match message {
+            RendezvousMessage::Register { room_id, secret } => {
+                let session = self.sessions.entry(room_id.clone()).or_insert_with(|| {
+                    println!("[中继] 房间 {} 已登记，主机 {}", room_id, from);
+                    // 0 表示主机还没有密钥，分配的密钥不能是 0
+                    Session { host: from, secret: rand::random::<u64>().max(1), client: None, relayed: false, last_seen: Instant::now() }
+                });
+                if session.host != from {
+                    if secret != session.secret {
+                        eprintln!("[中继] 拒绝 {} 登记房间 {}：房间已由 {} 登记，密钥不符", from, room_id, session.host);
+                        return;
+                    }
+                    // 主机换了地址（NAT 映射变化），旧的客户端信息作废
+                    println!("[中继] 房间 {} 的主机地址变为 {}", room_id, from);
+                    session.host = from;
+                    session.client = None;
+                    session.relayed = false;
+                }
+                session.last_seen = Instant::now();
+                let secret = session.secret;
+                self.send(&RendezvousMessage::Registered { public_addr: from, secret }, from);
+            }
+            RendezvousMessage::Connect { room_id } => {
+                let Some(session) = self.sessions.get_mut(&room_id) else {
+                    self.send(&RendezvousMessage::RoomNotFound { room_id }, from);
+                    return;
+                };
+                if session.client.is_some_and(|client| client != from) {
+                    eprintln!("[中继] 拒绝 {} 连接房间 {}：房间已有客户端", from, room_id);
+                    self.send(&RendezvousMessage::RoomTaken { room_id }, from);
+                    return;
+                }
+                if session.client != Some(from) {
+                    println!("[中继] 客户端 {} 请求连接房间 {}", from, room_id);
+                    session.client = Some(from);
+                    session.relayed = false;
+                }
+                session.last_seen = Instant::now();
+                let host = session.host;
+                self.send(&RendezvousMessage::PeerInfo { room_id: room_id.clone(), peer: host }, from);
+                self.send(&RendezvousMessage::PeerInfo { room_id, peer: from }, host);
+            }
+            RendezvousMessage::RelayRequest { room_id } => {
+                let Some(session) = self.sessions.get_mut(&room_id) else {
+                    self.send(&RendezvousMessage::RoomNotFound { room_id }, from);
+                    return;
+                };
+                if session.client != Some(from) {
+                    eprintln!("[中继] 忽略来自 {} 的中继请求：不是房间 {} 的客户端", from, room_id);
+                    return;
+                }
+                if !session.relayed {
+                    println!("[中继] 房间 {} 打洞失败，开始中继 {} <-> {}", room_id, session.host, from);
+                    session.relayed = true;
+                }
+                session.last_seen = Instant::now();
+                let host = session.host;
+                self.send(&RendezvousMessage::RelayReady { room_id: room_id.clone() }, from);
+                self.send(&RendezvousMessage::RelayReady { room_id }, host);
+            }
+            other => {
+                eprintln!("[中继] 忽略来自 {} 的控制报文: {:?}", from, other);
+            }
+        }
+    }
+
+    /// 转发游戏报文：只转发已切换到中继的房间中双方之间的报文
+    fn relay(&mut self, data: &[u8], from: SocketAddr) {
+        let target = self.sessions.values_mut().find_map(|session| {
+            if !session.relayed {
+                return None;
+            }
+            let target = if session.host == from {
+                session.client
+            } else if session.client == Some(from) {
+                Some(session.host)
+            } else {
+                None
+            };
+            if target.is_some() {
+                session.last_seen = Instant::now();
+            }
+            target
+        });
+        match target {
+            Some(target) => {
+                if let Err(e) = self.socket.send_to(data, target) {
+                    eprintln!("[中继] 转发 {} -> {} 失败: {}", from, target, e);
+                }
+            }
+            None => {
+                // 不属于任何中继会话的报文直接丢弃
+            }
+        }
+    }
+
+    fn sweep(&mut self) {
+        self.sessions.retain(|room_id, session| {
+            let alive = session.last_seen.elapsed() < SESSION_TIMEOUT;
+            if !alive {
+                println!("[中继] 房间 {} 已过期", room_id);
+            }
+            alive
+        });
+    }
+
+    /// 处理一个收到的数据报：控制报文交给 handle_control，其他报文按中继转发
+    fn handle_datagram(&mut self, data: &[u8], from: SocketAddr) {
+        if is_rendezvous_datagram(data) {
+            match decode_rendezvous(data) {
+                Ok(message) => self.handle_control(message, from),
+                Err(e) => eprintln!("[中继] 丢弃来自 {} 的非法控制报文: {}", from, e),
+            }
+        } else {
+            self.relay(data, from);
+        }
+    }
+
+    fn run(&mut self) {
+        let mut buf = [0u8; MAX_DATAGRAM_SIZE + 64];
+        let mut last_sweep = Instant::now();
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((size, from)) => self.handle_datagram(&buf[..size], from),
+                Err(e) => {
+                    // 读超时用于定期清理，其他错误（如 Windows 上对端不可达的 ICMP）只记录
+                    if e.kind() != std::io::ErrorKind::WouldBlock && e.kind() != std::io::ErrorKind::TimedOut {
+                        eprintln!("[中继] 接收数据错误: {}", e);
+                    }
+                }
+            }
+            if last_sweep.elapsed() >= SWEEP_INTERVAL {
+                last_sweep = Instant::now();
+                self.sweep();
+            }
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let bind_addr = args
+        .iter()
+        .position(|arg| arg == "--bind")
+        .and_then(|index| args.get(index + 1).cloned())
+        .unwrap_or_else(|| DEFAULT_BIND_ADDR.to_string());
+
+    let socket = match UdpSocket::bind(&bind_addr) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("[中继] 无法绑定 {}: {}", bind_addr, e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = socket.set_read_timeout(Some(SWEEP_INTERVAL)) {
+        eprintln!("[中继] 无法设置读超时: {}", e);
+    }
+    println!("[中继] 会合/中继服务器已启动，监听 {}", bind_addr);
+
+    RendezvousServer { socket, sessions: HashMap::new() }.run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_sniper_duel::protocol::{encode_message, NetworkMessage, PlayerId};
+
+    /// 在本机随机端口上启动服务器（后台线程），返回服务器地址
+    fn start_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("绑定服务器端口");
+        socket.set_read_timeout(Some(Duration::from_millis(100))).expect("设置读超时");
+        let addr = socket.local_addr().expect("服务器地址");
+        std::thread::spawn(move || RendezvousServer { socket, sessions: HashMap::new() }.run());
+        addr
+    }
+
+    fn peer_socket() -> UdpSocket {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("绑定端口");
+        socket.set_read_timeout(Some(Duration::from_secs(2))).expect("设置读超时");
+        socket
+    }
+
+    fn send(socket: &UdpSocket, message: &RendezvousMessage, server: SocketAddr) {
+        socket.send_to(&encode_rendezvous(message).expect("编码控制报文"), server).expect("发送控制报文");
+    }
+
+    fn recv_raw(socket: &UdpSocket) -> Vec<u8> {
+        let mut buf = [0u8; MAX_DATAGRAM_SIZE + 64];
+        let (size, _) = socket.recv_from(&mut buf).expect("等待服务器的报文超时");
+        buf[..size].to_vec()
+    }
+
+    fn recv(socket: &UdpSocket) -> RendezvousMessage {
+        decode_rendezvous(&recv_raw(socket)).expect("解码控制报文")
+    }
+
+    fn register(host: &UdpSocket, server: SocketAddr, room_id: &str, secret: u64) -> u64 {
+        send(host, &RendezvousMessage::Register { room_id: room_id.to_string(), secret }, server);
+        match recv(host) {
+            RendezvousMessage::Registered { public_addr, secret } => {
+                assert_eq!(public_addr, host.local_addr().unwrap());
+                assert_ne!(secret, 0);
+                secret
+            }
+            other => panic!("应回复 Registered，收到 {:?}", other),
+        }
+    }
+
+    #[test]
+    fn register_connect_and_relay() {
+        let server = start_server();
+        let (host, client) = (peer_socket(), peer_socket());
+        let room_id = "ROOM_1".to_string();
+        let secret = register(&host, server, &room_id, 0);
+        // 保活时带上密钥，密钥不变
+        assert_eq!(register(&host, server, &room_id, secret), secret);
+
+        send(&client, &RendezvousMessage::Connect { room_id: room_id.clone() }, server);
+        assert_eq!(recv(&client), RendezvousMessage::PeerInfo { room_id: room_id.clone(), peer: host.local_addr().unwrap() });
+        assert_eq!(recv(&host), RendezvousMessage::PeerInfo { room_id: room_id.clone(), peer: client.local_addr().unwrap() });
+
+        send(&client, &RendezvousMessage::RelayRequest { room_id: room_id.clone() }, server);
+        assert_eq!(recv(&client), RendezvousMessage::RelayReady { room_id: room_id.clone() });
+        assert_eq!(recv(&host), RendezvousMessage::RelayReady { room_id });
+
+        // 游戏报文原样转发给对方
+        let payload = encode_message(&NetworkMessage::HealthUpdate { player_id: PlayerId::Player1, health: 75.0 }).unwrap();
+        client.send_to(&payload, server).unwrap();
+        assert_eq!(recv_raw(&host), payload);
+        let reply = encode_message(&NetworkMessage::HealthUpdate { player_id: PlayerId::Player2, health: 50.0 }).unwrap();
+        host.send_to(&reply, server).unwrap();
+        assert_eq!(recv_raw(&client), reply);
+    }
+
+    #[test]
+    fn room_cannot_be_taken_over() {
+        let server = start_server();
+        let (host, client, intruder) = (peer_socket(), peer_socket(), peer_socket());
+        let room_id = "ROOM_2".to_string();
+        let secret = register(&host, server, &room_id, 0);
+
+        // 不知道密钥的地址不能接管房间，服务器不回复
+        intruder.set_read_timeout(Some(Duration::from_millis(300))).unwrap();
+        send(&intruder, &RendezvousMessage::Register { room_id: room_id.clone(), secret: 0 }, server);
+        send(&intruder, &RendezvousMessage::Register { room_id: room_id.clone(), secret: secret.wrapping_add(1) }, server);
+        let mut buf = [0u8; 64];
+        assert!(intruder.recv_from(&mut buf).is_err());
+
+        send(&client, &RendezvousMessage::Connect { room_id: room_id.clone() }, server);
+        assert_eq!(recv(&client), RendezvousMessage::PeerInfo { room_id: room_id.clone(), peer: host.local_addr().unwrap() });
+        assert!(matches!(recv(&host), RendezvousMessage::PeerInfo { .. }));
+
+        // 已有客户端时第二个客户端被拒绝，也拿不到主机地址
+        intruder.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        send(&intruder, &RendezvousMessage::Connect { room_id: room_id.clone() }, server);
+        assert_eq!(recv(&intruder), RendezvousMessage::RoomTaken { room_id: room_id.clone() });
+
+        // 主机带着密钥换了地址：允许，旧客户端作废
+        let moved_host = peer_socket();
+        assert_eq!(register(&moved_host, server, &room_id, secret), secret);
+    }
+}
@@ -0,0 +1,589 @@
+// 单人练习：电脑对手
+//
+// 单人练习沿用本地模式（同一台机器上的两个玩家实体），玩家固定为 Player1，电脑为 Player2，
+// 开局玩家先进攻。两种电脑都只根据对应视角能看到的东西做决定，并且有反应延迟。
+//
+// 电脑防守方（DefenderAI，defender_ai_system）：
+// - 看进攻方的准星和激光，不读取进攻方的按键
+// - 平时往完好的墙体后面移动，远离准星
+// - 准星停在身上时，在动作冷却允许的范围内下蹲或侧躲
+//
+// 电脑进攻方（AttackerAI，attacker_ai_system）：
+// - 只能透过破洞看到防守方，看到就瞄准露出的部分开枪
+// - 看不到时挑选要打碎的砖块来打开视线，最后一发子弹留给看得见的目标
+// - 通过 move_crosshair 移动准星、通过 AttackerTriggerEvent 开枪，和键盘操作走同一套逻辑
+//
+// 画面只显示玩家当前角色的视角（全屏），电脑那一侧的相机关闭。
+
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::PrimaryWindow;
+use rand::Rng;
+use std::collections::VecDeque;
+use crate::{PlayerId, PlayerRole, RoomInfo};
+use crate::{PLAYER_SIZE, BRICK_WIDTH, BRICK_HEIGHT};
+use crate::arena::Arena;
+use crate::utility::UtilityEffect;
+use crate::stamina::Stamina;
+use crate::game_rules::GameRules;
+use crate::gameplay::{
+    covered_by_prop, laser_segment_rect_intersects, move_crosshair, ActionCooldown, AttackerAI, AttackerTriggerEvent, Collider,
+    CoverQuery, CrosshairOffset, CursorPosition, DefenderAI, DefenderCamera, DodgeAction, PlayerActionEvent, PlayerCamera,
+    sight_openings, RoundInfo, ViewLayer, WallSegment,
+};
+
+/// 单人练习中玩家控制的角色
+pub const HUMAN_PLAYER: PlayerId = PlayerId::Player1;
+/// 单人练习中电脑控制的角色
+pub const BOT_PLAYER: PlayerId = PlayerId::Player2;
+
+/// 每次重新选位置时评估的候选点数量
+const PLAN_CANDIDATES: usize = 16;
+/// 离目标多近算到达（像素）
+const ARRIVE_DISTANCE: f32 = 2.0;
+/// 准星离身体中心多近算"正在瞄我"（像素）
+const THREAT_RADIUS: f32 = PLAYER_SIZE.y * 0.6;
+/// 准星高于身体中心多少（相对半身高）算瞄头，此时下蹲；否则侧躲
+const HEAD_AIM_RATIO: f32 = 0.3;
+/// 准星被完好墙体挡住（进攻方看不到自己）时，躲避概率打折
+const BLIND_AIM_DODGE_FACTOR: f64 = 0.5;
+/// 每块露出身体的破洞扣多少分
+const EXPOSURE_PENALTY: f32 = 2.0;
+/// 离准星的距离最多加到多少分（每 100 像素 1 分）
+const CROSSHAIR_DISTANCE_CAP: f32 = 300.0;
+/// 准星离瞄准点多近算瞄准到位（像素）
+const ON_TARGET_DISTANCE: f32 = 4.0;
+/// 至少留几发子弹给看得见的目标（不用来破墙）
+const RESERVED_BULLETS: i32 = 1;
+/// 墙上已经有这么多破洞时不再破墙，等防守方自己露出来
+const MAX_HOLES_BEFORE_WAIT: usize = 9;
+/// 回合剩余时间少于该值时，把剩下的子弹都打向最可能的位置
+const LAST_CHANCE_SECONDS: f32 = 4.0;
+/// 开枪间隔在射击冷却之外多等一点，避免扳机事件因为帧时间误差被射击系统忽略
+const TRIGGER_MARGIN_SECONDS: f64 = 0.05;
+/// 破墙时离已有破洞的距离最多加到多少分（每 100 像素 1 分）
+const HOLE_SPREAD_CAP: f32 = 200.0;
+
+/// 电脑防守方自己的实体：位置、角色、身体、动作冷却、当前动作、AI 状态和体力
+type DefenderBotQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static PlayerRole,
+        &'static PlayerId,
+        &'static Collider,
+        &'static mut ActionCooldown,
+        &'static mut DodgeAction,
+        &'static mut DefenderAI,
+        &'static Stamina,
+    ),
+>;
+
+/// 电脑防守方看到的墙和掩体（不含玩家实体）
+type DefenderSightQueries<'w, 's> = (
+    Query<'w, 's, (&'static Transform, &'static Collider, &'static WallSegment), Without<PlayerId>>,
+    CoverQuery<'w, 's, Without<PlayerId>>,
+);
+
+/// 电脑难度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BotDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl BotDifficulty {
+    pub fn label(self) -> &'static str {
+        match self {
+            BotDifficulty::Easy => "简单",
+            BotDifficulty::Normal => "普通",
+            BotDifficulty::Hard => "困难",
+        }
+    }
+
+    /// 菜单上点击难度按钮时切换到下一个难度
+    pub fn next(self) -> Self {
+        match self {
+            BotDifficulty::Easy => BotDifficulty::Normal,
+            BotDifficulty::Normal => BotDifficulty::Hard,
+            BotDifficulty::Hard => BotDifficulty::Easy,
+        }
+    }
+
+    /// 看到准星移动、或看到防守方露出的反应延迟（秒）
+    pub fn reaction_seconds(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 0.6,
+            BotDifficulty::Normal => 0.35,
+            BotDifficulty::Hard => 0.15,
+        }
+    }
+
+    /// 准星透过破洞瞄着自己时躲避的概率
+    pub fn dodge_chance(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 0.4,
+            BotDifficulty::Normal => 0.7,
+            BotDifficulty::Hard => 0.95,
+        }
+    }
+
+    /// 重新选择位置的间隔（秒）
+    pub fn replan_seconds(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 2.5,
+            BotDifficulty::Normal => 1.5,
+            BotDifficulty::Hard => 0.8,
+        }
+    }
+
+    /// 选位置（防守）或选砖块（进攻）时的随机扰动，越大越不会找掩体、破墙越盲目
+    pub fn plan_noise(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 3.0,
+            BotDifficulty::Normal => 1.5,
+            BotDifficulty::Hard => 0.3,
+        }
+    }
+
+    /// 进攻时的最大瞄准误差（像素）
+    pub fn aim_error(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 18.0,
+            BotDifficulty::Normal => 9.0,
+            BotDifficulty::Hard => 3.0,
+        }
+    }
+
+    /// 准星到位后稳定多久才开枪（秒）
+    pub fn settle_seconds(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 0.6,
+            BotDifficulty::Normal => 0.35,
+            BotDifficulty::Hard => 0.15,
+        }
+    }
+
+    /// 看不到防守方多久之后开始破墙（秒）
+    pub fn breach_patience_seconds(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 5.0,
+            BotDifficulty::Normal => 3.0,
+            BotDifficulty::Hard => 1.5,
+        }
+    }
+}
+
+/// 电脑进攻方当前的打算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttackerIntent {
+    Wait,          // 没有目标，准星移到防守方最可能出现的位置等待
+    Breach(Vec2),  // 打碎这块砖来打开视线
+    Kill(Vec2),    // 向防守方（看到的或最可能的位置）开枪
+}
+
+/// 单人练习设置（主菜单写入，setup_game 和电脑系统读取）
+#[derive(Resource, Default)]
+pub struct SinglePlayerSettings {
+    pub enabled: bool,
+    pub difficulty: BotDifficulty,
+}
+
+/// 是否处于单人练习中
+fn is_single_player(settings: &SinglePlayerSettings, room_info: &RoomInfo) -> bool {
+    settings.enabled && !room_info.is_connected
+}
+
+/// 站在某个位置时，有多少块破洞露出身体（进攻方视角中的破损砖块）
+fn exposure_at(position: Vec2, body_size: Vec2, holes: &[Vec2]) -> usize {
+    let reach = (body_size + Vec2::new(BRICK_WIDTH, BRICK_HEIGHT)) / 2.0;
+    holes
+        .iter()
+        .filter(|hole| (**hole - position).abs().cmplt(reach).all())
+        .count()
+}
+
+/// 模拟反应延迟：记录每帧看到的内容，返回 reaction 秒之前看到的那一帧（还没到时间返回 None）
+fn perceive_delayed<T: Copy>(history: &mut VecDeque<(f64, T)>, now: f64, seen: T, reaction: f64) -> Option<T> {
+    history.push_back((now, seen));
+    while history.get(1).is_some_and(|(t, _)| now - t >= reaction) {
+        history.pop_front();
+    }
+    history.front().filter(|(t, _)| now - t >= reaction).map(|(_, value)| *value)
+}
+
+/// 透过破洞能看到防守方身体的哪一部分：返回露出面积最大的那一块的中心
+fn visible_part(defender: Vec2, body_size: Vec2, holes: &[Vec2]) -> Option<Vec2> {
+    let half_brick = Vec2::new(BRICK_WIDTH, BRICK_HEIGHT) / 2.0;
+    let (body_min, body_max) = (defender - body_size / 2.0, defender + body_size / 2.0);
+    holes
+        .iter()
+        .filter_map(|hole| {
+            let min = (*hole - half_brick).max(body_min);
+            let max = (*hole + half_brick).min(body_max);
+            let size = max - min;
+            (size.x > 0.0 && size.y > 0.0).then_some((size.x * size.y, (min + max) / 2.0))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, center)| center)
+}
+
+/// 每次瞄准随机一个误差，难度越低误差越大
+fn roll_aim_error(difficulty: BotDifficulty) -> Vec2 {
+    let max_error = difficulty.aim_error();
+    let mut rng = rand::thread_rng();
+    Vec2::new(rng.gen_range(-max_error..=max_error), rng.gen_range(-max_error..=max_error))
+}
+
+/// 选一块要打碎的砖：靠近防守方最可能的位置、离已有破洞远（打开新的视线）、准星移过去不太远
+fn choose_breach_brick(ai: &AttackerAI, aim_speed: f32, crosshair: Vec2, guess: Vec2, intact: &[Vec2], holes: &[Vec2]) -> Option<Vec2> {
+    let mut rng = rand::thread_rng();
+    let noise = ai.difficulty.plan_noise();
+    intact
+        .iter()
+        .map(|brick| {
+            let near_guess = -(*brick - guess).length() / 100.0;
+            let spread = holes
+                .iter()
+                .map(|hole| (*hole - *brick).length())
+                .fold(HOLE_SPREAD_CAP, f32::min)
+                / 100.0;
+            // 看到过防守方就围着那里打，没看到过才靠分散破洞去找
+            let spread_weight = if ai.last_seen.is_some() { 0.2 } else { 1.0 };
+            let travel = (*brick - crosshair).length() / aim_speed;
+            let score = near_guess + spread * spread_weight - travel + rng.gen_range(-noise..=noise);
+            (score, *brick)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, brick)| brick)
+}
+
+/// 在防守范围内选一个新位置：优先没有破洞的地方，其次远离准星，也不愿意跑太远
+fn plan_target(ai: &DefenderAI, move_speed: f32, current: Vec2, bounds: (Vec2, Vec2), body_size: Vec2, crosshair: Option<Vec2>, holes: &[Vec2]) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    let noise = ai.difficulty.plan_noise();
+    let score = |candidate: Vec2, rng: &mut rand::rngs::ThreadRng| {
+        let exposure = exposure_at(candidate, body_size, holes) as f32;
+        let away_from_crosshair = crosshair
+            .map(|c| (candidate - c).length().min(CROSSHAIR_DISTANCE_CAP) / 100.0)
+            .unwrap_or(0.0);
+        let travel = (candidate - current).length() / move_speed;
+        away_from_crosshair - exposure * EXPOSURE_PENALTY - travel + rng.gen_range(-noise..=noise)
+    };
+
+    let mut best = current;
+    let mut best_score = score(current, &mut rng);
+    for _ in 0..PLAN_CANDIDATES {
+        let candidate = Vec2::new(
+            rng.gen_range(bounds.0.x..=bounds.1.x),
+            rng.gen_range(bounds.0.y..=bounds.1.y),
+        );
+        let candidate_score = score(candidate, &mut rng);
+        if candidate_score > best_score {
+            best = candidate;
+            best_score = candidate_score;
+        }
+    }
+    best
+}
+
+/// 单人练习中按电脑当前的角色挂上 DefenderAI 或 AttackerAI（换回合时由 switch_roles_system 移除）
+pub fn attach_bot_ai_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<SinglePlayerSettings>,
+    room_info: Res<RoomInfo>,
+    without_defender_ai: Query<(Entity, &PlayerId, &PlayerRole), Without<DefenderAI>>,
+    without_attacker_ai: Query<(Entity, &PlayerId, &PlayerRole), Without<AttackerAI>>,
+) {
+    if !is_single_player(&settings, &room_info) {
+        return;
+    }
+    for (entity, player_id, role) in without_defender_ai.iter() {
+        if *player_id == BOT_PLAYER && matches!(role, PlayerRole::Defender) {
+            commands.entity(entity).insert(DefenderAI::new(settings.difficulty));
+            // 调试输出已禁用: println!("[单人练习] 电脑开始防守，难度: {}", settings.difficulty.label());
+        }
+    }
+    for (entity, player_id, role) in without_attacker_ai.iter() {
+        if *player_id == BOT_PLAYER && matches!(role, PlayerRole::Attacker) {
+            commands.entity(entity).insert(AttackerAI::new(settings.difficulty, time.elapsed_seconds_f64()));
+            // 调试输出已禁用: println!("[单人练习] 电脑开始进攻，难度: {}", settings.difficulty.label());
+        }
+    }
+}
+
+/// 电脑防守方：移动到掩体后面，被瞄准时躲避
+pub fn defender_ai_system(
+    time: Res<Time>,
+    cursor_pos: Res<CursorPosition>,
+    mut bot_query: DefenderBotQuery,
+    attacker_query: Query<(&Transform, &PlayerRole), Without<DefenderAI>>,
+    (wall_query, cover_query): DefenderSightQueries,
+    mut events: EventWriter<PlayerActionEvent>,
+    (rules, arena): (Res<GameRules>, Res<Arena>),
+) {
+    let now = time.elapsed_seconds_f64();
+    // 速度与真人防守方相同
+    let move_speed = rules.player_move_speed * 0.8;
+    let Some(attacker_pos) = attacker_query
+        .iter()
+        .find(|(_, role)| matches!(role, PlayerRole::Attacker))
+        .map(|(transform, _)| transform.translation.truncate())
+    else {
+        return;
+    };
+
+    // 进攻方视角中的破洞（前后几层都打穿才算），以及挡住激光的完好砖块
+    let mut intact_bricks = Vec::new();
+    let mut segments = Vec::new();
+    for (transform, collider, segment) in wall_query.iter() {
+        if segment.view_layer != ViewLayer::AttackerView {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        // 玻璃挡不住视线和激光，当作破洞
+        if segment.blocks_sight() {
+            intact_bricks.push((position - collider.size / 2.0, position + collider.size / 2.0));
+        }
+        segments.push((position, segment));
+    }
+    // 掩体后面的破洞露不出身体
+    let mut holes = sight_openings(&segments);
+    holes.retain(|hole| !covered_by_prop(*hole, ViewLayer::AttackerView, &cover_query));
+    // 掩体和完好砖块一样挡住激光
+    for (transform, cover, collider) in cover_query.iter() {
+        if cover.view_layer == ViewLayer::AttackerView {
+            let position = transform.translation.truncate();
+            intact_bricks.push((position - collider.size / 2.0, position + collider.size / 2.0));
+        }
+    }
+
+    for (mut transform, role, player_id, collider, mut cooldown, mut dodge_action, mut ai, stamina) in bot_query.iter_mut() {
+        if !matches!(role, PlayerRole::Defender) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let bounds = arena.defender_bounds(collider.size);
+        let reaction = ai.difficulty.reaction_seconds();
+        let crosshair = perceive_delayed(&mut ai.crosshair_history, now, cursor_pos.0, reaction);
+
+        // 激光没被完好砖块挡住，说明进攻方能透过破洞看到准星所指的地方
+        let aimed_through_hole = crosshair.is_some_and(|c| {
+            !intact_bricks.iter().any(|rect| laser_segment_rect_intersects(attacker_pos, c, *rect))
+        });
+        let threatened = crosshair.is_some_and(|c| (c - position).length() < THREAT_RADIUS);
+        if !threatened {
+            ai.threat_handled = false;
+        }
+
+        // 被瞄准时立即换位置，否则按计时器定期重新选择
+        ai.move_timer.tick(time.delta());
+        let new_threat = threatened && !ai.threat_handled;
+        if ai.target.is_none() || ai.move_timer.just_finished() || new_threat {
+            ai.target = Some(plan_target(&ai, move_speed, position, bounds, collider.size, crosshair, &holes));
+        }
+
+        // 每次被瞄准只决定一次是否躲避（冷却没好就只换位置）
+        let cooldown_ready = now - cooldown.last_action_time >= cooldown.cooldown_duration;
+        if new_threat {
+            ai.threat_handled = true;
+        }
+        if new_threat && cooldown_ready && *dodge_action == DodgeAction::None {
+            let mut chance = ai.difficulty.dodge_chance();
+            if !aimed_through_hole {
+                chance *= BLIND_AIM_DODGE_FACTOR;
+            }
+            if let Some(c) = crosshair.filter(|_| rand::thread_rng().gen_bool(chance)) {
+                // 瞄头或体力不够侧躲就下蹲，否则往远离准星的一侧躲（靠墙边时往另一侧）
+                let can_left = position.x - rules.side_dodge_distance >= bounds.0.x;
+                let can_right = position.x + rules.side_dodge_distance <= bounds.1.x;
+                let action = if c.y - position.y > collider.size.y / 2.0 * HEAD_AIM_RATIO || !stamina.can_side_dodge(&rules) {
+                    DodgeAction::Crouch
+                } else if (c.x >= position.x && can_left) || !can_right {
+                    DodgeAction::SideLeft
+                } else {
+                    DodgeAction::SideRight
+                };
+                *dodge_action = action;
+                events.send(PlayerActionEvent { player_id: *player_id, action });
+                cooldown.last_action_time = now;
+            }
+        }
+
+        // 移动到目标位置
+        let to_target = ai.target.unwrap_or(position) - position;
+        let distance = to_target.length();
+        if distance <= ARRIVE_DISTANCE {
+            ai.direction = 0.0;
+            continue;
+        }
+        let step = (move_speed * time.delta_seconds()).min(distance);
+        let new_position = (position + to_target / distance * step).clamp(bounds.0, bounds.1);
+        ai.direction = to_target.x.signum();
+        transform.translation.x = new_position.x;
+        transform.translation.y = new_position.y;
+    }
+}
+
+/// 电脑进攻方：透过破洞找防守方，找不到就破墙，管理子弹和回合时间
+pub fn attacker_ai_system(
+    time: Res<Time>,
+    (round_info, rules, arena): (Res<RoundInfo>, Res<GameRules>, Res<Arena>),
+    mut aim: (ResMut<CrosshairOffset>, ResMut<CursorPosition>),
+    mut bot_query: Query<(&PlayerId, &PlayerRole, &mut AttackerAI)>,
+    defender_query: Query<(&Transform, &PlayerRole, &Collider), Without<AttackerAI>>,
+    (wall_query, cover_query, utility_query): (Query<(&Transform, &WallSegment)>, CoverQuery, Query<&UtilityEffect>),
+    mut trigger_events: EventWriter<AttackerTriggerEvent>,
+) {
+    let Ok((player_id, role, mut ai)) = bot_query.get_single_mut() else {
+        return;
+    };
+    if !matches!(role, PlayerRole::Attacker) || *player_id != round_info.current_attacker {
+        return;
+    }
+    if round_info.is_switching || round_info.bullets_left <= 0 || round_info.round_timer.finished() {
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    let (crosshair_offset, cursor_pos) = (&mut *aim.0, &mut *aim.1);
+    let crosshair = cursor_pos.0;
+
+    // 进攻方视角中的破洞和完好砖块
+    let mut intact = Vec::new();
+    let mut segments = Vec::new();
+    let mut broken = 0;
+    for (transform, segment) in wall_query.iter() {
+        if segment.view_layer != ViewLayer::AttackerView {
+            continue;
+        }
+        segments.push((transform.translation.truncate(), segment));
+        // 玻璃能看穿但要先打碎；钢板打不碎，不用来破墙
+        broken += usize::from(segment.damaged);
+        if !segment.damaged && segment.spec.hit_points().is_some() && !segment.spec.material.is_see_through() {
+            intact.push(transform.translation.truncate());
+        }
+    }
+    // 前后几层都打穿的破洞才看得到后面，被掩体挡住的破洞看不到后面的防守方
+    let mut holes = sight_openings(&segments);
+    holes.retain(|hole| !covered_by_prop(*hole, ViewLayer::AttackerView, &cover_query));
+    // 烟雾后面的破洞也看不到东西
+    holes.retain(|hole| !crate::utility::in_smoke(*hole, utility_query.iter()));
+
+    // 透过破洞看防守方（带反应延迟）；分不清假人和真人，看到假人也会当成防守方
+    let seen = defender_query
+        .iter()
+        .find(|(_, role, _)| matches!(role, PlayerRole::Defender))
+        .and_then(|(transform, _, collider)| visible_part(transform.translation.truncate(), collider.size, &holes))
+        .or_else(|| {
+            crate::utility::decoy_positions(utility_query.iter())
+                .into_iter()
+                .find_map(|decoy| visible_part(decoy, PLAYER_SIZE, &holes))
+        });
+    let reaction = ai.difficulty.reaction_seconds();
+    let perceived = perceive_delayed(&mut ai.defender_history, now, seen, reaction).flatten();
+    if let Some(position) = perceived {
+        ai.last_seen = Some(position);
+        ai.searching_since = now;
+    }
+
+    // 决定这一帧的打算：看得见就打；快没时间就盲打；否则在子弹有富余时破墙
+    let guess = ai.last_seen.unwrap_or(arena.defender_spawn().truncate());
+    let previous_intent = ai.intent;
+    ai.intent = if let Some(position) = perceived {
+        AttackerIntent::Kill(position)
+    } else if round_info.round_timer.remaining_secs() < LAST_CHANCE_SECONDS {
+        AttackerIntent::Kill(guess)
+    } else if let AttackerIntent::Breach(brick) = previous_intent {
+        // 已经选好的砖块，打出去之前不换
+        AttackerIntent::Breach(brick)
+    } else if round_info.bullets_left > RESERVED_BULLETS
+        && broken < MAX_HOLES_BEFORE_WAIT
+        && now - ai.searching_since >= ai.difficulty.breach_patience_seconds()
+    {
+        choose_breach_brick(&ai, rules.aim_speed, crosshair, guess, &intact, &holes)
+            .map_or(AttackerIntent::Wait, AttackerIntent::Breach)
+    } else {
+        AttackerIntent::Wait
+    };
+    if std::mem::discriminant(&previous_intent) != std::mem::discriminant(&ai.intent) {
+        ai.on_target_since = None;
+        ai.aim_error = roll_aim_error(ai.difficulty);
+    }
+
+    // 移动准星（速度与键盘瞄准相同）
+    let target = match ai.intent {
+        AttackerIntent::Wait => guess,
+        AttackerIntent::Breach(brick) => brick,
+        AttackerIntent::Kill(position) => position + ai.aim_error,
+    };
+    // 弹道规则：按风和下坠抬高、偏移准星（电脑固定使用栓动步枪）
+    let target = crate::ballistics::hold_over(arena.aim_origin(), target, rules.bullet_speed, &rules, round_info.wind);
+    let to_target = target - crosshair;
+    let max_step = rules.aim_speed * time.delta_seconds();
+    let move_direction = if max_step > 0.0 { to_target / max_step } else { Vec2::ZERO };
+    let aim_world_pos = move_crosshair(crosshair_offset, cursor_pos, arena.aim_origin(), move_direction, max_step);
+
+    // 准星到位并稳定一会儿后开枪
+    if matches!(ai.intent, AttackerIntent::Wait) || (target - aim_world_pos).length() > ON_TARGET_DISTANCE {
+        ai.on_target_since = None;
+        return;
+    }
+    let on_target_since = *ai.on_target_since.get_or_insert(now);
+    let cooldown_ready = ai
+        .last_shot
+        .is_none_or(|t| now - t >= rules.shoot_cooldown_seconds as f64 + TRIGGER_MARGIN_SECONDS);
+    if now - on_target_since < ai.difficulty.settle_seconds() || !cooldown_ready {
+        return;
+    }
+    trigger_events.send(AttackerTriggerEvent { player_id: *player_id });
+    // 调试输出已禁用: println!("[单人练习] 电脑开枪: {:?}", ai.intent);
+    ai.aim_error = roll_aim_error(ai.difficulty);
+    ai.last_shot = Some(now);
+    ai.on_target_since = None;
+    ai.searching_since = now;
+    ai.intent = AttackerIntent::Wait;
+}
+
+/// 单人练习只显示玩家当前角色的视角：进攻时显示进攻方相机，防守时显示防守方相机，铺满整个窗口
+pub fn single_player_view_system(
+    settings: Res<SinglePlayerSettings>,
+    room_info: Res<RoomInfo>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<(&PlayerId, &PlayerRole)>,
+    mut camera_query: Query<(&mut Camera, Option<&DefenderCamera>), With<PlayerCamera>>,
+) {
+    if !is_single_player(&settings, &room_info) {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.x == 0 || window_size.y == 0 {
+        return;
+    }
+    let human_is_defender = player_query
+        .iter()
+        .any(|(id, role)| *id == HUMAN_PLAYER && matches!(role, PlayerRole::Defender));
+
+    for (mut camera, defender_camera) in camera_query.iter_mut() {
+        let show = defender_camera.is_some() == human_is_defender;
+        if camera.is_active != show {
+            camera.is_active = show;
+        }
+        let is_full_window = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == UVec2::ZERO && viewport.physical_size == window_size
+        });
+        if show && !is_full_window {
+            camera.viewport = Some(Viewport {
+                physical_position: UVec2::ZERO,
+                physical_size: window_size,
+                depth: 0.0..1.0,
+            });
+        }
+    }
+}
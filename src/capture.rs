@@ -0,0 +1,342 @@
+// 网络抓包与协议检查工具
+//
+// 抓包模式默认关闭。启动时通过 `--capture <文件>` 参数或环境变量 `SNIPER_CAPTURE=<文件>` 开启，
+// 开启后每个收发的数据报都会以一行 RON 的形式追加写入文件（时间戳、方向、对端地址、解码后的消息）。
+// 发送的报文在 send_to 成功后才记录；收到但无法解码的数据报记录原始字节和解码错误（类型名为 Undecodable）。
+//
+// 离线查看抓包文件：
+//   bevy_sniper_duel inspect print <抓包文件> [--kind 消息类型] [--dir sent|recv]
+//   bevy_sniper_duel inspect diff <主机抓包> <客户端抓包> [--kind 消息类型] [--ignore 类型1,类型2]
+
+use serde::{Serialize, Deserialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::network_game::NetworkMessage;
+
+/// 抓包开关的环境变量名
+pub const CAPTURE_ENV_VAR: &str = "SNIPER_CAPTURE";
+
+/// diff 时为跳过丢包向前查找的最大报文数
+const DIFF_LOSS_WINDOW: usize = 16;
+
+/// 报文方向
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+    Sent,
+    Received,
+}
+
+/// 抓包文件中的一条记录
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CaptureRecord {
+    pub seq: u64,           // 本进程内的记录序号
+    pub timestamp_ms: u64,  // UNIX 时间戳（毫秒）
+    pub direction: CaptureDirection,
+    pub peer: String,       // 对端地址
+    pub payload: CapturePayload,
+}
+
+/// 记录的报文内容
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum CapturePayload {
+    Message(Box<NetworkMessage>),
+    Undecodable { bytes: Vec<u8>, error: String }, // 无法解码的数据报（原始字节和解码错误）
+}
+
+/// 全局抓包写入器（接收线程没有 Bevy 资源可用，所以用全局变量）
+static CAPTURE_WRITER: OnceLock<Option<Mutex<LineWriter<File>>>> = OnceLock::new();
+static CAPTURE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 初始化抓包：优先使用命令行参数 `--capture <文件>`，其次使用环境变量
+/// 未指定时抓包保持关闭，record 调用没有任何开销
+pub fn init_capture(args: &[String]) {
+    let path = args
+        .iter()
+        .position(|arg| arg == "--capture")
+        .and_then(|index| args.get(index + 1).cloned())
+        .or_else(|| std::env::var(CAPTURE_ENV_VAR).ok())
+        .filter(|path| !path.is_empty());
+
+    let writer = path.and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => {
+                eprintln!("[抓包] 已开启，写入文件: {}", path);
+                Some(Mutex::new(LineWriter::new(file)))
+            }
+            Err(e) => {
+                eprintln!("[抓包] 无法打开抓包文件 {}: {}", path, e);
+                None
+            }
+        }
+    });
+    let _ = CAPTURE_WRITER.set(writer);
+}
+
+/// 记录一条收发的消息（抓包关闭时直接返回）
+pub fn record(direction: CaptureDirection, peer: SocketAddr, message: &NetworkMessage) {
+    if capture_enabled() {
+        write_record(direction, peer, CapturePayload::Message(Box::new(message.clone())));
+    }
+}
+
+/// 记录一个收到但无法解码的数据报
+pub fn record_undecodable(peer: SocketAddr, data: &[u8], error: &str) {
+    if capture_enabled() {
+        write_record(CaptureDirection::Received, peer, CapturePayload::Undecodable { bytes: data.to_vec(), error: error.to_string() });
+    }
+}
+
+fn capture_enabled() -> bool {
+    matches!(CAPTURE_WRITER.get(), Some(Some(_)))
+}
+
+fn write_record(direction: CaptureDirection, peer: SocketAddr, payload: CapturePayload) {
+    let Some(Some(writer)) = CAPTURE_WRITER.get() else {
+        return;
+    };
+    let record = CaptureRecord {
+        seq: CAPTURE_SEQ.fetch_add(1, Ordering::Relaxed),
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        direction,
+        peer: peer.to_string(),
+        payload,
+    };
+    match ron::ser::to_string(&record) {
+        Ok(line) => {
+            let Ok(mut writer) = writer.lock() else {
+                return;
+            };
+            if let Err(e) = writeln!(writer, "{}", line) {
+                eprintln!("[抓包] 写入失败: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[抓包] 序列化记录失败: {}", e),
+    }
+}
+
+/// 报文类型名（消息的枚举变体名，无法解码的数据报为 Undecodable），用于过滤
+/// 逐个列出变体，类型名不随 Debug 输出的格式变化；新增消息时编译器会提示在这里补上
+pub fn message_kind(payload: &CapturePayload) -> &'static str {
+    let CapturePayload::Message(message) = payload else {
+        return "Undecodable";
+    };
+    match message.as_ref() {
+        NetworkMessage::RoomDiscoveryRequest => "RoomDiscoveryRequest",
+        NetworkMessage::RoomDiscoveryResponse { .. } => "RoomDiscoveryResponse",
+        NetworkMessage::JoinRequest { .. } => "JoinRequest",
+        NetworkMessage::JoinAccept { .. } => "JoinAccept",
+        NetworkMessage::JoinReject => "JoinReject",
+        NetworkMessage::GameState { .. } => "GameState",
+        NetworkMessage::PlayerInput { .. } => "PlayerInput",
+        NetworkMessage::PlayerHit { .. } => "PlayerHit",
+        NetworkMessage::GameOver { .. } => "GameOver",
+        NetworkMessage::StartGame { .. } => "StartGame",
+        NetworkMessage::RoundInfoSync { .. } => "RoundInfoSync",
+        NetworkMessage::SwitchRoles { .. } => "SwitchRoles",
+        NetworkMessage::CrosshairPosition { .. } => "CrosshairPosition",
+        NetworkMessage::DefenderState { .. } => "DefenderState",
+        NetworkMessage::BulletSpawn { .. } => "BulletSpawn",
+        NetworkMessage::HealthUpdate { .. } => "HealthUpdate",
+        NetworkMessage::RematchRequest => "RematchRequest",
+        NetworkMessage::RematchReady => "RematchReady",
+        NetworkMessage::RulesUpdate { .. } => "RulesUpdate",
+        NetworkMessage::WeaponSelect { .. } => "WeaponSelect",
+        NetworkMessage::DefenderUtility { .. } => "DefenderUtility",
+        NetworkMessage::WallRepair { .. } => "WallRepair",
+        NetworkMessage::RulesRequest => "RulesRequest",
+    }
+}
+
+/// 读取抓包文件，跳过无法解析的行
+pub fn load_capture(path: &str) -> Result<Vec<CaptureRecord>, String> {
+    let file = File::open(path).map_err(|e| format!("无法打开 {}: {}", path, e))?;
+    let mut records = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("读取 {} 失败: {}", path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match ron::from_str::<CaptureRecord>(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => eprintln!("[抓包] {}:{} 解析失败，已跳过: {}", path, line_no + 1, e),
+        }
+    }
+    Ok(records)
+}
+
+/// 过滤条件
+#[derive(Default)]
+struct CaptureFilter {
+    kinds: Vec<String>,
+    ignored: Vec<String>,
+    direction: Option<CaptureDirection>,
+}
+
+impl CaptureFilter {
+    fn matches(&self, record: &CaptureRecord) -> bool {
+        let kind = message_kind(&record.payload);
+        if !self.kinds.is_empty() && !self.kinds.iter().any(|k| k == kind) {
+            return false;
+        }
+        if self.ignored.iter().any(|k| k == kind) {
+            return false;
+        }
+        self.direction.is_none_or(|direction| record.direction == direction)
+    }
+
+    /// 选出指定方向上满足过滤条件的记录
+    fn select<'a>(&self, records: &'a [CaptureRecord], direction: CaptureDirection) -> Vec<&'a CaptureRecord> {
+        records.iter().filter(|r| r.direction == direction && self.matches(r)).collect()
+    }
+}
+
+/// 解析命令行中的过滤参数，返回过滤条件和剩余的位置参数
+fn parse_filter_args(args: &[String]) -> Result<(CaptureFilter, Vec<String>), String> {
+    let mut filter = CaptureFilter::default();
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--kind" => {
+                let value = iter.next().ok_or("--kind 需要一个消息类型")?;
+                filter.kinds.extend(value.split(',').map(|s| s.trim().to_string()));
+            }
+            "--ignore" => {
+                let value = iter.next().ok_or("--ignore 需要一个消息类型")?;
+                filter.ignored.extend(value.split(',').map(|s| s.trim().to_string()));
+            }
+            "--dir" => {
+                let value = iter.next().ok_or("--dir 需要 sent 或 recv")?;
+                filter.direction = Some(match value.as_str() {
+                    "sent" => CaptureDirection::Sent,
+                    "recv" | "received" => CaptureDirection::Received,
+                    other => return Err(format!("未知方向: {}（应为 sent 或 recv）", other)),
+                });
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+    Ok((filter, positional))
+}
+
+fn print_record(record: &CaptureRecord) {
+    let arrow = match record.direction {
+        CaptureDirection::Sent => "->",
+        CaptureDirection::Received => "<-",
+    };
+    let body = match &record.payload {
+        CapturePayload::Message(message) => ron::ser::to_string_pretty(message.as_ref(), ron::ser::PrettyConfig::default())
+            .unwrap_or_else(|_| format!("{:?}", message)),
+        CapturePayload::Undecodable { bytes, error } => {
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            format!("无法解码（{} 字节）: {}\n{}", bytes.len(), error, hex.join(" "))
+        }
+    };
+    println!("#{} [{}] {} {}", record.seq, record.timestamp_ms, arrow, record.peer);
+    for line in body.lines() {
+        println!("    {}", line);
+    }
+}
+
+/// 比较一个方向上的报文流（发送方的 Sent 与接收方的 Received）
+/// 对端缺少的报文视为 UDP 丢包并跳过，返回第一个真正分歧的位置
+fn diff_stream(label: &str, sent: &[&CaptureRecord], received: &[&CaptureRecord]) -> bool {
+    let encode = |record: &CaptureRecord| ron::ser::to_string(&record.payload).unwrap_or_default();
+    let sent_encoded: Vec<String> = sent.iter().map(|r| encode(r)).collect();
+    let received_encoded: Vec<String> = received.iter().map(|r| encode(r)).collect();
+
+    let mut i = 0;
+    let mut j = 0;
+    let mut lost = 0;
+    while i < sent_encoded.len() && j < received_encoded.len() {
+        if sent_encoded[i] == received_encoded[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        // 接收方的报文可能出现在发送方稍后的位置（中间的报文丢失）
+        let window_end = (i + 1 + DIFF_LOSS_WINDOW).min(sent_encoded.len());
+        if let Some(offset) = sent_encoded[i + 1..window_end].iter().position(|s| *s == received_encoded[j]) {
+            lost += offset + 1;
+            i += offset + 1;
+            continue;
+        }
+        println!("[{}] 第一个分歧点：发送方第 {} 条 / 接收方第 {} 条", label, i, j);
+        println!("  发送方:");
+        print_record(sent[i]);
+        println!("  接收方:");
+        print_record(received[j]);
+        return true;
+    }
+    if j < received_encoded.len() {
+        println!("[{}] 接收方多出 {} 条发送方没有记录的报文，从以下报文开始:", label, received_encoded.len() - j);
+        print_record(received[j]);
+        return true;
+    }
+    lost += sent_encoded.len() - i;
+    println!("[{}] 一致（比较 {} 条，疑似丢包 {} 条）", label, j, lost);
+    false
+}
+
+/// `inspect` 子命令入口，返回进程退出码
+pub fn run_inspect_command(args: &[String]) -> i32 {
+    let usage = "用法:\n  inspect print <抓包文件> [--kind 类型] [--ignore 类型] [--dir sent|recv]\n  inspect diff <主机抓包> <客户端抓包> [--kind 类型] [--ignore 类型]";
+    let Some(command) = args.first() else {
+        eprintln!("{}", usage);
+        return 2;
+    };
+    let (filter, positional) = match parse_filter_args(&args[1..]) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, usage);
+            return 2;
+        }
+    };
+
+    match (command.as_str(), positional.as_slice()) {
+        ("print", [path]) => {
+            let records = match load_capture(path) {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 1;
+                }
+            };
+            for record in records.iter().filter(|r| filter.matches(r)) {
+                print_record(record);
+            }
+            0
+        }
+        ("diff", [host_path, client_path]) => {
+            let (host, client) = match (load_capture(host_path), load_capture(client_path)) {
+                (Ok(host), Ok(client)) => (host, client),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("{}", e);
+                    return 1;
+                }
+            };
+            let host_to_client = diff_stream(
+                "主机 -> 客户端",
+                &filter.select(&host, CaptureDirection::Sent),
+                &filter.select(&client, CaptureDirection::Received),
+            );
+            let client_to_host = diff_stream(
+                "客户端 -> 主机",
+                &filter.select(&client, CaptureDirection::Sent),
+                &filter.select(&host, CaptureDirection::Received),
+            );
+            if host_to_client || client_to_host { 1 } else { 0 }
+        }
+        _ => {
+            eprintln!("{}", usage);
+            2
+        }
+    }
+}
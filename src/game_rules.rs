@@ -0,0 +1,112 @@
+// 对局规则的加载与同步
+//
+// 启动时通过 `--rules <预设名或文件>` 参数或环境变量 `SNIPER_RULES` 选择规则：
+// - 预设名（如 classic、hardcore）对应 assets/rules/<预设名>.ron
+// - 以 .ron 结尾的参数当作文件路径直接加载
+// 未指定时使用 classic；文件缺失或不合法时退回内置的经典规则。
+//
+// 主菜单可以切换赛制，切换结果记入本地选择的规则。
+// 联机时客户端在收到 StartGame 后改用主机的规则，回到主菜单时恢复自己选择的规则。
+// 规则变化后，接收线程用的消息校验范围由 network_game::publish_message_limits 重新计算。
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+pub use bevy_sniper_duel::rules::{GameRules, HitResolution, MatchFormat};
+
+/// 规则选择的环境变量名
+pub const RULES_ENV_VAR: &str = "SNIPER_RULES";
+/// 默认预设
+pub const DEFAULT_PRESET: &str = "classic";
+/// 预设文件所在目录
+const PRESET_DIR: &str = "assets/rules";
+
+/// 本地选择的规则：启动时加载，主菜单切换赛制时更新（回到主菜单时恢复）
+static CONFIGURED_RULES: LazyLock<RwLock<GameRules>> = LazyLock::new(|| RwLock::new(GameRules::default()));
+
+/// 预设名或路径对应的规则文件
+fn rules_path(selection: &str) -> PathBuf {
+    if selection.ends_with(".ron") {
+        PathBuf::from(selection)
+    } else {
+        PathBuf::from(PRESET_DIR).join(format!("{}.ron", selection))
+    }
+}
+
+/// 初始化对局规则：优先使用命令行参数，其次使用环境变量，返回要插入的资源
+pub fn init_game_rules(args: &[String]) -> GameRules {
+    let selection = args
+        .iter()
+        .position(|arg| arg == "--rules")
+        .and_then(|index| args.get(index + 1).cloned())
+        .or_else(|| std::env::var(RULES_ENV_VAR).ok())
+        .filter(|selection| !selection.is_empty())
+        .unwrap_or_else(|| DEFAULT_PRESET.to_string());
+
+    let path = rules_path(&selection);
+    let rules = match std::fs::read_to_string(&path) {
+        Ok(text) => match GameRules::from_ron(&text) {
+            Ok(rules) => {
+                eprintln!("[规则] 使用规则: {} ({})", selection, path.display());
+                rules
+            }
+            Err(e) => {
+                eprintln!("[规则] 规则文件 {} 不合法，使用内置经典规则: {}", path.display(), e);
+                GameRules::default()
+            }
+        },
+        Err(e) => {
+            eprintln!("[规则] 无法读取规则文件 {}，使用内置经典规则: {}", path.display(), e);
+            GameRules::default()
+        }
+    };
+    if let Ok(mut configured) = CONFIGURED_RULES.write() {
+        *configured = rules.clone();
+    }
+    rules
+}
+
+/// 把规则保存为命名预设（assets/rules/<预设名>.ron），返回保存的路径
+/// 预设名只允许字母、数字、下划线和连字符，不能覆盖默认预设
+pub fn save_preset(name: &str, rules: &GameRules) -> Result<PathBuf, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("预设名只能包含字母、数字、下划线和连字符: {:?}", name));
+    }
+    if name == DEFAULT_PRESET {
+        return Err(format!("不能覆盖默认预设 {}", DEFAULT_PRESET));
+    }
+    rules.validate()?;
+    let text = rules.to_ron()?;
+    std::fs::create_dir_all(PRESET_DIR).map_err(|e| format!("无法创建目录 {}: {}", PRESET_DIR, e))?;
+    let path = rules_path(name);
+    std::fs::write(&path, format!("// 调参面板保存的规则预设\n{}\n", text))
+        .map_err(|e| format!("无法写入 {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// 主菜单切换赛制：同时修改当前规则和本地选择的规则
+pub fn select_match_format(rules: &mut GameRules, match_format: MatchFormat) {
+    rules.match_format = match_format;
+    if let Ok(mut configured) = CONFIGURED_RULES.write() {
+        configured.match_format = match_format;
+    }
+}
+
+/// 主菜单切换命中判定方式：同时修改当前规则和本地选择的规则
+pub fn select_hit_resolution(rules: &mut GameRules, hit_resolution: HitResolution) {
+    rules.hit_resolution = hit_resolution;
+    if let Ok(mut configured) = CONFIGURED_RULES.write() {
+        configured.hit_resolution = hit_resolution;
+    }
+}
+
+/// 回到主菜单时恢复本地选择的规则（联机时可能被主机的规则覆盖过）
+pub fn restore_configured_rules(mut rules: ResMut<GameRules>) {
+    let Ok(configured) = CONFIGURED_RULES.read() else {
+        return;
+    };
+    if *rules != *configured {
+        // 调试输出已禁用: println!("[规则] 恢复本地选择的规则");
+        *rules = configured.clone();
+    }
+}
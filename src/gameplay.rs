@@ -7,17 +7,17 @@ use bevy::{
     ecs::system::ParamSet,
 };
 use rand::Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use crate::{
     PlayerId, PlayerRole, AppState, RoundState,
-    PLAYER_SIZE, WALL_SIZE, WALL_POSITION, DEFENDER_START_POS, ATTACKER_START_POS,
-    BULLET_SIZE, BULLET_SPEED, MUZZLE_FLASH_DURATION, PLAYER_MOVE_SPEED,
-    AIM_SPEED, MAX_AIM_OFFSET, SIDE_DODGE_DISTANCE, CROSSHAIR_DAMAGE_RANGE,
-    DAMAGE_HEAD, DAMAGE_TORSO, DAMAGE_LEGS,
-    ACTION_DURATION_SECONDS, BULLETS_PER_ROUND,
-    BRICK_COLS, BRICK_ROWS, BRICK_WIDTH, BRICK_HEIGHT,
+    PLAYER_SIZE,
+    BULLET_SIZE, MUZZLE_FLASH_DURATION, MAX_AIM_OFFSET,
+    BRICK_WIDTH, BRICK_HEIGHT,
     BulletIcon, PlayerHealthDisplay, ActionCooldownText, TimerText,
 };
+use crate::game_rules::{GameRules, HitResolution};
+use crate::weapons::WeaponEffects;
+use bevy_sniper_duel::materials::BrickSpec;
 
 // --- 游玩系统集定义 ---
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
@@ -40,14 +40,8 @@ pub struct ActionCooldown {
     pub cooldown_duration: f64,
 }
 
-#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DodgeAction {
-    #[default]
-    None,
-    Crouch,
-    SideLeft,
-    SideRight,
-}
+// DodgeAction 直接通过网络发送，定义在 protocol.rs
+pub use bevy_sniper_duel::protocol::DodgeAction;
 
 #[derive(Component, Debug)]
 pub struct ActionTimer {
@@ -99,22 +93,69 @@ pub struct Wall {
 pub struct WallSegment {
     pub wall_entity: Entity,
     pub position: Vec2,
+    pub depth: u8,          // 所在墙的深度（0 在最前面）
     pub damaged: bool,
     pub view_layer: ViewLayer,
+    pub spec: BrickSpec,    // 材质和是否加固
+    pub hit_points: u32,    // 剩余耐久（钢板不使用）
+    pub intact_color: Color, // 完好时的颜色（修好后恢复）
+}
+
+impl WallSegment {
+    /// 受到一次破墙伤害，返回实际扣除的耐久（钢板和已碎的砖块为 0），耐久归零时砖块碎掉
+    pub fn take_hit(&mut self, wall_damage: u32) -> u32 {
+        if self.damaged || self.spec.hit_points().is_none() {
+            return 0;
+        }
+        let lost = wall_damage.min(self.hit_points);
+        self.hit_points -= lost;
+        self.damaged = self.hit_points == 0;
+        lost
+    }
+
+    /// 防守方修好砖块：恢复完整耐久（钢板不会碎，不需要修）
+    pub fn repair(&mut self) {
+        self.damaged = false;
+        self.hit_points = self.spec.hit_points().unwrap_or(0);
+    }
+
+    /// 完好且不透明（挡住视线和激光）
+    pub fn blocks_sight(&self) -> bool {
+        !self.damaged && !self.spec.material.is_see_through()
+    }
 }
 
 #[derive(Component)]
 pub struct WallBackground {
     pub position: Vec2,
+    pub depth: u8,
+    pub view_layer: ViewLayer,
+}
+
+/// 防守方视角中砖块碎掉后留下的弹孔（修好砖块时一起移除）
+#[derive(Component)]
+pub struct BulletHole {
+    pub position: Vec2,
+    pub depth: u8,
+}
+
+/// 地图中的掩体：挡住子弹，打不碎
+#[derive(Component)]
+pub struct CoverBlock {
     pub view_layer: ViewLayer,
 }
 
+/// 查询掩体的位置和大小
+pub type CoverQuery<'w, 's, F = ()> = Query<'w, 's, (&'static Transform, &'static CoverBlock, &'static Collider), F>;
+
 #[derive(Component, Debug)]
 pub struct Bullet {
     pub velocity: Vec2,
     pub start_pos: Vec2,
     pub target_pos: Vec2,
     pub owner: PlayerId,
+    pub weapon: crate::weapons::WeaponKind, // 决定破墙范围
+    pub hit_wall: bool, // 已经在落点处破过墙（每发子弹只破一次）
 }
 
 /// 标记子弹的渲染层（用于同步两个渲染层的子弹位置）
@@ -139,10 +180,56 @@ pub struct Crosshair;
 #[derive(Component)]
 pub struct DefenderCrosshairIndicator;
 
+/// 电脑防守方（单人练习），由 bot::defender_ai_system 驱动
 #[derive(Component, Debug)]
 pub struct DefenderAI {
-    pub direction: f32,
-    pub move_timer: Timer,
+    pub direction: f32,                                // 当前水平移动方向（-1 左，1 右，0 停）
+    pub move_timer: Timer,                             // 重新选择目标位置的计时器
+    pub difficulty: crate::bot::BotDifficulty,
+    pub target: Option<Vec2>,                          // 当前要移动到的位置（None 表示还没选）
+    pub crosshair_history: VecDeque<(f64, Vec2)>,      // 看到的准星位置（用于模拟反应延迟）
+    pub threat_handled: bool,                          // 本次被瞄准是否已经做过躲避决定
+}
+
+impl DefenderAI {
+    pub fn new(difficulty: crate::bot::BotDifficulty) -> Self {
+        Self {
+            direction: 0.0,
+            move_timer: Timer::from_seconds(difficulty.replan_seconds(), TimerMode::Repeating),
+            difficulty,
+            target: None,
+            crosshair_history: VecDeque::new(),
+            threat_handled: false,
+        }
+    }
+}
+
+/// 电脑进攻方（单人练习），由 bot::attacker_ai_system 驱动
+#[derive(Component, Debug)]
+pub struct AttackerAI {
+    pub difficulty: crate::bot::BotDifficulty,
+    pub intent: crate::bot::AttackerIntent,             // 当前打算做什么
+    pub aim_error: Vec2,                                // 本次瞄准的误差（每次开枪后重新随机）
+    pub on_target_since: Option<f64>,                   // 准星到达瞄准点的时间（稳定一会儿才开枪）
+    pub last_shot: Option<f64>,
+    pub searching_since: f64,                           // 开始找不到防守方的时间（等够了才破墙）
+    pub defender_history: VecDeque<(f64, Option<Vec2>)>, // 透过破洞看到的防守方（用于模拟反应延迟）
+    pub last_seen: Option<Vec2>,                        // 最近一次看到防守方的位置
+}
+
+impl AttackerAI {
+    pub fn new(difficulty: crate::bot::BotDifficulty, now: f64) -> Self {
+        Self {
+            difficulty,
+            intent: crate::bot::AttackerIntent::Wait,
+            aim_error: Vec2::ZERO,
+            on_target_since: None,
+            last_shot: None,
+            searching_since: now,
+            defender_history: VecDeque::new(),
+            last_seen: None,
+        }
+    }
 }
 
 #[derive(Component)]
@@ -158,13 +245,75 @@ pub enum ViewLayer {
     DefenderView,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HumanoidPartType {
     Head,
     Torso,
     Legs,
 }
 
+impl HumanoidPartType {
+    pub const ALL: [HumanoidPartType; 3] = [HumanoidPartType::Head, HumanoidPartType::Torso, HumanoidPartType::Legs];
+
+    /// 打中该部位时的伤害部位
+    pub fn hitbox_type(self) -> HitboxType {
+        match self {
+            HumanoidPartType::Head => HitboxType::Head,
+            HumanoidPartType::Torso => HitboxType::Torso,
+            HumanoidPartType::Legs => HitboxType::Legs,
+        }
+    }
+}
+
+/// 侧躲时上身向躲避方向倾斜的距离（占身体宽度的比例）：头部最多，躯干一半，腿部不动
+const SIDE_DODGE_LEAN: f32 = 0.25;
+
+/// 人形一个部位的矩形：中心相对于玩家位置的偏移和大小
+#[derive(Debug, Clone, Copy)]
+pub struct PartRect {
+    pub part_type: HumanoidPartType,
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+impl PartRect {
+    pub fn contains(&self, point: Vec2) -> bool {
+        let local = (point - self.offset).abs();
+        local.x <= self.size.x / 2.0 && local.y <= self.size.y / 2.0
+    }
+}
+
+/// 当前动作下人形各部位的矩形（绘制身体部位和命中判定共用同一套几何形状）
+/// 头部占身高的 30%、躯干 50%、腿部 20%；下蹲时身高变为 70%，侧躲时上身向躲避方向倾斜
+pub fn humanoid_part_rects(dodge_action: &DodgeAction) -> [PartRect; 3] {
+    let player_width = PLAYER_SIZE.x;
+    let current_height = body_height(dodge_action);
+    let lean = match dodge_action {
+        DodgeAction::SideLeft => -player_width * SIDE_DODGE_LEAN,
+        DodgeAction::SideRight => player_width * SIDE_DODGE_LEAN,
+        _ => 0.0,
+    };
+
+    let head_height = current_height * 0.3;
+    let torso_height = current_height * 0.5;
+    let legs_height = current_height * 0.2;
+
+    let head_y = (current_height - head_height) / 2.0;
+    let torso_y = -(current_height - torso_height) / 2.0 + head_height / 2.0;
+    let legs_y = -(current_height - legs_height) / 2.0;
+
+    HumanoidPartType::ALL.map(|part_type| match part_type {
+        HumanoidPartType::Head => PartRect { part_type, offset: Vec2::new(lean, head_y), size: Vec2::new(player_width * 0.8, head_height) },
+        HumanoidPartType::Torso => PartRect { part_type, offset: Vec2::new(lean / 2.0, torso_y), size: Vec2::new(player_width * 0.9, torso_height) },
+        HumanoidPartType::Legs => PartRect { part_type, offset: Vec2::new(0.0, legs_y), size: Vec2::new(player_width * 0.7, legs_height) },
+    })
+}
+
+/// 当前动作下的身高（下蹲时为 70%）
+pub fn body_height(dodge_action: &DodgeAction) -> f32 {
+    if matches!(dodge_action, DodgeAction::Crouch) { PLAYER_SIZE.y * 0.7 } else { PLAYER_SIZE.y }
+}
+
 #[derive(Component)]
 pub struct LaserIndicator;
 
@@ -195,6 +344,8 @@ pub struct RoundInfo {
     pub bullets_fired_this_round: i32,
     pub bullets_hit_defender: i32,
     pub is_switching: bool,
+    pub score: crate::scoring::MatchScore, // 比分（累计伤害、淘汰次数、已结束的回合数）
+    pub wind: Vec2, // 本回合的风（弹道规则，主机生成后同步给客户端）
 }
 
 #[derive(Resource, Default, Debug)]
@@ -262,6 +413,12 @@ pub struct PlayerActionEvent {
     pub action: DodgeAction,
 }
 
+/// 电脑进攻方扣动扳机（与按 J 键走同一套射击逻辑）
+#[derive(Event, Debug)]
+pub struct AttackerTriggerEvent {
+    pub player_id: PlayerId,
+}
+
 #[derive(Event, Debug)]
 pub struct CameraSwitchEvent {
     pub is_attacker_view: bool,
@@ -628,6 +785,10 @@ pub fn attacker_aim_system(
     player_query: Query<(&PlayerRole, &PlayerId), (With<PlayerId>, Without<DefenderCamera>)>,
     view_config: Res<ViewConfig>,
     room_info: Option<Res<crate::RoomInfo>>,
+    (rules, arena): (Res<GameRules>, Res<crate::arena::Arena>),
+    round_info: Res<RoundInfo>,
+    loadout: Res<crate::weapons::Loadout>,
+    mut scope: ResMut<crate::scope::ScopeState>,
 ) {
     // 检查是否为本地模式
     let is_local_mode = room_info.as_ref().map(|r| !r.is_connected).unwrap_or(false);
@@ -665,22 +826,28 @@ pub fn attacker_aim_system(
         if !view_config.is_attacker_view { return; }
     }
     
-    let attacker_pos = ATTACKER_START_POS.truncate();
     let mut move_direction = Vec2::ZERO;
     if keyboard_input.pressed(KeyCode::KeyW) { move_direction.y += 1.0; }
     if keyboard_input.pressed(KeyCode::KeyS) { move_direction.y -= 1.0; }
     if keyboard_input.pressed(KeyCode::KeyA) { move_direction.x -= 1.0; }
     if keyboard_input.pressed(KeyCode::KeyD) { move_direction.x += 1.0; }
     
-    if move_direction.length_squared() > 0.0 {
-        move_direction = move_direction.normalize();
-        let movement = move_direction * AIM_SPEED * time.delta_seconds();
-        crosshair_offset.0 += movement;
-        crosshair_offset.0 = crosshair_offset.0.clamp_length_max(MAX_AIM_OFFSET);
-    }
+    // 瞄准镜：移动时晃动变大，屏息和倍镜减小晃动，倍镜同时降低瞄准速度
+    crate::scope::update_scope_from_input(&mut scope, &keyboard_input, move_direction != Vec2::ZERO, time.delta_seconds());
     
-    let aim_world_pos = attacker_pos + crosshair_offset.0;
-    cursor_pos.0 = aim_world_pos;
+    let aim_world_pos = move_crosshair(
+        &mut crosshair_offset,
+        &mut cursor_pos,
+        arena.aim_origin(),
+        move_direction.normalize_or_zero(),
+        rules.aim_speed * scope.aim_speed_scale() * time.delta_seconds(),
+    );
+    
+    // 瞄准镜晃动：准星围绕瞄准点漂移，幅度由武器和瞄准镜状态决定（相机仍对准瞄准点）
+    let weapon = loadout.attacker_weapon(&round_info, &rules);
+    let sway = weapon.idle_sway(time.elapsed_seconds()) * scope.sway_scale();
+    let swayed_offset = (crosshair_offset.0 + sway).clamp_length_max(MAX_AIM_OFFSET);
+    cursor_pos.0 = arena.aim_origin() + swayed_offset;
     
     // 进攻方摄像机跟随瞄准点移动，保持准星在屏幕中心
     if is_local_mode {
@@ -708,7 +875,7 @@ pub fn attacker_aim_system(
                 }
                 // 在网络模式下，这个查询只匹配进攻方相机（没有DefenderCamera组件，order = 0）
                 // 相机位置 = 瞄准点位置，这样瞄准点（准星）就会在屏幕中心
-                // 进攻方玩家位置固定（ATTACKER_START_POS），但相机跟随瞄准点移动
+                // 进攻方玩家位置固定（地图的进攻方出生点），但相机跟随瞄准点移动
                 // 注意：只有在进攻方视图时才更新相机位置，避免在角色切换时覆盖相机设置
                 camera_transform.translation.x = aim_world_pos.x;
                 camera_transform.translation.y = aim_world_pos.y;
@@ -724,6 +891,26 @@ pub fn attacker_aim_system(
     }
 }
 
+/// 按方向移动准星并更新瞄准点（键盘瞄准和电脑进攻方共用），返回新的瞄准点
+/// origin 为进攻方位置；move_direction 的长度不超过 1，长度小于 1 时按比例减速；max_step 为本帧全速时的移动距离
+pub fn move_crosshair(
+    crosshair_offset: &mut CrosshairOffset,
+    cursor_pos: &mut CursorPosition,
+    origin: Vec2,
+    move_direction: Vec2,
+    max_step: f32,
+) -> Vec2 {
+    if move_direction.length_squared() > 0.0 {
+        let movement = move_direction.clamp_length_max(1.0) * max_step;
+        crosshair_offset.0 += movement;
+        crosshair_offset.0 = crosshair_offset.0.clamp_length_max(MAX_AIM_OFFSET);
+    }
+    
+    let aim_world_pos = origin + crosshair_offset.0;
+    cursor_pos.0 = aim_world_pos;
+    aim_world_pos
+}
+
 /// 攻击方射击系统（无残留轨迹）
 /// 游戏结束延迟系统：延迟2秒后发送游戏结束事件和网络消息
 pub fn game_over_delay_system(
@@ -734,7 +921,7 @@ pub fn game_over_delay_system(
     room_info: Option<Res<crate::RoomInfo>>,
     network_manager: Option<Res<crate::network_game::NetworkManager>>,
     player_query: Query<(&Transform, &PlayerId, &PlayerRole, &DodgeAction, &Health)>,
-    round_info: Option<Res<RoundInfo>>,
+    (round_info, rules): (Option<Res<RoundInfo>>, Res<GameRules>),
 ) {
     let Some(mut timer) = game_over_delay.timer.as_mut() else {
         // 如果延迟计时器不存在，检查是否需要持续发送网络消息
@@ -815,6 +1002,7 @@ pub fn game_over_delay_system(
                                     &**nm,
                                     &player_data,
                                     round_info,
+                                    rules.fingerprint(),
                                 );
                             }
                             
@@ -843,16 +1031,21 @@ pub fn attacker_shoot_system(
     mut bullet_id_counter: ResMut<BulletIdCounter>,
     cursor_pos: Res<CursorPosition>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    attacker_query: Query<(&Transform, &PlayerRole, &PlayerId)>,
+    mut trigger_events: EventReader<AttackerTriggerEvent>,
+    attacker_query: Query<(&Transform, &PlayerRole, &PlayerId, Has<AttackerAI>)>,
     mut player_query: Query<(&Transform, &PlayerId, &PlayerRole, &DodgeAction, &mut Health), (With<PlayerId>, Without<Bullet>)>,
     mut events: EventWriter<PlayerHitEvent>,
-    mut game_over_delay: ResMut<GameOverDelay>,
     view_config: Res<ViewConfig>,
     room_info: Option<Res<crate::RoomInfo>>,
     network_manager: Option<Res<crate::network_game::NetworkManager>>,
     time: Res<Time>,
+    (rules, loadout, arena): (Res<GameRules>, Res<crate::weapons::Loadout>, Res<crate::arena::Arena>),
+    (wall_query, cover_query, mut utility_query): (Query<(&Transform, &WallSegment, &Collider)>, CoverQuery, Query<&mut crate::utility::UtilityEffect>),
     mut shoot_cooldown: Local<f32>, // 射击冷却时间
 ) {
+    // 先读完电脑的扳机事件，避免提前返回时残留到下一帧
+    let bot_triggers: Vec<PlayerId> = trigger_events.read().map(|event| event.player_id).collect();
+    
     // 网络模式下，只允许当前是进攻方的玩家射击
     if let Some(room_info) = room_info.as_ref() {
         if room_info.is_connected {
@@ -883,16 +1076,18 @@ pub fn attacker_shoot_system(
         }
     }
     
-    let mut attacker_pos = ATTACKER_START_POS.truncate();
+    let mut attacker_pos = arena.aim_origin();
     let mut attacker_id = round_info.current_attacker;
+    let mut attacker_is_bot = false;
     
     // 查找进攻方角色（优先匹配 current_attacker）
     let mut found_attacker = false;
-    for (transform, role, id) in attacker_query.iter() {
+    for (transform, role, id, is_bot) in attacker_query.iter() {
         if *id == round_info.current_attacker {
             // 如果找到了 current_attacker，使用它的位置
             attacker_pos = transform.translation.truncate();
             attacker_id = *id;
+            attacker_is_bot = is_bot;
             found_attacker = true;
             
             // 如果是网络模式，验证角色是否正确
@@ -907,10 +1102,11 @@ pub fn attacker_shoot_system(
     
     // 如果没有找到 current_attacker，尝试查找任何进攻方角色（兼容性处理）
     if !found_attacker {
-        for (transform, role, id) in attacker_query.iter() {
+        for (transform, role, id, is_bot) in attacker_query.iter() {
             if matches!(role, PlayerRole::Attacker) {
                 attacker_pos = transform.translation.truncate();
                 attacker_id = *id;
+                attacker_is_bot = is_bot;
                 found_attacker = true;
                 // 调试输出已禁用: println!("[警告] 未找到 current_attacker ({:?})，使用进攻方角色 {:?}", round_info.current_attacker, id);
                 break;
@@ -929,21 +1125,36 @@ pub fn attacker_shoot_system(
         *shoot_cooldown -= time.delta_seconds();
     }
     
-    // 检查射击条件：J键按下、有子弹、时间未到、冷却完成
-    if keyboard_input.just_pressed(KeyCode::KeyJ) 
+    // 电脑进攻方只响应扳机事件，玩家按J键不能替电脑开枪
+    let trigger_pulled = if attacker_is_bot {
+        bot_triggers.contains(&attacker_id)
+    } else {
+        keyboard_input.just_pressed(KeyCode::KeyJ)
+    };
+    
+    let weapon = crate::weapons::Weapon::of(loadout.get(attacker_id), &rules);
+    
+    // 检查射击条件：J键按下（或电脑扣扳机）、有子弹、时间未到、冷却完成
+    if trigger_pulled
         && round_info.bullets_left > 0 
         && !round_info.round_timer.finished()
         && *shoot_cooldown <= 0.0 {
         
-        // 设置射击冷却（1秒）
-        *shoot_cooldown = 1.0;
+        // 设置射击冷却（接收方按同一武器检查射速）
+        *shoot_cooldown = weapon.cooldown_seconds;
         
         round_info.bullets_left -= 1;
         round_info.bullets_fired_this_round += 1;
 
         let target_pos = cursor_pos.0;
         let direction = (target_pos - attacker_pos).normalize_or_zero();
-        let velocity = direction * BULLET_SPEED;
+        let velocity = direction * weapon.bullet_speed;
+        // 弹道规则下子弹会下坠、被风吹偏，命中和破墙都按落点判定（未开启时落点就是准星）
+        let landing_pos = crate::ballistics::landing_point(attacker_pos, target_pos, weapon.bullet_speed, &rules, round_info.wind);
+        // 穿墙：落点处的完好砖块按材质削弱伤害，超过武器穿透力或被钢板、玻璃挡住时打不到后面的防守方（砖块仍会受损）
+        // 落在掩体上的子弹同样打不到防守方，打到护盾上的这一发被护盾吸收
+        let damage_scale = shot_damage_scale(landing_pos, ViewLayer::AttackerView, &weapon, &wall_query, &cover_query)
+            .filter(|_| !crate::utility::absorb_shot(landing_pos, &mut utility_query));
 
         // 优化：减少日志输出以提高性能
         // println!("=== 射击: 进攻方={:?}, 准心位置=({:.1}, {:.1}) ===", attacker_id, target_pos.x, target_pos.y);
@@ -961,19 +1172,30 @@ pub fn attacker_shoot_system(
                     start_pos: [attacker_pos.x, attacker_pos.y],
                     target_pos: [target_pos.x, target_pos.y],
                     velocity: [velocity.x, velocity.y],
+                    weapon: weapon.kind,
+                    turn: round_info.score.turns_played,
                 };
                 crate::network_game::send_network_message(&**network_manager, bullet_msg);
             }
         }
         
+        // 弹道判定：子弹飞到瞄准点时再按防守方当时的位置判定（见 projectile.rs），这里不结算命中
+        let resolve_on_arrival = rules.hit_resolution == HitResolution::Projectile;
+        if let Some(damage_scale) = damage_scale.filter(|_| resolve_on_arrival) {
+            crate::projectile::spawn_pending_shot(&mut commands, attacker_id, (attacker_pos, landing_pos), weapon, damage_scale, is_network_mode);
+        }
+        
         // 在网络模式下，查找防守方（可能是对方玩家）
         // 防守方位置应该已经通过 handle_player_input_system 更新
         let mut found_defender = false;
-        let mut game_over_info: Option<(PlayerId, PlayerId)> = None; // (winner, loser)
         for (defender_transform, defender_id, defender_role, dodge_action, mut health) in player_query.iter_mut() {
-            if !matches!(defender_role, PlayerRole::Defender) {
+            if resolve_on_arrival || !matches!(defender_role, PlayerRole::Defender) {
                 continue;
             }
+            let Some(damage_scale) = damage_scale else {
+                // 调试输出已禁用: println!("  -> 子弹被墙挡住，未能打穿");
+                continue;
+            };
             
             if *defender_id == attacker_id { 
                 continue;
@@ -985,104 +1207,16 @@ pub fn attacker_shoot_system(
             // println!("  [碰撞检测] 检查防守方: {:?}, 位置=({:.1}, {:.1}), 血量={:.1}", 
             //          defender_id, defender_pos.x, defender_pos.y, health.0);
             
-            let offset = target_pos - defender_pos;
-            let distance = offset.length();
-            
-            let player_height = PLAYER_SIZE.y;
-            let player_width = PLAYER_SIZE.x;
-            let is_crouching = matches!(dodge_action, DodgeAction::Crouch);
-            let current_height = if is_crouching { player_height * 0.7 } else { player_height };
-            
-            // 改进碰撞检测：使用更宽松的距离检测
-            // 考虑到网络延迟，使用更大的碰撞检测范围（1.5倍）
-            let hitbox_radius = (player_width * player_width + current_height * current_height).sqrt() / 2.0;
-            let max_hit_distance = hitbox_radius * 1.5; // 增加50%的容错范围，应对网络延迟
-            
-            if distance > max_hit_distance {
-                // 调试输出已禁用: println!("  -> 准心距离防守方太远: {:.1} > {:.1}（容错范围），未命中", distance, max_hit_distance);
-                continue;
-            }
-            
-            // 使用更宽松的相对位置检测（1.2倍容错）
-            let relative_y = offset.y / (current_height / 2.0);
-            let relative_x = offset.x / (player_width / 2.0);
-            
-            if relative_x.abs() > 1.2 || relative_y.abs() > 1.2 {
-                // 调试输出已禁用: println!("  -> 准心不在玩家身体范围内: 相对位置=({:.2}, {:.2})，容错范围=±1.2", relative_x, relative_y);
-                continue;
-            }
-            
-            let hit_part = if relative_y > 0.3 {
-                Some(HitboxType::Head)
-            } else if relative_y > -0.2 {
-                Some(HitboxType::Torso)
-            } else {
-                Some(HitboxType::Legs)
-            };
-            
-            if let Some(hitbox_type) = hit_part {
-                let part_name = match hitbox_type {
-                    HitboxType::Head => "头部",
-                    HitboxType::Torso => "躯干",
-                    HitboxType::Legs => "腿部",
-                };
-                // 调试输出已禁用: println!("  -> 命中{}！相对位置=({:.2}, {:.2})", part_name, relative_x, relative_y);
-                
-                let damage = match hitbox_type {
-                    HitboxType::Head => DAMAGE_HEAD,
-                    HitboxType::Torso => DAMAGE_TORSO,
-                    HitboxType::Legs => DAMAGE_LEGS,
-                };
-                
-                let old_health = health.0;
-                health.0 = (health.0 - damage).max(0.0);
-                
-                match *defender_id {
-                    PlayerId::Player1 => round_info.p1_health = health.0,
-                    PlayerId::Player2 => round_info.p2_health = health.0,
-                }
-                
-                // 调试输出已禁用: println!("  *** 造成伤害: {} 点，防守方 {:?} 血量: {:.1} -> {:.1} ***", damage, defender_id, old_health, health.0);
-                
-                // 在网络模式下，发送血量更新消息
-                if is_network_mode {
-                    if let Some(network_manager) = network_manager.as_ref() {
-                        let health_msg = crate::network_game::NetworkMessage::HealthUpdate {
-                            player_id: *defender_id,
-                            health: health.0,
-                        };
-                        crate::network_game::send_network_message(&**network_manager, health_msg);
-                    }
-                }
-                
-                events.send(PlayerHitEvent {
-                    player_id: *defender_id,
-                    damage,
+            if let Some(hitbox_type) = hitbox_at(landing_pos, defender_pos, dodge_action) {
+                apply_hit(
+                    &mut round_info,
+                    attacker_id,
+                    (*defender_id, &mut health),
+                    weapon.damage(hitbox_type) * damage_scale,
                     hitbox_type,
-                });
-                
-                round_info.bullets_hit_defender += 1;
-                
-                if health.0 <= 0.0 {
-                    let winner_id = match *defender_id {
-                        PlayerId::Player1 => PlayerId::Player2,
-                        PlayerId::Player2 => PlayerId::Player1,
-                    };
-                    
-                    // 调试输出已禁用: println!("  *** 游戏结束: 防守方 {:?} 被淘汰！获胜者: {:?} ***", defender_id, winner_id);
-                    
-                    // 记录游戏结束信息，在循环外部处理延迟（避免借用冲突）
-                    game_over_info = Some((winner_id, *defender_id));
-                    
-                    // 不立即发送事件和切换状态，延迟系统会在2秒后处理
-                    // game_over_events.send(GameOverEvent {
-                    //     winner_id,
-                    //     loser_id: *defender_id,
-                    // });
-                    // next_app_state.set(AppState::GameOver);
-                    
-                    break;
-                }
+                    &mut events,
+                    network_manager.as_deref().filter(|_| is_network_mode),
+                );
             }
         }
 
@@ -1091,33 +1225,148 @@ pub fn attacker_shoot_system(
             // 调试输出已禁用: println!("  [警告] 网络模式下未找到防守方！可能防守方位置未同步");
         }
         
-        // 在循环结束后，如果需要启动游戏结束延迟（避免借用冲突）
-        if let Some((winner_id, loser_id)) = game_over_info {
-            // 启动延迟计时器（2秒）
-            game_over_delay.timer = Some(Timer::from_seconds(2.0, TimerMode::Once));
-            game_over_delay.winner_id = Some(winner_id);
-            game_over_delay.loser_id = Some(loser_id);
-            
-            // 不立即发送网络消息，延迟系统会在2秒后发送
-            // if is_network_mode {
-            //     if let Some(nm) = network_manager.as_ref() {
-            //         // ... 网络消息发送逻辑移到延迟系统 ...
-            //     }
-            // }
-        }
-
         // 创建子弹（本地创建，网络模式下会通过BulletSpawn消息同步到对方）
         spawn_bullet(
             &mut commands,
             &mut bullet_id_counter,
             attacker_id,
             attacker_pos,
-            target_pos,
+            landing_pos,
             velocity,
+            weapon.kind,
         );
     }
 }
 
+/// 判定瞄准点落在防守方身上的部位（准星判定和弹道判定共用）
+/// 按身体部位实际绘制的矩形判定（见 humanoid_part_rects），部位之间的空隙和身体两侧都算没打中
+pub fn hitbox_at(target_pos: Vec2, defender_pos: Vec2, dodge_action: &DodgeAction) -> Option<HitboxType> {
+    let offset = target_pos - defender_pos;
+    let hitbox_type = humanoid_part_rects(dodge_action)
+        .iter()
+        .find(|rect| rect.contains(offset))
+        .map(|rect| rect.part_type.hitbox_type());
+    // 调试输出已禁用: println!("  -> 命中判定: 相对位置=({:.1}, {:.1})，结果={:?}", offset.x, offset.y, hitbox_type);
+    hitbox_type
+}
+
+/// 落点处挡在防守方前面的完好砖块相当于几块砖（只看 view_layer 视角的墙，两个视角的墙破损情况相同）
+/// 有钢板、玻璃这类打不穿的砖块挡着时返回 None
+pub fn bricks_on_path(landing_pos: Vec2, view_layer: ViewLayer, wall_query: &Query<(&Transform, &WallSegment, &Collider)>) -> Option<usize> {
+    wall_query
+        .iter()
+        .filter(|(_, segment, _)| !segment.damaged && segment.view_layer == view_layer)
+        .filter(|(transform, _, collider)| {
+            let offset = (landing_pos - transform.translation.truncate()).abs();
+            offset.x <= collider.size.x / 2.0 && offset.y <= collider.size.y / 2.0
+        })
+        .map(|(_, segment, _)| segment.spec.penetration_cost())
+        .sum()
+}
+
+/// 落点处最前面的完好砖块如果这一发就能打碎，返回它相当于几块砖（子弹先打碎它再飞过去，不损失伤害），否则为 0
+pub fn bricks_broken_by_shot(landing_pos: Vec2, view_layer: ViewLayer, wall_damage: u32, wall_query: &Query<(&Transform, &WallSegment, &Collider)>) -> usize {
+    wall_query
+        .iter()
+        .filter(|(_, segment, _)| !segment.damaged && segment.view_layer == view_layer)
+        .filter(|(transform, _, collider)| {
+            let offset = (landing_pos - transform.translation.truncate()).abs();
+            offset.x <= collider.size.x / 2.0 && offset.y <= collider.size.y / 2.0
+        })
+        .min_by_key(|(_, segment, _)| segment.depth)
+        .filter(|(_, segment, _)| segment.spec.hit_points().is_some() && segment.hit_points <= wall_damage)
+        .and_then(|(_, segment, _)| segment.spec.penetration_cost())
+        .unwrap_or(0)
+}
+
+/// 落在 landing_pos 的子弹穿过墙和掩体后剩余的伤害比例，打不到墙后时返回 None
+pub fn shot_damage_scale<F: bevy::ecs::query::QueryFilter>(
+    landing_pos: Vec2,
+    view_layer: ViewLayer,
+    weapon: &crate::weapons::Weapon,
+    wall_query: &Query<(&Transform, &WallSegment, &Collider)>,
+    cover_query: &CoverQuery<F>,
+) -> Option<f32> {
+    bricks_on_path(landing_pos, view_layer, wall_query)
+        .filter(|_| !covered_by_prop(landing_pos, view_layer, cover_query))
+        .and_then(|bricks| {
+            let broken = bricks_broken_by_shot(landing_pos, view_layer, weapon.wall_damage, wall_query);
+            weapon.penetration_damage_scale(bricks, broken)
+        })
+}
+
+/// 落点是否在掩体上（掩体打不穿，挡住后面的防守方）
+pub fn covered_by_prop<F: bevy::ecs::query::QueryFilter>(landing_pos: Vec2, view_layer: ViewLayer, cover_query: &CoverQuery<F>) -> bool {
+    cover_query
+        .iter()
+        .filter(|(_, cover, _)| cover.view_layer == view_layer)
+        .any(|(transform, _, collider)| {
+            let offset = (landing_pos - transform.translation.truncate()).abs();
+            offset.x <= collider.size.x / 2.0 && offset.y <= collider.size.y / 2.0
+        })
+}
+
+/// 进攻方视角中能看穿的砖块位置：这块砖碎了或是玻璃，而且前后其他层的墙在这里没有完好的不透明砖块
+/// （前后几层墙的破洞要对齐才能看到后面；掩体另外判断）
+pub fn sight_openings(segments: &[(Vec2, &WallSegment)]) -> Vec<Vec2> {
+    let half_brick = Vec2::new(BRICK_WIDTH, BRICK_HEIGHT) / 2.0;
+    let blockers: Vec<(Vec2, u8)> = segments
+        .iter()
+        .filter(|(_, segment)| segment.view_layer == ViewLayer::AttackerView && segment.blocks_sight())
+        .map(|(position, segment)| (*position, segment.depth))
+        .collect();
+    segments
+        .iter()
+        .filter(|(_, segment)| segment.view_layer == ViewLayer::AttackerView && !segment.blocks_sight())
+        .filter(|(position, segment)| {
+            !blockers
+                .iter()
+                .any(|(blocker, depth)| *depth != segment.depth && (*blocker - *position).abs().cmplt(half_brick).all())
+        })
+        .map(|(position, _)| *position)
+        .collect()
+}
+
+/// 对防守方造成伤害：更新血量和比分，联机时（传入 network_manager）同步血量，并发送受击事件
+pub fn apply_hit(
+    round_info: &mut RoundInfo,
+    attacker_id: PlayerId,
+    (defender_id, health): (PlayerId, &mut Health),
+    damage: f32,
+    hitbox_type: HitboxType,
+    events: &mut EventWriter<PlayerHitEvent>,
+    network_manager: Option<&crate::network_game::NetworkManager>,
+) {
+    let old_health = health.0;
+    health.0 = (health.0 - damage).max(0.0);
+    
+    match defender_id {
+        PlayerId::Player1 => round_info.p1_health = health.0,
+        PlayerId::Player2 => round_info.p2_health = health.0,
+    }
+    round_info.score.add_damage(attacker_id, old_health - health.0);
+    
+    // 调试输出已禁用: println!("  *** 造成伤害: {} 点，防守方 {:?} 血量: {:.1} -> {:.1} ***", damage, defender_id, old_health, health.0);
+    
+    // 在网络模式下，发送血量更新消息
+    if let Some(network_manager) = network_manager {
+        let health_msg = crate::network_game::NetworkMessage::HealthUpdate {
+            player_id: defender_id,
+            health: health.0,
+        };
+        crate::network_game::send_network_message(network_manager, health_msg);
+    }
+    
+    events.send(PlayerHitEvent {
+        player_id: defender_id,
+        damage,
+        hitbox_type,
+    });
+    
+    round_info.bullets_hit_defender += 1;
+    // 血量归零后的胜负由 scoring::match_decision_system 按赛制判定
+}
+
 /// 创建子弹（可被本地射击系统和网络同步系统调用）
 pub fn spawn_bullet(
     commands: &mut Commands,
@@ -1126,12 +1375,13 @@ pub fn spawn_bullet(
     attacker_pos: Vec2,
     target_pos: Vec2,
     velocity: Vec2,
+    weapon: crate::weapons::WeaponKind,
 ) {
     // 获取子弹同步ID
     let sync_id = bullet_id_counter.0;
     bullet_id_counter.0 += 1;
     
-    spawn_bullet_with_id(commands, attacker_id, attacker_pos, target_pos, velocity, sync_id);
+    spawn_bullet_with_id(commands, attacker_id, attacker_pos, target_pos, velocity, sync_id, weapon);
 }
 
 /// 从网络消息创建子弹（使用网络消息中的bullet_id）
@@ -1142,6 +1392,7 @@ pub fn spawn_bullet_with_id(
     target_pos: Vec2,
     velocity: Vec2,
     sync_id: u64,
+    weapon: crate::weapons::WeaponKind,
 ) {
     // 枪口闪光：在两个渲染层都显示（进攻方和防守方都能看到）
     let muzzle_flash_pos = attacker_pos.extend(6.0);
@@ -1180,7 +1431,9 @@ pub fn spawn_bullet_with_id(
                 velocity, 
                 start_pos: attacker_pos, 
                 target_pos,
-                owner: attacker_id 
+                owner: attacker_id,
+                weapon,
+                hit_wall: false,
             },
         BulletSyncId(sync_id), // 使用网络消息中的同步ID
             SpriteBundle {
@@ -1203,7 +1456,9 @@ pub fn spawn_bullet_with_id(
             velocity, 
             start_pos: attacker_pos, 
             target_pos,
-            owner: attacker_id 
+            owner: attacker_id,
+            weapon,
+            hit_wall: false,
         },
         BulletSyncId(sync_id), // 使用网络消息中的同步ID
         SpriteBundle {
@@ -1225,10 +1480,11 @@ pub fn spawn_bullet_with_id(
 pub fn defender_move_system(
     time: Res<Time>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &PlayerRole, &Collider, &PlayerId)>,
+    mut query: Query<(&mut Transform, &PlayerRole, &Collider, &PlayerId, &mut crate::stamina::Stamina), Without<DefenderAI>>, // 电脑防守方不受键盘控制
     view_config: Res<crate::ViewConfig>,
     room_info: Option<Res<crate::RoomInfo>>,
     app_state: Res<State<crate::AppState>>,
+    (rules, arena): (Res<GameRules>, Res<crate::arena::Arena>),
 ) {
     // 如果游戏已结束，不允许移动
     if *app_state.get() == crate::AppState::GameOver {
@@ -1256,7 +1512,7 @@ pub fn defender_move_system(
         None // 本地模式下，不限制玩家ID
     };
     
-    for (mut transform, role, collider, player_id) in query.iter_mut() {
+    for (mut transform, role, collider, player_id, mut stamina) in query.iter_mut() {
         if let PlayerRole::Defender = role {
             // 网络模式下，只允许操控本地玩家的防守方角色
             if is_network_mode {
@@ -1276,29 +1532,28 @@ pub fn defender_move_system(
             if keyboard_input.pressed(KeyCode::KeyA) { move_direction.x -= 1.0; }
             if keyboard_input.pressed(KeyCode::KeyD) { move_direction.x += 1.0; }
             
+            // 按住左 Shift 冲刺（体力用完后恢复正常速度，体力在 stamina_system 中扣除）
+            stamina.sprinting = keyboard_input.pressed(crate::stamina::SPRINT_KEY)
+                && stamina.can_sprint()
+                && move_direction.length_squared() > 0.0;
+            
             // 如果没有输入，跳过移动（但不跳过后续处理）
             if move_direction.length_squared() > 0.0 {
                 move_direction = move_direction.normalize();
             
             // 防守方移动速度是原来的80%
-            let defender_move_speed = PLAYER_MOVE_SPEED * 0.8;
+            let sprint = if stamina.sprinting { rules.sprint_multiplier } else { 1.0 };
+            let defender_move_speed = rules.player_move_speed * 0.8 * sprint;
             let movement = move_direction * defender_move_speed * time.delta_seconds();
                 let old_x = transform.translation.x;
                 let old_y = transform.translation.y;
                 let new_x = old_x + movement.x;
                 let new_y = old_y + movement.y;
             
-                // 限制移动范围（在墙的范围内）
-            let wall_half_width = WALL_SIZE.x / 2.0;
-            let player_half_width = collider.size.x / 2.0;
-            transform.translation.x = new_x.clamp(-wall_half_width + player_half_width, wall_half_width - player_half_width);
-            
-            let wall_half_height = WALL_SIZE.y / 2.0;
-            let player_half_height = collider.size.y / 2.0;
-            transform.translation.y = new_y.clamp(
-                WALL_POSITION.y - wall_half_height + player_half_height,
-                WALL_POSITION.y + wall_half_height - player_half_height
-            );
+                // 限制移动范围（在地图的防守方区域内）
+            let (bounds_min, bounds_max) = arena.defender_bounds(collider.size);
+            transform.translation.x = new_x.clamp(bounds_min.x, bounds_max.x);
+            transform.translation.y = new_y.clamp(bounds_min.y, bounds_max.y);
                 
                 // 调试：确认玩家位置已更新
                 if (transform.translation.x != old_x || transform.translation.y != old_y) && is_network_mode {
@@ -1316,11 +1571,12 @@ pub fn defender_move_system(
 pub fn defender_action_system(
     time: Res<Time>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &PlayerRole, &mut ActionCooldown, &mut Collider, &mut DodgeAction, &PlayerId)>,
+    mut query: Query<(&mut Transform, &PlayerRole, &mut ActionCooldown, &mut Collider, &mut DodgeAction, &PlayerId, &crate::stamina::Stamina), Without<DefenderAI>>,
     mut events: EventWriter<PlayerActionEvent>,
     view_config: Res<crate::ViewConfig>,
     room_info: Option<Res<crate::RoomInfo>>,
     app_state: Res<State<crate::AppState>>,
+    rules: Res<GameRules>,
 ) {
     // 如果游戏已结束，不允许动作
     if *app_state.get() == crate::AppState::GameOver {
@@ -1335,7 +1591,7 @@ pub fn defender_action_system(
         return; // 进攻方视角，不允许操控防守方
     }
     
-    for (_transform, role, mut cooldown, _collider, mut dodge_action, player_id) in query.iter_mut() {
+    for (_transform, role, mut cooldown, _collider, mut dodge_action, player_id, stamina) in query.iter_mut() {
         if let PlayerRole::Defender = role {
             // 网络模式下，只允许操控本地玩家的防守方角色
             if is_network_mode {
@@ -1355,9 +1611,9 @@ pub fn defender_action_system(
             let time_since_last_action = current_time - cooldown.last_action_time;
             
             if time_since_last_action >= cooldown.cooldown_duration {
-                // K键触发技能：随机选择下蹲或侧躲
+                // K键触发技能：随机选择下蹲或侧躲（体力不够侧躲时只能下蹲）
                 if keyboard_input.just_pressed(KeyCode::KeyK) {
-                    let action = if rand::thread_rng().gen_bool(0.5) {
+                    let action = if !stamina.can_side_dodge(&rules) || rand::thread_rng().gen_bool(0.5) {
                         DodgeAction::Crouch
                     } else {
                         if rand::thread_rng().gen_bool(0.5) {
@@ -1383,9 +1639,11 @@ pub fn action_timer_system(
         Query<(Entity, &mut ActionTimer, &mut Sprite)>,
         Query<(Entity, &mut Transform, &mut Collider, &mut DodgeAction, &PlayerId)>,
     )>,
+    rules: Res<GameRules>,
 ) {
     let mut action_timer_query = param_set.p0();
     let mut finished_actions = Vec::new();
+    let mut active_players = Vec::new(); // 动作仍在进行中的玩家（不重复位移）
     for (entity, mut timer, mut sprite) in action_timer_query.iter_mut() {
         timer.timer.tick(time.delta());
         match timer.action {
@@ -1401,6 +1659,8 @@ pub fn action_timer_system(
         if timer.timer.finished() {
             finished_actions.push((timer.player_id, timer.action));
             commands.entity(entity).despawn();
+        } else {
+            active_players.push(timer.player_id);
         }
     }
     
@@ -1413,14 +1673,15 @@ pub fn action_timer_system(
             }
         }
         
-        if *dodge_action != DodgeAction::None {
+        // 只在动作开始时位移一次并生成计时器，否则侧躲会每帧叠加位移
+        if *dodge_action != DodgeAction::None && !active_players.contains(player_id) {
             let transform_pos = transform.translation;
             new_actions.push((*player_id, *dodge_action, transform_pos, entity));
             
             match *dodge_action {
                 DodgeAction::Crouch => {}
-                DodgeAction::SideLeft => transform.translation.x -= SIDE_DODGE_DISTANCE,
-                DodgeAction::SideRight => transform.translation.x += SIDE_DODGE_DISTANCE,
+                DodgeAction::SideLeft => transform.translation.x -= rules.side_dodge_distance,
+                DodgeAction::SideRight => transform.translation.x += rules.side_dodge_distance,
                 _ => {}
             }
         }
@@ -1434,7 +1695,7 @@ pub fn action_timer_system(
         
         commands.spawn((
             ActionTimer { 
-                timer: Timer::from_seconds(ACTION_DURATION_SECONDS, TimerMode::Once), 
+                timer: Timer::from_seconds(rules.action_duration_seconds, TimerMode::Once), 
                 action,
                 player_id,
             },
@@ -1511,21 +1772,8 @@ pub fn update_humanoid_sprite_positions(
 ) {
     for (player_transform, player_id, dodge_action, mut collider) in player_query.iter_mut() {
         let player_pos = player_transform.translation;
-        let player_height = PLAYER_SIZE.y;
-        let player_width = PLAYER_SIZE.x;
-        
-        let is_crouching = matches!(dodge_action, DodgeAction::Crouch);
-        let current_height = if is_crouching { player_height * 0.7 } else { player_height };
-        
-        collider.size = Vec2::new(player_width, current_height);
-        
-        let head_height = current_height * 0.3;
-        let torso_height = current_height * 0.5;
-        let legs_height = current_height * 0.2;
-        
-        let head_y = player_pos.y + (current_height - head_height) / 2.0;
-        let torso_y = player_pos.y - (current_height - torso_height) / 2.0 + head_height / 2.0;
-        let legs_y = player_pos.y - (current_height - legs_height) / 2.0;
+        collider.size = Vec2::new(PLAYER_SIZE.x, body_height(dodge_action));
+        let rects = humanoid_part_rects(dodge_action);
         
         for (mut part_transform, mut part_sprite, humanoid_part) in humanoid_query.iter_mut() {
             if humanoid_part.player_id == *player_id {
@@ -1533,27 +1781,11 @@ pub fn update_humanoid_sprite_positions(
                     ViewLayer::AttackerView => 1.0,
                     ViewLayer::DefenderView => 2.0,
                 };
-                
-                match humanoid_part.part_type {
-                    HumanoidPartType::Head => {
-                        part_transform.translation.x = player_pos.x;
-                        part_transform.translation.y = head_y;
-                        part_transform.translation.z = z_pos;
-                        part_sprite.custom_size = Some(Vec2::new(player_width * 0.8, head_height));
-                    }
-                    HumanoidPartType::Torso => {
-                        part_transform.translation.x = player_pos.x;
-                        part_transform.translation.y = torso_y;
-                        part_transform.translation.z = z_pos;
-                        part_sprite.custom_size = Some(Vec2::new(player_width * 0.9, torso_height));
-                    }
-                    HumanoidPartType::Legs => {
-                        part_transform.translation.x = player_pos.x;
-                        part_transform.translation.y = legs_y;
-                        part_transform.translation.z = z_pos;
-                        part_sprite.custom_size = Some(Vec2::new(player_width * 0.7, legs_height));
-                    }
-                }
+                let Some(rect) = rects.iter().find(|rect| rect.part_type == humanoid_part.part_type) else {
+                    continue;
+                };
+                part_transform.translation = (player_pos.truncate() + rect.offset).extend(z_pos);
+                part_sprite.custom_size = Some(rect.size);
             }
         }
     }
@@ -1564,8 +1796,10 @@ pub fn update_humanoid_sprite_positions(
 pub fn bullet_movement_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut bullet_query: Query<(Entity, &Bullet, &mut Transform, &BulletSyncId), With<Bullet>>,
+    mut bullet_query: Query<(Entity, &mut Bullet, &mut Transform, &BulletSyncId)>,
     q_window: Query<&Window, With<PrimaryWindow>>,
+    round_info: Res<RoundInfo>,
+    rules: Res<GameRules>,
 ) {
     let window = q_window.single();
     let half_width = window.width() / 2.0;
@@ -1576,8 +1810,12 @@ pub fn bullet_movement_system(
     let mut to_despawn: Vec<Entity> = Vec::new();
     let mut despawned_ids: Vec<BulletSyncId> = Vec::new();
     
+    // 弹道规则：重力和风改变子弹速度（未开启时为零）
+    let acceleration = crate::ballistics::acceleration(&rules, round_info.wind);
+    
     // 第一遍：计算新位置并检查边界
-    for (entity, bullet, mut transform, sync_id) in bullet_query.iter_mut() {
+    for (entity, mut bullet, mut transform, sync_id) in bullet_query.iter_mut() {
+        bullet.velocity += acceleration * time.delta_seconds();
         transform.translation.x += bullet.velocity.x * time.delta_seconds();
         transform.translation.y += bullet.velocity.y * time.delta_seconds();
 
@@ -1623,125 +1861,159 @@ pub fn muzzle_flash_system(
 }
 
 /// 碰撞检测系统
+/// 每个子弹副本只打自己渲染层对应视角的墙（layer 0 为进攻方视角，layer 1 为防守方视角），
+/// 每发子弹只在落点处破一次墙：弹孔范围内的砖块按武器的破墙伤害扣耐久，耐久归零才碎；
+/// 前后几层墙由前往后依次打，打碎了这一层弹孔中心的砖块才能接着打后面一层
 pub fn collision_detection_system(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform, &Collider, &Bullet)>,
-    mut wall_segment_query: Query<(Entity, &mut WallSegment, &mut Sprite, &mut Visibility, &Transform, &Collider), With<WallSegment>>,
+    mut bullet_query: Query<(Entity, &Transform, &mut Bullet, &RenderLayers)>,
+    mut wall_segment_query: Query<(Entity, &mut WallSegment, &mut Sprite, &mut Visibility, &Transform, &Collider)>,
     mut wall_query: Query<&mut Wall>,
     mut broken_wall_data: Option<ResMut<crate::BrokenWallData>>,
-    room_info: Option<Res<crate::RoomInfo>>,
+    (room_info, rules): (Option<Res<crate::RoomInfo>>, Res<GameRules>),
+    cover_query: CoverQuery,
 ) {
-    for (bullet_entity, bullet_transform, _bullet_collider, bullet) in bullet_query.iter() {
+    let is_host = room_info.as_ref().map(|room| room.is_host);
+    for (bullet_entity, bullet_transform, mut bullet, render_layers) in bullet_query.iter_mut() {
         let bullet_pos = bullet_transform.translation;
-        let mut bullet_hit_something = false;
-        let mut bullet_can_pass = false;
+        let view_layer = if render_layers.intersects(&RenderLayers::layer(0)) {
+            ViewLayer::AttackerView
+        } else {
+            ViewLayer::DefenderView
+        };
 
         let bullet_start = bullet.start_pos;
         let bullet_target = bullet.target_pos;
-        
-        let mut hit_wall_segment_pos = None;
-        for (_segment_entity, segment, _sprite, _visibility, segment_transform, segment_collider) in wall_segment_query.iter_mut() {
-            if segment.damaged { continue; }
-            
-            let segment_pos = segment_transform.translation.truncate();
-            
-            let is_ray_hit_wall = check_line_collision(
-                bullet_start, 
-                bullet_target,
-                segment_pos,
-                segment_collider.size,
-            );
-            
-            let distance_to_target = (segment_pos - bullet_target).length();
-            let is_near_target = distance_to_target < CROSSHAIR_DAMAGE_RANGE;
 
-            if is_ray_hit_wall && is_near_target && hit_wall_segment_pos.is_none() {
-                hit_wall_segment_pos = Some(segment_pos);
+        // 前后几层墙由前往后依次打（深度小的在前）
+        let mut depths: Vec<u8> = wall_segment_query
+            .iter()
+            .filter(|(_, segment, ..)| !bullet.hit_wall && !segment.damaged && segment.view_layer == view_layer)
+            .map(|(_, segment, ..)| segment.depth)
+            .collect();
+        depths.sort_unstable();
+        depths.dedup();
+
+        // 破墙范围、砖块数和破墙伤害由武器决定
+        let weapon = crate::weapons::Weapon::of(bullet.weapon, &rules);
+        let damage_range = weapon.hole_radius();
+        let mut bullet_stopped = false;
+
+        for depth in depths {
+            // 取这一层离落点最近的砖块作为弹孔中心，保证子弹路径上的砖块一定被打到
+            let mut hit_wall_segment: Option<(Entity, Vec2)> = None;
+            for (segment_entity, segment, _sprite, _visibility, segment_transform, segment_collider) in wall_segment_query.iter() {
+                if segment.damaged || segment.view_layer != view_layer || segment.depth != depth { continue; }
+                
+                let segment_pos = segment_transform.translation.truncate();
+                
+                let is_ray_hit_wall = check_line_collision(
+                    bullet_start, 
+                    bullet_target,
+                    segment_pos,
+                    segment_collider.size,
+                );
+                
+                let distance_to_target = (segment_pos - bullet_target).length();
+                let is_near_target = distance_to_target < rules.crosshair_damage_range;
+
+                let is_closer = hit_wall_segment.is_none_or(|(_, pos)| distance_to_target < (pos - bullet_target).length());
+                if is_ray_hit_wall && is_near_target && is_closer {
+                    hit_wall_segment = Some((segment_entity, segment_pos));
+                }
             }
-        }
 
-        if let Some(hit_pos) = hit_wall_segment_pos {
-            let brick_width = BRICK_WIDTH;
-            let damage_range = brick_width * 1.5;
+            let Some((hit_entity, hit_pos)) = hit_wall_segment else {
+                continue; // 这一层在落点处是空的（破洞或没有墙）
+            };
+            bullet.hit_wall = true;
+            let mut bullet_can_pass = false;
             
+            // 由近到远打这一层弹孔范围内的砖块
             let mut segments_to_damage = Vec::new();
-            for (_segment_entity, segment, _sprite, _visibility, segment_transform, _) in wall_segment_query.iter() {
-                if segment.damaged { continue; }
+            for (segment_entity, segment, _sprite, _visibility, segment_transform, _) in wall_segment_query.iter() {
+                if segment.damaged || segment.view_layer != view_layer || segment.depth != depth { continue; }
                 
                 let segment_pos = segment_transform.translation.truncate();
                 let distance_to_hit = (segment_pos - hit_pos).length();
                 if distance_to_hit < damage_range {
-                    segments_to_damage.push(segment_pos);
-                    if segments_to_damage.len() >= 3 {
-                        break;
-                    }
+                    segments_to_damage.push((distance_to_hit, segment_entity));
                 }
             }
+            segments_to_damage.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-            for hit_segment_pos in segments_to_damage {
-                for (_segment_entity, mut segment, mut sprite, mut visibility, segment_transform, _) in wall_segment_query.iter_mut() {
-                    if segment.damaged { continue; }
-                    
-                    let segment_pos = segment_transform.translation.truncate();
-                    if (segment_pos - hit_segment_pos).length() < 1.0 {
-                        segment.damaged = true;
-                        
-                        // 记录破碎墙体位置（主机和客户端分别存储）
-                        if let (Some(mut broken_data), Some(room)) = (broken_wall_data.as_mut(), room_info.as_ref()) {
-                            if room.is_host {
-                                // 主机：根据当前视角存储
-                                if !broken_data.host_broken_segments.contains(&segment_pos) {
-                                    broken_data.host_broken_segments.push(segment_pos);
-                                    // 调试输出已禁用: println!("[破碎墙体] 主机记录破碎墙体位置: {:?} (视角: {:?})", segment_pos, segment.view_layer);
-                                }
-                            } else {
-                                // 客户端：根据当前视角存储
-                                if !broken_data.client_broken_segments.contains(&segment_pos) {
-                                    broken_data.client_broken_segments.push(segment_pos);
-                                    // 调试输出已禁用: println!("[破碎墙体] 客户端记录破碎墙体位置: {:?} (视角: {:?})", segment_pos, segment.view_layer);
-                                }
-                            }
-                        }
-                        
-                        match segment.view_layer {
-                            ViewLayer::AttackerView => {
-                                sprite.color = Color::rgba(0.0, 0.0, 0.0, 0.0);
-                                *visibility = Visibility::Hidden;
-                            }
-                            ViewLayer::DefenderView => {
-                                sprite.color = Color::rgba(0.0, 0.0, 0.0, 0.8);
-                                *visibility = Visibility::Visible;
-                                
-                                commands.spawn((
-                                    SpriteBundle {
-                                        sprite: Sprite { color: Color::rgba(0.0, 0.0, 0.0, 0.9), custom_size: Some(Vec2::new(30.0, 30.0)), ..default() },
-                                        transform: Transform::from_translation(segment_transform.translation + Vec3::new(0.0, 0.0, 0.5)),
-                                        ..default()
-                                    },
-                                    RenderLayers::layer(1),
-                                ));
-                            }
+            for (_, segment_entity) in segments_to_damage.into_iter().take(weapon.bricks_destroyed) {
+                let Ok((_, mut segment, mut sprite, mut visibility, segment_transform, _)) = wall_segment_query.get_mut(segment_entity) else {
+                    continue;
+                };
+                let brick_key = (segment.depth, segment_transform.translation.truncate());
+                let lost = segment.take_hit(weapon.wall_damage);
+                if !segment.damaged {
+                    // 没打碎：钢板毫发无损，其他材质留下裂纹（颜色变暗）
+                    if lost > 0 {
+                        sprite.color = BrickSpec::cracked_color(sprite.color, lost);
+                        if let (Some(broken_data), Some(is_host)) = (broken_wall_data.as_mut(), is_host) {
+                            broken_data.record_worn(is_host, brick_key, segment.hit_points);
                         }
+                    }
+                    continue;
+                }
+
+                // 记录破碎墙体位置（主机和客户端分别存储）
+                if let (Some(broken_data), Some(is_host)) = (broken_wall_data.as_mut(), is_host) {
+                    broken_data.record_broken(is_host, brick_key);
+                    // 调试输出已禁用: println!("[破碎墙体] 记录破碎墙体位置: {:?} (视角: {:?})", brick_key, segment.view_layer);
+                }
+                
+                match segment.view_layer {
+                    ViewLayer::AttackerView => {
+                        sprite.color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+                        *visibility = Visibility::Hidden;
+                    }
+                    ViewLayer::DefenderView => {
+                        sprite.color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+                        *visibility = Visibility::Visible;
                         
-                        bullet_can_pass = true;
-                        bullet_hit_something = true;
+                        commands.spawn((
+                            BulletHole { position: segment_transform.translation.truncate(), depth: segment.depth },
+                            SpriteBundle {
+                                sprite: Sprite { color: Color::rgba(0.0, 0.0, 0.0, 0.9), custom_size: Some(Vec2::new(30.0, 30.0)), ..default() },
+                                transform: Transform::from_translation(segment_transform.translation + Vec3::new(0.0, 0.0, 0.5)),
+                                ..default()
+                            },
+                            RenderLayers::layer(1),
+                        ));
                     }
                 }
+                
+                // 打碎了弹孔中心的砖块才能飞过去（玻璃碎了也会挡下这一发）
+                if segment_entity == hit_entity && segment.spec.penetration_cost().is_some() {
+                    bullet_can_pass = true;
+                }
             }
 
-            if let Ok(mut wall) = wall_query.get_single_mut() {
+            let hit_wall_entity = wall_segment_query.get(hit_entity).ok().map(|(_, segment, ..)| segment.wall_entity);
+            if let Some(mut wall) = hit_wall_entity.and_then(|entity| wall_query.get_mut(entity).ok()) {
                 wall.damaged = true;
                 wall.damage_positions = hit_pos;
             }
+
+            // 这一层没打穿就停下，打穿了接着打后面一层
+            if !bullet_can_pass {
+                bullet_stopped = true;
+                break;
+            }
         }
 
-        if bullet_hit_something && !bullet_can_pass {
+        if bullet_stopped {
             commands.entity(bullet_entity).despawn();
             continue;
         }
         
         let bullet_distance = (bullet_pos.truncate() - bullet.start_pos).length();
-        if bullet_distance > 1500.0 {
+        // 落点在掩体上的子弹飞到落点就停下
+        let reached_target = bullet_distance >= (bullet.target_pos - bullet.start_pos).length();
+        if bullet_distance > 1500.0 || (reached_target && covered_by_prop(bullet.target_pos, view_layer, &cover_query)) {
             commands.entity(bullet_entity).despawn();
         }
     }
@@ -1912,7 +2184,7 @@ pub fn round_timer_update_system(
 pub fn check_win_condition_system(
     _query: Query<(&PlayerId, &Health)>,
 ) {
-    // 游戏结束检查现在在 attacker_shoot_system 中完成
+    // 游戏结束检查现在在 scoring::match_decision_system 和回合结束结算中完成
 }
 
 /// 延迟回合切换系统
@@ -1921,10 +2193,17 @@ pub fn check_win_condition_system(
 pub fn delayed_round_switch_system(
     mut round_info: ResMut<RoundInfo>,
     mut next_round_state: ResMut<NextState<RoundState>>,
-    bullet_query: Query<Entity, With<Bullet>>,
+    bullet_query: Query<(), Or<(With<Bullet>, With<crate::projectile::PendingShot>)>>, // 弹道判定下还要等子弹到达后结算
     room_info: Option<Res<crate::RoomInfo>>,
     network_manager: Option<Res<crate::network_game::NetworkManager>>,
+    mut game_over_delay: ResMut<GameOverDelay>,
+    (rules, mut tuning_panel): (Res<GameRules>, ResMut<crate::tuning::TuningPanel>),
 ) {
+    // 比赛已经分出胜负，等待进入结算画面，不再换边
+    if game_over_delay.timer.is_some() {
+        return;
+    }
+    
     // 检查是否需要切换角色的条件：
     // 1. 所有子弹都消失了（bullet_query.is_empty()）
     // 2. 且（三发子弹打完 或 30秒到了）
@@ -1948,6 +2227,14 @@ pub fn delayed_round_switch_system(
             round_info.is_switching = true;
         // 调试输出已禁用: println!("[角色切换] 触发条件满足：子弹用完={}, 时间到期={}, 子弹剩余={}, 子弹消失={}", bullets_exhausted, time_expired, round_info.bullets_left, bullets_finished);
         
+        // 进攻回合结束：本地模式和主机按赛制结算比分，分出胜负时结束比赛（客户端等待主机的 GameOver 消息）
+        if crate::scoring::is_match_authority(room_info.as_deref()) {
+            if let Some(winner_id) = crate::scoring::end_attack_turn(&mut round_info, rules.match_format) {
+                crate::scoring::start_game_over(&mut game_over_delay, winner_id);
+                return;
+            }
+        }
+        
         // 网络模式下，主机发送角色切换消息
         if let (Some(room_info), Some(network_manager)) = (room_info.as_ref(), network_manager.as_ref()) {
             if room_info.is_connected && network_manager.is_host {
@@ -1961,6 +2248,8 @@ pub fn delayed_round_switch_system(
                 round_info.current_attacker = new_attacker;
                 
                 // 发送角色切换消息给客户端
+                // 先发调参面板的修改，客户端在处理 SwitchRoles 之前就排好新规则
+                crate::tuning::queue_pending_rules_for_switch(&mut tuning_panel, &rules, network_manager);
                 let switch_msg = crate::network_game::NetworkMessage::SwitchRoles {
                     new_attacker,
                 };
@@ -1994,6 +2283,8 @@ pub fn delayed_round_switch_system(
                     PlayerId::Player2 => PlayerId::Player1,
                 };
                 round_info.current_attacker = new_attacker;
+                // 先发调参面板的修改，客户端在处理 SwitchRoles 之前就排好新规则
+                crate::tuning::queue_pending_rules_for_switch(&mut tuning_panel, &rules, network_manager);
                 let switch_msg = crate::network_game::NetworkMessage::SwitchRoles {
                     new_attacker,
                 };
@@ -2035,6 +2326,7 @@ pub fn switch_roles_system(
     mut camera_state_cache: ResMut<CameraStateCache>,
     room_info: Option<Res<crate::RoomInfo>>,
     mut camera_switch_writer: EventWriter<CameraSwitchEvent>,
+    (rules, arena): (Res<GameRules>, Res<crate::arena::Arena>),
 ) {
     // 调试输出已禁用: println!("[角色切换] ========== 开始切换角色 ==========");
     // 调试输出已禁用: println!("[角色切换] 当前 round_info.current_attacker = {:?}", round_info.current_attacker);
@@ -2095,19 +2387,21 @@ pub fn switch_roles_system(
             if *id == new_attacker {
                 // 这个玩家是新的进攻方
                 *role = PlayerRole::Attacker;
-                transform.translation = ATTACKER_START_POS;
+                transform.translation = arena.attacker_spawn();
                 collider.size = PLAYER_SIZE;
                 *dodge_action = DodgeAction::None;
                 // 移除防守方AI组件（如果存在）
                 commands.entity(entity).remove::<DefenderAI>();
-                // 调试输出已禁用: println!("  - 玩家 {:?} 现在是进攻方，位置: {:?}", id, ATTACKER_START_POS);
+                // 调试输出已禁用: println!("  - 玩家 {:?} 现在是进攻方，位置: {:?}", id, arena.attacker_spawn());
             } else {
                 // 这个玩家是新的防守方
                 *role = PlayerRole::Defender;
-                transform.translation = DEFENDER_START_POS;
+                transform.translation = arena.defender_spawn();
                 collider.size = PLAYER_SIZE;
                 *dodge_action = DodgeAction::None;
-                // 调试输出已禁用: println!("  - 玩家 {:?} 现在是防守方，位置: {:?}", id, DEFENDER_START_POS);
+                // 移除进攻方AI组件（如果存在）
+                commands.entity(entity).remove::<AttackerAI>();
+                // 调试输出已禁用: println!("  - 玩家 {:?} 现在是防守方，位置: {:?}", id, arena.defender_spawn());
             }
         }
         
@@ -2120,15 +2414,17 @@ pub fn switch_roles_system(
             PlayerRole::Attacker => {
                     // 原来的进攻方变成防守方
                 *role = PlayerRole::Defender;
-                transform.translation = DEFENDER_START_POS;
+                transform.translation = arena.defender_spawn();
                 collider.size = PLAYER_SIZE;
                 *dodge_action = DodgeAction::None;
+                    // 移除进攻方AI组件（如果存在）
+                commands.entity(entity).remove::<AttackerAI>();
                     // 调试输出已禁用: println!("  - 玩家 {:?} 从进攻方变成防守方", id);
             }
             PlayerRole::Defender => {
                     // 原来的防守方变成进攻方
                 *role = PlayerRole::Attacker;
-                transform.translation = ATTACKER_START_POS;
+                transform.translation = arena.attacker_spawn();
                 collider.size = PLAYER_SIZE;
                 *dodge_action = DodgeAction::None;
                     new_attacker = *id;
@@ -2142,7 +2438,7 @@ pub fn switch_roles_system(
     };
     
     // 重置回合信息
-    round_info.bullets_left = BULLETS_PER_ROUND;
+    round_info.bullets_left = rules.bullets_per_round;
     round_info.round_timer.reset();
     round_info.current_attacker = new_attacker_id;
     round_info.bullets_fired_this_round = 0;
@@ -2266,9 +2562,8 @@ pub fn wall_visibility_update_system(
                     
                     let segment_pos = segment_transform.translation.truncate();
                     for (background, mut bg_visibility) in wall_background_query.iter_mut() {
-                        if background.view_layer == ViewLayer::AttackerView {
-                            let bg_pos = Vec2::new(background.position.x, WALL_POSITION.y + background.position.y);
-                            if (bg_pos - segment_pos).length() < 1.0 {
+                        if background.view_layer == ViewLayer::AttackerView && background.depth == segment.depth {
+                            if (background.position - segment_pos).length() < 1.0 {
                                 *bg_visibility = Visibility::Hidden;
                             }
                         }
@@ -2284,23 +2579,13 @@ pub fn wall_visibility_update_system(
             
             let segment_pos = segment_transform.translation.truncate();
             for (background, mut bg_visibility) in wall_background_query.iter_mut() {
-                if background.view_layer == segment.view_layer {
-                    let bg_pos = Vec2::new(background.position.x, WALL_POSITION.y + background.position.y);
-                    if (bg_pos - segment_pos).length() < 1.0 {
+                if background.view_layer == segment.view_layer && background.depth == segment.depth {
+                    if (background.position - segment_pos).length() < 1.0 {
                         *bg_visibility = Visibility::Visible;
                     }
                 }
             }
-            let brick_width = BRICK_WIDTH;
-            let brick_col = ((segment_transform.translation.x + WALL_SIZE.x / 2.0) / brick_width) as i32;
-            let brick_row = ((segment_transform.translation.y - WALL_POSITION.y + WALL_SIZE.y / 2.0) / BRICK_HEIGHT) as i32;
-            
-            let brick_colors = vec![
-                Color::rgb(0.8, 0.6, 0.5),
-                Color::rgb(0.5, 0.35, 0.25),
-            ];
-            let brick_index = ((brick_col + brick_row) as usize) % brick_colors.len();
-            sprite.color = brick_colors[brick_index];
+            // 完好砖块的颜色（材质和裂纹）在创建和受击时设置，这里不再改动
         }
     }
 }
@@ -2334,8 +2619,9 @@ pub fn defender_visibility_system(
 pub fn update_laser_indicator_system(
     mut laser_query: Query<(&mut Transform, &mut Sprite), With<LaserIndicator>>,
     cursor_pos: Res<CursorPosition>,
+    arena: Res<crate::arena::Arena>,
 ) {
-    let attacker_pos = ATTACKER_START_POS.truncate();
+    let attacker_pos = arena.aim_origin();
     let target_pos = cursor_pos.0;
     let laser_direction = (target_pos - attacker_pos).normalize_or_zero();
     let laser_angle = laser_direction.y.atan2(laser_direction.x);
@@ -2359,12 +2645,13 @@ pub fn update_laser_visibility_system(
     )>,
     cursor_pos: Res<CursorPosition>,
     round_info: Res<RoundInfo>,
+    arena: Res<crate::arena::Arena>,
 ) {
     const TRUNCATE_OFFSET: f32 = 3.0;
     const MIN_LASER_LENGTH: f32 = 5.0;
 
     let attacker_query = param_set.p1();
-    let mut attacker_pos = ATTACKER_START_POS.truncate();
+    let mut attacker_pos = arena.aim_origin();
     let mut found_attacker = false;
     for (transform, role, id) in attacker_query.iter() {
         if matches!(role, PlayerRole::Attacker) && *id == round_info.current_attacker {
@@ -2389,7 +2676,8 @@ pub fn update_laser_visibility_system(
 
     let wall_segment_query = param_set.p2();
     for (wall_transform, wall_collider, wall_segment) in wall_segment_query.iter() {
-        if wall_segment.damaged {
+        // 激光能穿过破洞和玻璃
+        if !wall_segment.blocks_sight() {
             continue;
         }
         let wall_pos = wall_transform.translation.truncate();
@@ -2644,7 +2932,7 @@ fn laser_segments_intersect(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
 }
 
 /// 激光专用线段-轴对齐矩形相交判断
-fn laser_segment_rect_intersects(p1: Vec2, p2: Vec2, rect: (Vec2, Vec2)) -> bool {
+pub(crate) fn laser_segment_rect_intersects(p1: Vec2, p2: Vec2, rect: (Vec2, Vec2)) -> bool {
     let (rect_min, rect_max) = rect;
 
     let seg_min = Vec2::new(p1.x.min(p2.x), p1.y.min(p2.y));
@@ -2830,6 +3118,9 @@ pub fn setup_gameover_screen(
     mut camera_query: Query<(Entity, &mut Camera), (With<Camera2d>, With<IsDefaultUiCamera>)>,
     defender_ui_root_query: Query<Entity, With<crate::DefenderUIRoot>>,
     game_over_delay: Option<Res<GameOverDelay>>,
+    round_info: Option<Res<RoundInfo>>,
+    rules: Res<GameRules>,
+    arena: Res<crate::arena::Arena>,
 ) {
     // 首先尝试从事件读取，如果读取不到，从资源读取
     let game_over_event = if let Some(event) = reader.read().next() {
@@ -2870,6 +3161,15 @@ pub fn setup_gameover_screen(
         PlayerId::Player2 => "玩家2",
     };
     // 调试输出已禁用: println!("[游戏结束调试] 获胜者文本: {}", winner_text);
+    let score_text = round_info
+        .as_ref()
+        .map(|round_info| crate::scoring::score_summary(rules.match_format, &round_info.score))
+        .unwrap_or_default();
+    // 生成的地图显示种子，之后用 --map seed:<种子> 可以重玩同一张地图
+    let seed_text = arena
+        .seed
+        .map(|seed| format!("地图种子: {}（启动时加 --map seed:{} 重玩这张地图）\n\n", seed, seed))
+        .unwrap_or_default();
     
     let font = font_resource.font.clone();
     
@@ -2883,6 +3183,11 @@ pub fn setup_gameover_screen(
         font_size: 32.0,
         color: Color::WHITE,
     };
+    let score_text_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.0,
+        color: Color::rgb(1.0, 0.9, 0.5),
+    };
     let hint_text_style = TextStyle {
         font: font.clone(),
         font_size: 24.0,
@@ -2922,6 +3227,8 @@ pub fn setup_gameover_screen(
         parent.spawn(TextBundle::from_sections([
             TextSection::new("游戏结束\n\n".to_string(), text_style.clone()),
             TextSection::new(format!("获胜者: {}\n\n", winner_text), sub_text_style.clone()),
+            TextSection::new(format!("{}\n\n", score_text), score_text_style.clone()),
+            TextSection::new(seed_text.clone(), score_text_style.clone()),
         ]));
         
         // 按钮容器（网络模式）
@@ -3014,6 +3321,8 @@ pub fn setup_gameover_screen(
                         game_over_parent.spawn(TextBundle::from_sections([
                             TextSection::new("游戏结束\n\n".to_string(), text_style.clone()),
                             TextSection::new(format!("获胜者: {}\n\n", winner_text), sub_text_style.clone()),
+                            TextSection::new(format!("{}\n\n", score_text), score_text_style.clone()),
+                            TextSection::new(seed_text.clone(), score_text_style.clone()),
                         ]));
                         
                         game_over_parent.spawn(NodeBundle {
@@ -3092,6 +3401,7 @@ pub fn setup_gameover_screen(
                 parent.spawn(TextBundle::from_sections([
                     TextSection::new("游戏结束\n\n".to_string(), text_style.clone()),
                     TextSection::new(format!("获胜者: {}\n\n", winner_text), sub_text_style.clone()),
+                    TextSection::new(format!("{}\n\n", score_text), score_text_style.clone()),
                 ]));
                 
                 parent.spawn(NodeBundle {
@@ -0,0 +1,108 @@
+// 命中判定调试：按 F4 在两个视角中画出每个玩家身体各部位的判定框
+//
+// 判定框与命中判定使用同一套几何形状（gameplay::humanoid_part_rects），随下蹲、侧躲实时变化，
+// 头部红色、躯干黄色、腿部绿色，画在墙和人物之上。
+// 判定框画在墙上面，进攻方视角中能透过完好的墙看到防守方，只在本地对战和单人练习中可用：
+// 联机时按 F4 不起作用，已经打开的判定框也会移除。
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::gameplay::{humanoid_part_rects, DodgeAction, HumanoidPartType, ViewLayer};
+use crate::{PlayerId, RoomInfo};
+
+/// 开关判定框的按键
+const TOGGLE_KEY: KeyCode = KeyCode::F4;
+/// 判定框的 Z（在墙、人物和道具之上）
+const HITBOX_Z: f32 = 3.5;
+/// 判定框的不透明度
+const HITBOX_ALPHA: f32 = 0.35;
+
+/// 是否显示判定框
+#[derive(Resource, Default)]
+pub struct HitboxDebug {
+    pub enabled: bool,
+}
+
+/// 一个玩家一个部位在一个视角中的判定框
+#[derive(Component)]
+pub struct HitboxOverlay {
+    player_id: PlayerId,
+    part_type: HumanoidPartType,
+}
+
+fn part_color(part_type: HumanoidPartType) -> Color {
+    match part_type {
+        HumanoidPartType::Head => Color::rgba(1.0, 0.1, 0.1, HITBOX_ALPHA),
+        HumanoidPartType::Torso => Color::rgba(1.0, 0.9, 0.1, HITBOX_ALPHA),
+        HumanoidPartType::Legs => Color::rgba(0.1, 1.0, 0.2, HITBOX_ALPHA),
+    }
+}
+
+/// 判定框能否显示（联机时会让进攻方看穿墙，不允许）
+fn overlay_allowed(debug: &HitboxDebug, room_info: &RoomInfo) -> bool {
+    debug.enabled && !room_info.is_connected
+}
+
+/// 按 F4 开关判定框（只在本地对战和单人练习中）
+pub fn toggle_hitbox_debug_system(keyboard_input: Res<ButtonInput<KeyCode>>, room_info: Res<RoomInfo>, mut debug: ResMut<HitboxDebug>) {
+    if room_info.is_connected {
+        debug.enabled = false;
+        return;
+    }
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        debug.enabled = !debug.enabled;
+        // 调试输出已禁用: println!("[判定框] {}", if debug.enabled { "显示" } else { "隐藏" });
+    }
+}
+
+/// 画出判定框：打开时为每个玩家的每个部位在两个视角各建一个半透明方块，之后每帧跟随位置和动作，关闭时移除
+pub fn update_hitbox_overlay_system(
+    mut commands: Commands,
+    (debug, room_info): (Res<HitboxDebug>, Res<RoomInfo>),
+    player_query: Query<(&Transform, &PlayerId, &DodgeAction), Without<HitboxOverlay>>,
+    mut overlay_query: Query<(Entity, &HitboxOverlay, &mut Transform, &mut Sprite)>,
+) {
+    if !overlay_allowed(&debug, &room_info) {
+        for (entity, ..) in overlay_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if overlay_query.is_empty() {
+        for (transform, player_id, dodge_action) in player_query.iter() {
+            let position = transform.translation.truncate();
+            for rect in humanoid_part_rects(dodge_action) {
+                for view_layer in [ViewLayer::AttackerView, ViewLayer::DefenderView] {
+                    let render_layer = match view_layer {
+                        ViewLayer::AttackerView => RenderLayers::layer(0),
+                        ViewLayer::DefenderView => RenderLayers::layer(1),
+                    };
+                    commands.spawn((
+                        HitboxOverlay { player_id: *player_id, part_type: rect.part_type },
+                        SpriteBundle {
+                            sprite: Sprite { color: part_color(rect.part_type), custom_size: Some(rect.size), ..default() },
+                            transform: Transform::from_translation((position + rect.offset).extend(HITBOX_Z)),
+                            ..default()
+                        },
+                        render_layer,
+                    ));
+                }
+            }
+        }
+        return;
+    }
+    for (transform, player_id, dodge_action) in player_query.iter() {
+        let position = transform.translation.truncate();
+        let rects = humanoid_part_rects(dodge_action);
+        for (_, overlay, mut overlay_transform, mut sprite) in overlay_query.iter_mut() {
+            if overlay.player_id != *player_id {
+                continue;
+            }
+            let Some(rect) = rects.iter().find(|rect| rect.part_type == overlay.part_type) else {
+                continue;
+            };
+            overlay_transform.translation = (position + rect.offset).extend(HITBOX_Z);
+            sprite.custom_size = Some(rect.size);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+// 库目标导出网络协议、对局规则、砖块材质、武器数值、地图和地图生成模块，供 fuzz 目标和会合/中继服务器（src/bin/rendezvous_server.rs）使用
+// 游戏本体仍由 main.rs 构建
+
+pub mod map;
+pub mod mapgen;
+pub mod materials;
+pub mod protocol;
+pub mod rules;
+pub mod weapon_stats;
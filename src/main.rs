@@ -17,6 +17,24 @@ mod menu;
 mod network;
 mod room;
 mod network_game;
+mod capture;
+mod anti_cheat;
+mod ballistics;
+mod bot;
+mod rendezvous;
+mod game_rules;
+mod arena;
+mod map_editor;
+mod tuning;
+mod projectile;
+mod scope;
+mod scoring;
+mod weapons;
+mod utility;
+mod recon;
+mod repair;
+mod stamina;
+mod hitbox_debug;
 
 use gameplay::*;
 use gameplay::{CameraStateCache, LastRoleState};
@@ -24,38 +42,25 @@ use menu::*;
 use network::*;
 use room::*;
 use network_game::*;
+use game_rules::GameRules;
+use arena::{Arena, WallDef};
+use bevy_sniper_duel::materials::BrickSpec;
+use bevy_sniper_duel::map::MAX_AIM_OFFSET;
 
-// --- 核心常量 ---
-const PLAYER_HP: f32 = 100.0;
-const ROUND_TIME_SECONDS: f32 = 30.0;
-const BULLETS_PER_ROUND: i32 = 3;
-const DODGE_COOLDOWN_SECONDS: f32 = 5.0;
-const ACTION_DURATION_SECONDS: f32 = 1.0;
-
-const DAMAGE_HEAD: f32 = 100.0;
-const DAMAGE_TORSO: f32 = 40.0;
-const DAMAGE_LEGS: f32 = 30.0;
+// 血量、回合时间、子弹数、冷却、伤害、速度等平衡数值见 GameRules（src/rules.rs）
 
 // --- 视觉和物理常量（含新增需求相关配置）---
-const PLAYER_SIZE: Vec2 = Vec2::new(50.0, 100.0);
-const BRICK_COLS: usize = 22; // 砖块列数
-const BRICK_ROWS: usize = 10; // 砖块行数
+// 玩家尺寸和瞄准范围也用于联机消息的校验，定义在 src/map.rs
+const PLAYER_SIZE: Vec2 = Vec2::from_array(bevy_sniper_duel::map::PLAYER_SIZE);
+// 墙的行列数、位置和双方出生点由地图决定（见 src/map.rs 和 assets/maps/）
 const BRICK_WIDTH: f32 = 40.0; // 单个砖块宽度（保持不变）
 const BRICK_HEIGHT: f32 = 31.25; // 单个砖块高度（保持不变）
-const WALL_SIZE: Vec2 = Vec2::new(BRICK_WIDTH * BRICK_COLS as f32, BRICK_HEIGHT * BRICK_ROWS as f32); // 墙面大小：22列×10行
-const WALL_POSITION: Vec3 = Vec3::new(0.0, 0.0, 0.0);
-const DEFENDER_START_POS: Vec3 = Vec3::new(0.0, -40.0, 1.0); // 防守方生成在墙中心开口处
-const ATTACKER_START_POS: Vec3 = Vec3::new(0.0, 200.0, 1.0); // 适配新墙体
+const WALL_POSITION: Vec3 = Vec3::new(0.0, 0.0, 0.0); // 防守方相机的位置（地图以原点为中心）
+const ATTACKER_START_POS: Vec3 = Vec3::new(0.0, 200.0, 1.0); // 角色切换时进攻方相机的初始位置（随后跟随瞄准点）
 const BULLET_SIZE: Vec2 = Vec2::new(8.0, 8.0);
-const BULLET_SPEED: f32 = 1200.0;
 const MUZZLE_FLASH_DURATION: f32 = 0.1;
-const PLAYER_MOVE_SPEED: f32 = 300.0;
-const AIM_SPEED: f32 = 300.0;
-const MAX_AIM_OFFSET: f32 = 500.0; // 适配新墙体瞄准范围
-const SIDE_DODGE_DISTANCE: f32 = 30.0;
 
 // 新增：需求相关常量
-const CROSSHAIR_DAMAGE_RANGE: f32 = 50.0; // 准心附近墙破坏范围
 const DEFENDER_CAMERA_OFFSET: Vec3 = Vec3::new(0.0, 0.0, 1000.0); // 摄像头跟随偏移
 
 // --- 系统集定义已移至 gameplay.rs ---
@@ -70,6 +75,7 @@ pub enum AppState {
     CreatingRoom,     // 创建房间（等待页面）
     JoiningRoom,      // 加入房间（输入房间号）
     InRoom,           // 在房间内（等待开始）
+    MapEditor,        // 地图编辑器
     Playing,          // 游戏中
     GameOver,         // 游戏结束
 }
@@ -82,17 +88,8 @@ enum RoundState {
 }
 
 // --- 组件定义 ---
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-pub enum PlayerId {
-    Player1,
-    Player2,
-}
-
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-pub enum PlayerRole {
-    Attacker,
-    Defender,
-}
+// PlayerId/PlayerRole 同时用于网络协议，定义在 protocol.rs
+pub use bevy_sniper_duel::protocol::{PlayerId, PlayerRole};
 
 // --- 游玩系统组件已移至 gameplay.rs ---
 
@@ -182,13 +179,89 @@ impl Default for RoleSwitchCooldown {
     }
 }
 
+/// 砖块在墙体数据中的键（墙的深度, 位置）：前后几层墙的砖块可能在同一个位置
+pub type BrickKey = (u8, Vec2);
+
 /// 存储破碎墙体的数据（主机和客户端分别存储）
 #[derive(Resource, Default)]
 pub struct BrokenWallData {
-    /// 主机视角的破碎墙体位置（ViewLayer::AttackerView 或 ViewLayer::DefenderView）
-    pub host_broken_segments: Vec<Vec2>,
-    /// 客户端视角的破碎墙体位置
-    pub client_broken_segments: Vec<Vec2>,
+    /// 主机视角的破碎砖块（ViewLayer::AttackerView 或 ViewLayer::DefenderView）
+    pub host_broken_segments: Vec<BrickKey>,
+    /// 客户端视角的破碎砖块
+    pub client_broken_segments: Vec<BrickKey>,
+    /// 主机视角打裂但没碎的砖块（砖块, 剩余耐久）
+    pub host_worn_segments: Vec<(BrickKey, u32)>,
+    /// 客户端视角打裂但没碎的砖块
+    pub client_worn_segments: Vec<(BrickKey, u32)>,
+    /// 主机视角被防守方修好的砖块（地图中的破洞和裂纹修好后也不再恢复）
+    pub host_repaired_segments: Vec<BrickKey>,
+    /// 客户端视角被防守方修好的砖块
+    pub client_repaired_segments: Vec<BrickKey>,
+}
+
+impl BrokenWallData {
+    fn lists(&self, is_host: bool) -> (&Vec<BrickKey>, &Vec<(BrickKey, u32)>) {
+        if is_host {
+            (&self.host_broken_segments, &self.host_worn_segments)
+        } else {
+            (&self.client_broken_segments, &self.client_worn_segments)
+        }
+    }
+
+    fn lists_mut(&mut self, is_host: bool) -> (&mut Vec<BrickKey>, &mut Vec<(BrickKey, u32)>) {
+        if is_host {
+            (&mut self.host_broken_segments, &mut self.host_worn_segments)
+        } else {
+            (&mut self.client_broken_segments, &mut self.client_worn_segments)
+        }
+    }
+
+    fn repaired_mut(&mut self, is_host: bool) -> &mut Vec<BrickKey> {
+        if is_host { &mut self.host_repaired_segments } else { &mut self.client_repaired_segments }
+    }
+
+    pub fn is_broken(&self, is_host: bool, brick: BrickKey) -> bool {
+        self.lists(is_host).0.contains(&brick)
+    }
+
+    pub fn is_repaired(&self, is_host: bool, brick: BrickKey) -> bool {
+        let repaired = if is_host { &self.host_repaired_segments } else { &self.client_repaired_segments };
+        repaired.contains(&brick)
+    }
+
+    /// 打裂的砖块剩余的耐久
+    pub fn worn_hit_points(&self, is_host: bool, brick: BrickKey) -> Option<u32> {
+        self.lists(is_host).1.iter().find(|(key, _)| *key == brick).map(|(_, hit_points)| *hit_points)
+    }
+
+    pub fn record_broken(&mut self, is_host: bool, brick: BrickKey) {
+        self.repaired_mut(is_host).retain(|key| *key != brick);
+        let (broken, worn) = self.lists_mut(is_host);
+        worn.retain(|(key, _)| *key != brick);
+        if !broken.contains(&brick) {
+            broken.push(brick);
+        }
+    }
+
+    pub fn record_worn(&mut self, is_host: bool, brick: BrickKey, hit_points: u32) {
+        self.repaired_mut(is_host).retain(|key| *key != brick);
+        let (_, worn) = self.lists_mut(is_host);
+        match worn.iter_mut().find(|(key, _)| *key == brick) {
+            Some(entry) => entry.1 = hit_points,
+            None => worn.push((brick, hit_points)),
+        }
+    }
+
+    /// 防守方修好了砖块：恢复完整耐久
+    pub fn record_repaired(&mut self, is_host: bool, brick: BrickKey) {
+        let (broken, worn) = self.lists_mut(is_host);
+        broken.retain(|key| *key != brick);
+        worn.retain(|(key, _)| *key != brick);
+        let repaired = self.repaired_mut(is_host);
+        if !repaired.contains(&brick) {
+            repaired.push(brick);
+        }
+    }
 }
 
 // --- 游玩系统资源和事件已移至 gameplay.rs ---
@@ -396,6 +469,17 @@ fn play_background_music(
 
 // --- 游戏主程序 ---
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // 命令行工具：查看/比较抓包文件，不启动游戏窗口
+    if args.get(1).map(String::as_str) == Some("inspect") {
+        std::process::exit(capture::run_inspect_command(&args[2..]));
+    }
+    capture::init_capture(&args);
+    rendezvous::init_rendezvous(&args);
+    let rules = game_rules::init_game_rules(&args);
+    let map_choice = arena::init_map_choice(&args);
+    let current_arena = map_choice.arena();
+
     let mut app = App::new();
     app.add_plugins(DefaultPlugins
         .set(LogPlugin {
@@ -434,8 +518,10 @@ fn main() {
     .init_resource::<gameplay::GameOverDelay>()
     .init_resource::<gameplay::UiStateTracker>() // UI状态跟踪资源（用于优化UI更新系统）
     .add_event::<PlayerActionEvent>()
+    .add_event::<gameplay::AttackerTriggerEvent>()
     .add_event::<gameplay::CameraSwitchEvent>()
     .add_event::<room::ReconnectEvent>()
+    .add_event::<tuning::RulesAppliedEvent>()
     // 2. 初始化游戏状态（Bevy 0.13 用 init_state，而非 add_state）
     .init_state::<AppState>()
     .init_state::<RoundState>()
@@ -451,6 +537,15 @@ fn main() {
     .init_resource::<RecreateGameEntitiesOnRoleSwitch>() // 初始化角色切换时重建游戏实体资源
     .init_resource::<LastRoleState>() // 初始化角色状态缓存（用于优化性能）
     .init_resource::<CameraStateCache>() // 初始化相机状态缓存（用于优化性能）
+    .init_resource::<anti_cheat::AntiCheatMonitor>() // 对方输入的反作弊监控
+    .init_resource::<hitbox_debug::HitboxDebug>() // 命中判定框调试开关（F4）
+    .init_resource::<bot::SinglePlayerSettings>() // 单人练习设置（主菜单选择）
+    .insert_resource(rules) // 对局规则（联机时客户端改用主机的规则）
+    .insert_resource(map_choice) // 本地选择的地图
+    .insert_resource(current_arena) // 本局地图（联机时客户端改用主机的地图）
+    .init_resource::<tuning::HostRulesHash>() // 客户端：主机当前规则的指纹（核对规则是否一致）
+    .init_resource::<tuning::TuningPanel>() // 调参面板状态（未生效的修改、预设名）
+    .init_resource::<map_editor::MapEditor>() // 地图编辑器状态（试玩回来后继续编辑）
     // 4. 配置系统集（确保所有变体存在，只在Playing状态下运行）
     .configure_sets(
             Update,
@@ -485,6 +580,18 @@ fn main() {
         app_state.set(AppState::Playing);
     })
     
+    // 地图编辑器（试玩走本地双人的流程，结束后回到主菜单时再回到编辑器）
+    .add_systems(OnEnter(AppState::MapEditor), map_editor::setup_map_editor)
+    .add_systems(Update, (
+        map_editor::editor_keyboard_system,
+        map_editor::editor_name_input_system,
+        map_editor::editor_mouse_system,
+        map_editor::rebuild_editor_view_system,
+        map_editor::editor_cursor_system,
+        map_editor::update_editor_ui_system,
+    ).chain().run_if(in_state(AppState::MapEditor)))
+    .add_systems(OnExit(AppState::MapEditor), map_editor::cleanup_map_editor)
+    
     // 网络菜单系统
     .add_systems(OnEnter(AppState::NetworkMenu), setup_network_menu)
     .add_systems(Update, handle_network_menu_buttons.run_if(in_state(AppState::NetworkMenu)))
@@ -497,6 +604,7 @@ fn main() {
         update_room_code_display.run_if(in_state(AppState::CreatingRoom)),
         room::update_host_ip_display.run_if(in_state(AppState::CreatingRoom)),
         handle_room_buttons_creating.run_if(in_state(AppState::CreatingRoom)),
+        room::handle_room_map_button.run_if(in_state(AppState::CreatingRoom)),
     ))
     .add_systems(OnExit(AppState::CreatingRoom), cleanup_room_ui)
     // 注意：不在这里清理网络资源，因为返回按钮已经清理了
@@ -536,6 +644,7 @@ fn main() {
         gameplay::preload_sound_effects, // 预加载音效资源
         setup_game, // 创建新的游戏相机和UI相机
         update_network_ui_visibility_once.after(setup_game), // 在UI创建后立即更新一次显示状态
+        anti_cheat::reset_anti_cheat, // 每局开始时清空违规分
     ))
     // 在Update中检查并播放BGM（等待加载完成）
     .add_systems(Update, play_background_music.run_if(in_state(AppState::Playing)))
@@ -551,6 +660,9 @@ fn main() {
         network_game::handle_crosshair_position_system.run_if(in_state(AppState::Playing)).after(network_game::handle_player_input_system), // 防守方接收准星位置（在handle_player_input_system之后，确保消息不被重复处理）
         network_game::handle_bullet_spawn_system.run_if(in_state(AppState::Playing)), // 接收方创建子弹（双方都需要处理）
         network_game::handle_health_update_system.run_if(in_state(AppState::Playing)), // 接收方更新血量（双方都需要处理）
+        utility::handle_remote_utility_system.run_if(in_state(AppState::Playing)), // 进攻方显示对方放出的道具
+        repair::handle_remote_repair_system.run_if(in_state(AppState::Playing)), // 进攻方恢复对方修好的砖块
+        anti_cheat::anti_cheat_decay_system.run_if(in_state(AppState::Playing)), // 违规分随时间衰减
     ))
     .add_systems(Update, (
                 attacker_aim_system,
@@ -558,16 +670,29 @@ fn main() {
                     .after(network_game::handle_player_input_system) // 确保防守方位置已更新
                     .before(check_win_condition_system), // 确保射击在游戏结束检查之前
                 defender_move_system,
+                bot::attacker_ai_system.before(attacker_shoot_system), // 单人练习：电脑进攻方瞄准和扣扳机
+                weapons::weapon_select_system.before(attacker_shoot_system), // 配装：开火前按 Tab 换枪
+                recon::thermal_scan_system, // 侦察：按 T 热成像扫描
+                hitbox_debug::toggle_hitbox_debug_system, // 调试：按 F4 显示命中判定框
     ).in_set(GameplaySystems::InputSystems))
     .add_systems(Update, (
         defender_action_system,
+        bot::attach_bot_ai_system, // 单人练习：按电脑当前的角色挂上AI
+        bot::defender_ai_system.after(bot::attach_bot_ai_system).before(action_timer_system), // 单人练习：电脑防守方移动和躲避
         action_timer_system,
+        utility::defender_utility_system, // 防守方道具：U 切换、L 使用
+        repair::defender_repair_system.after(action_timer_system), // 防守方修墙：按住 H（躲避会打断）
+        stamina::stamina_system.after(action_timer_system).after(bot::defender_ai_system), // 防守方体力：冲刺、侧躲、站起来消耗，站着不动恢复
     ).in_set(GameplaySystems::ActionSystems))
     .add_systems(Update, (
         // check_image_loading_system, // 检查图片加载状态（已移至Startup，只运行一次）
         recreate_game_entities_on_role_switch_system.after(gameplay::switch_roles_system), // 角色切换后重建游戏实体
         gameplay::ensure_network_view_matches_role_system.before(gameplay::switch_network_camera_system), // 确保视角与角色保持一致
         gameplay::switch_network_camera_system.after(gameplay::switch_roles_system).before(update_crosshair_position_system), // 网络模式相机切换（在角色切换之后立即执行）
+        scope::apply_scope_zoom_system
+            .after(gameplay::switch_network_camera_system)
+            .after(attacker_aim_system)
+            .run_if(in_state(AppState::Playing)), // 倍镜：调整进攻方相机的视野
         cleanup_camera_components_system
             .before(ensure_single_active_camera_system)
             .run_if(in_state(AppState::Playing))
@@ -596,6 +721,7 @@ fn main() {
         update_crosshair_position_system,
         update_defender_crosshair_indicator_system, // 更新防守方视角的准星指示器
         update_viewports.run_if(|room_info: Res<RoomInfo>| !room_info.is_connected), // 只在本地模式下运行，避免与网络模式系统冲突
+        bot::single_player_view_system.after(update_viewports), // 单人练习只显示玩家自己的视角（全屏）
         update_laser_indicator_system.before(update_laser_visibility_system),
         update_laser_visibility_system,
         update_humanoid_sprite_positions.before(defender_visibility_system), // 确保身体部位位置更新在可见性检测之前
@@ -608,14 +734,36 @@ fn main() {
         defender_visibility_system, // 防守方可见性（简化版本，依赖Z轴顺序和墙段隐藏）
                 round_timer_update_system,
                 check_win_condition_system,
-                gameplay::game_over_delay_system.after(attacker_shoot_system), // 游戏结束延迟系统（在射击系统之后运行）
+                projectile::resolve_pending_shots_system.after(attacker_shoot_system), // 弹道判定：子弹到达瞄准点时结算命中
+                projectile::update_shot_markers_system,
+                scoring::match_decision_system
+            .after(attacker_shoot_system)
+            .after(projectile::resolve_pending_shots_system)
+            .after(network_game::handle_health_update_system)
+            .before(gameplay::game_over_delay_system), // 按赛制判定胜负（本地模式和主机）
+        gameplay::game_over_delay_system.after(attacker_shoot_system), // 游戏结束延迟系统（在射击系统之后运行）
         delayed_round_switch_system,
         follow_defender_camera_system.after(defender_move_system), // 防守方相机跟随应该在防守方移动之后
+        utility::utility_lifetime_system, // 道具到时淡出并移除
+        recon::thermal_outline_system.after(wall_visibility_update_system), // 热成像轮廓叠在墙体可见性之上
+        recon::footstep_cue_system.before(recon::update_footstep_cues_system), // 防守方移动时的脚步提示
+        recon::update_footstep_cues_system,
+        hitbox_debug::update_hitbox_overlay_system.after(update_humanoid_sprite_positions), // 判定框跟随身体部位
     ).in_set(GameplaySystems::LogicSystems))
     .add_systems(Update, (
                 update_ui,
         update_health_display,
         update_action_cooldown_display,
+        scoring::update_score_display_system,
+        weapons::update_weapon_display_system,
+        scope::update_scope_display_system,
+        projectile::update_travel_time_display_system,
+        ballistics::update_wind_display_system,
+        utility::update_utility_display_system,
+        recon::update_recon_display_system,
+        repair::update_repair_progress_system,
+        repair::update_repair_display_system,
+        stamina::update_stamina_bar_system,
     ).in_set(GameplaySystems::UISystems))
     .add_systems(Update, (
         handle_player_hit_event,
@@ -623,7 +771,31 @@ fn main() {
         handle_player_action_event,
         gameplay::handle_number_key_sound_system.run_if(in_state(AppState::Playing)), // 数字键音效（仅在游戏中）
     ).in_set(GameplaySystems::EventSystems))
-    .add_systems(OnEnter(RoundState::Switching), (cleanup_bullets_on_switch, switch_roles_system))
+    // 调参面板（F2）：修改在下一次回合切换时生效
+    .add_systems(Update, (
+        tuning::toggle_tuning_panel_system,
+        tuning::tuning_slider_system,
+        tuning::tuning_button_system,
+        tuning::tuning_name_input_system,
+        tuning::update_tuning_panel_system,
+        tuning::announce_rules_system,
+        tuning::rules_notice_system,
+    ).chain().in_set(GameplaySystems::UISystems))
+    .add_systems(Update, tuning::check_host_rules_system.run_if(in_state(AppState::Playing)).after(network_game::handle_game_state_system)) // 客户端发现漏收的规则时向主机重新请求
+    .add_systems(OnEnter(RoundState::Switching), (
+        tuning::apply_pending_rules_system.before(switch_roles_system), // 先换规则，再按新规则重置回合
+        cleanup_bullets_on_switch,
+        switch_roles_system,
+        scoring::reset_health_on_switch_system.after(tuning::apply_pending_rules_system), // 先淘汰N次赛制：新回合双方回满血
+        weapons::reload_on_switch_system.after(switch_roles_system), // 按新进攻方的武器装填弹匣
+        scope::reset_scope_on_switch_system, // 收起倍镜、恢复气息
+        projectile::cleanup_shots_on_switch,
+        ballistics::roll_wind_on_switch_system, // 每个进攻回合换一次风
+        utility::reset_utilities_on_switch, // 新回合恢复道具次数、清掉场上的道具
+        recon::reset_recon_on_switch, // 新回合恢复热成像次数和冷却
+        repair::reset_repairs_on_switch, // 新回合恢复修墙次数
+        stamina::reset_stamina_on_switch, // 新回合双方恢复满体力
+    ))
         .add_systems(OnEnter(AppState::GameOver), setup_gameover_screen)
     .add_systems(Update, (
         handle_gameover_input,
@@ -650,7 +822,14 @@ fn main() {
     .add_systems(OnEnter(AppState::MainMenu), (
         cleanup_game.before(setup_main_menu),
         setup_main_menu,
-    ));
+        game_rules::restore_configured_rules.before(setup_main_menu), // 菜单上显示的赛制取自恢复后的规则
+        arena::restore_configured_map.before(setup_main_menu),
+        tuning::cleanup_tuning_system,
+        map_editor::return_after_test_play,
+    ))
+    // 启动时按加载的规则和地图计算消息校验范围，之后规则或地图变化（例如客户端收到主机的规则和地图）时重新计算
+    .add_systems(Startup, network_game::publish_message_limits)
+    .add_systems(PreUpdate, network_game::publish_message_limits);
 
     app.add_systems(PostUpdate, handle_app_exit);
     
@@ -671,8 +850,9 @@ fn spawn_humanoid_sprite(
     is_current_player: bool,
     current_player_is_attacker: bool,
 ) {
-    let player_height = PLAYER_SIZE.y;
-    let player_width = PLAYER_SIZE.x;
+    // 各部位按站立姿势摆放（与命中判定使用同一套几何形状，之后由 update_humanoid_sprite_positions 按动作更新）
+    let [head_rect, torso_rect, legs_rect] = humanoid_part_rects(&DodgeAction::None);
+    let part_translation = |rect: &PartRect, z_pos: f32| (position.truncate() + rect.offset).extend(z_pos);
     
     // 确定要创建的视角
     let view_layers_to_create = if is_network_mode {
@@ -703,9 +883,7 @@ fn spawn_humanoid_sprite(
             };
             
             // 头部：占身高的30%，位于顶部
-            let head_height = player_height * 0.3;
-            let head_y = position.y + (player_height - head_height) / 2.0;
-            let head_size = Vec2::new(player_width * 0.8, head_height);
+            let head_size = head_rect.size;
             
             // 如果提供了图片，使用图片；否则使用纯色
             let head_bundle = if let Some(ref image_handle) = head_image {
@@ -716,7 +894,7 @@ fn spawn_humanoid_sprite(
                         custom_size: Some(head_size),
                 ..default()
             },
-                    transform: Transform::from_translation(Vec3::new(position.x, head_y, z_pos)),
+                    transform: Transform::from_translation(part_translation(&head_rect, z_pos)),
                     visibility: Visibility::Visible,
             ..default()
                 };
@@ -730,7 +908,7 @@ fn spawn_humanoid_sprite(
                         custom_size: Some(head_size),
                 ..default()
             },
-                    transform: Transform::from_translation(Vec3::new(position.x, head_y, z_pos)),
+                    transform: Transform::from_translation(part_translation(&head_rect, z_pos)),
                     visibility: Visibility::Visible,
             ..default()
                 }
@@ -747,8 +925,6 @@ fn spawn_humanoid_sprite(
             ));
             
             // 躯干：占身高的50%，位于中间
-            let torso_height = player_height * 0.5;
-            let torso_y = position.y - (player_height - torso_height) / 2.0 + head_height / 2.0;
     commands.spawn((
                 HumanoidPart {
                     player_id,
@@ -758,10 +934,10 @@ fn spawn_humanoid_sprite(
         SpriteBundle {
             sprite: Sprite {
                         color: color,
-                        custom_size: Some(Vec2::new(player_width * 0.9, torso_height)),
+                        custom_size: Some(torso_rect.size),
                 ..default()
             },
-                    transform: Transform::from_translation(Vec3::new(position.x, torso_y, z_pos)),
+                    transform: Transform::from_translation(part_translation(&torso_rect, z_pos)),
                     visibility: Visibility::Visible,
             ..default()
         },
@@ -769,8 +945,6 @@ fn spawn_humanoid_sprite(
     ));
 
             // 腿部：占身高的20%，位于底部
-            let legs_height = player_height * 0.2;
-            let legs_y = position.y - (player_height - legs_height) / 2.0;
     commands.spawn((
                 HumanoidPart {
                     player_id,
@@ -780,10 +954,10 @@ fn spawn_humanoid_sprite(
         SpriteBundle {
             sprite: Sprite {
                         color: color * 0.8, // 腿部稍微暗一点
-                        custom_size: Some(Vec2::new(player_width * 0.7, legs_height)),
+                        custom_size: Some(legs_rect.size),
                 ..default()
             },
-                    transform: Transform::from_translation(Vec3::new(position.x, legs_y, z_pos)),
+                    transform: Transform::from_translation(part_translation(&legs_rect, z_pos)),
                     visibility: Visibility::Visible,
             ..default()
         },
@@ -831,10 +1005,11 @@ fn setup_fonts(mut commands: Commands, asset_server: Res<AssetServer>) {
 fn setup_game(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    _view_config: Res<ViewConfig>,
+    (rules, arena): (Res<GameRules>, Res<Arena>),
     font_resource: Res<FontResource>,
     room_info: Res<RoomInfo>,
     broken_wall_data: Option<Res<BrokenWallData>>,
+    single_player: Res<bot::SinglePlayerSettings>,
 ) {
     // 调试输出已禁用: println!("=== 开始设置游戏 ===");
     println!("[调试] RoomInfo状态: is_connected={}, is_host={}, room_code={:?}", 
@@ -870,8 +1045,8 @@ fn setup_game(
             (PlayerRole::Attacker, PlayerRole::Defender, PlayerId::Player1)
         }
     } else {
-        // 本地模式：随机决定
-    let p1_starts_as_attacker = rand::thread_rng().gen_bool(0.5);
+        // 本地模式：随机决定（单人练习时玩家先进攻，电脑先防守）
+    let p1_starts_as_attacker = single_player.enabled || rand::thread_rng().gen_bool(0.5);
         // 调试输出已禁用: println!("[本地模式] 随机分配角色: P1作为{}", if p1_starts_as_attacker { "进攻方" } else { "防守方" });
         if p1_starts_as_attacker {
         (PlayerRole::Attacker, PlayerRole::Defender, PlayerId::Player1)
@@ -882,18 +1057,27 @@ fn setup_game(
     // 调试输出已禁用: println!("[调试] 最终角色分配: P1={:?}, P2={:?}, 当前进攻方={:?}", p1_role, p2_role, current_attacker);
     
     commands.insert_resource(RoundInfo {
-        bullets_left: BULLETS_PER_ROUND,
-        round_timer: Timer::from_seconds(ROUND_TIME_SECONDS, TimerMode::Once),
+        bullets_left: rules.bullets_per_round,
+        round_timer: Timer::from_seconds(rules.round_time_seconds, TimerMode::Once),
         current_attacker,
-        p1_health: PLAYER_HP,
-        p2_health: PLAYER_HP,
+        p1_health: rules.player_hp,
+        p2_health: rules.player_hp,
         bullets_fired_this_round: 0,
         bullets_hit_defender: 0,
         is_switching: false,
+        score: scoring::MatchScore::default(),
+        // 客户端的风由主机的 RoundInfoSync 下发
+        wind: if scoring::is_match_authority(Some(&room_info)) { ballistics::roll_wind(&rules) } else { Vec2::ZERO },
     });
     
     // 初始化子弹ID计数器
     commands.insert_resource(gameplay::BulletIdCounter::default());
+    // 每局开始时双方都拿栓动步枪（弹匣与上面的子弹数一致）
+    commands.insert_resource(weapons::Loadout::default());
+    commands.insert_resource(utility::DefenderUtilities::default());
+    commands.insert_resource(recon::ReconState::default());
+    commands.insert_resource(repair::WallRepair::default());
+    commands.insert_resource(scope::ScopeState::default());
 
     // 根据游戏模式创建相机
     if is_network_mode {
@@ -915,7 +1099,7 @@ fn setup_game(
             // 同时作为UI相机，这样UI会直接渲染到游戏相机，不会"捕捉"现有画面
             let attacker_camera_entity = commands.spawn((
                 Camera2dBundle {
-                    transform: Transform::from_translation(Vec3::new(ATTACKER_START_POS.x, ATTACKER_START_POS.y, 1000.0)),
+                    transform: Transform::from_translation(arena.attacker_spawn().truncate().extend(1000.0)),
                     projection: OrthographicProjection {
                         scale: 0.5,
                         ..default()
@@ -1011,7 +1195,7 @@ fn setup_game(
         let (left_camera_pos, left_camera_scale, left_camera_color, left_render_layer, left_is_defender_camera) = 
             if matches!(p1_role, PlayerRole::Attacker) {
                 // P1是进攻方
-                (Vec3::new(ATTACKER_START_POS.x, ATTACKER_START_POS.y, 1000.0), 0.5, Color::rgb(0.0, 0.0, 0.0), RenderLayers::layer(0), false)
+                (arena.attacker_spawn().truncate().extend(1000.0), 0.5, Color::rgb(0.0, 0.0, 0.0), RenderLayers::layer(0), false)
             } else {
                 // P1是防守方
                 (DEFENDER_CAMERA_OFFSET, 1.5, Color::rgb(0.05, 0.05, 0.1), RenderLayers::layer(1), true)
@@ -1021,7 +1205,7 @@ fn setup_game(
         let (right_camera_pos, right_camera_scale, right_camera_color, right_render_layer, right_is_defender_camera) = 
             if matches!(p2_role, PlayerRole::Attacker) {
                 // P2是进攻方
-                (Vec3::new(ATTACKER_START_POS.x, ATTACKER_START_POS.y, 1000.0), 0.5, Color::rgb(0.0, 0.0, 0.0), RenderLayers::layer(0), false)
+                (arena.attacker_spawn().truncate().extend(1000.0), 0.5, Color::rgb(0.0, 0.0, 0.0), RenderLayers::layer(0), false)
             } else {
                 // P2是防守方
                 (DEFENDER_CAMERA_OFFSET, 1.5, Color::rgb(0.05, 0.05, 0.1), RenderLayers::layer(1), true)
@@ -1126,19 +1310,20 @@ fn setup_game(
     }
 
     // 创建玩家1（蓝色）
-    let p1_pos = if matches!(p1_role, PlayerRole::Attacker) { ATTACKER_START_POS } else { DEFENDER_START_POS };
+    let p1_pos = if matches!(p1_role, PlayerRole::Attacker) { arena.attacker_spawn() } else { arena.defender_spawn() };
     commands.spawn((
         PlayerId::Player1,
         p1_role,
-        Health(PLAYER_HP),
+        Health(rules.player_hp),
         Transform::from_translation(p1_pos),
         Visibility::Visible,
         Collider { size: PLAYER_SIZE },
         ActionCooldown {
             last_action_time: 0.0,
-            cooldown_duration: DODGE_COOLDOWN_SECONDS as f64,
+            cooldown_duration: rules.dodge_cooldown_seconds as f64,
         },
         DodgeAction::None,
+        stamina::Stamina::full(&rules),
     ));
     
     // 加载玩家头像图片（使用小写路径，确保兼容性）
@@ -1181,19 +1366,20 @@ fn setup_game(
     );
 
     // 创建玩家2（绿色）
-    let p2_pos = if matches!(p2_role, PlayerRole::Attacker) { ATTACKER_START_POS } else { DEFENDER_START_POS };
+    let p2_pos = if matches!(p2_role, PlayerRole::Attacker) { arena.attacker_spawn() } else { arena.defender_spawn() };
     commands.spawn((
         PlayerId::Player2,
         p2_role,
-        Health(PLAYER_HP),
+        Health(rules.player_hp),
         Transform::from_translation(p2_pos),
         Visibility::Visible,
         Collider { size: PLAYER_SIZE },
         ActionCooldown {
             last_action_time: 0.0,
-            cooldown_duration: DODGE_COOLDOWN_SECONDS as f64,
+            cooldown_duration: rules.dodge_cooldown_seconds as f64,
         },
         DodgeAction::None,
+        stamina::Stamina::full(&rules),
     ));
     
     // 为玩家2创建人形sprite（绿色，使用wmh.jpg作为头部）
@@ -1210,17 +1396,8 @@ fn setup_game(
     
     // 调试输出已禁用: println!("[调试] 游戏实体创建完成: P1={:?} at {:?}, P2={:?} at {:?}", p1_role, p1_pos, p2_role, p2_pos);
     
-    // 创建加大加宽墙体（30列×12行）
-    // 调试输出已禁用: println!("[调试] 开始创建墙体...");
-    let brick_cols = BRICK_COLS;
-    let brick_rows = BRICK_ROWS;
-    let brick_width = BRICK_WIDTH;
-    let brick_height = BRICK_HEIGHT;
-    
-    let brick_colors = vec![Color::rgb(0.8, 0.6, 0.5), Color::rgb(0.5, 0.35, 0.25)];
-    let wall_entity = commands.spawn((Wall { damaged: false, damage_positions: Vec2::ZERO },)).id();
-    // 调试输出已禁用: println!("[调试] 墙体实体创建完成，开始创建砖块...");
-    
+    // 按地图创建墙体和掩体
+
     // 确定要创建的视角
     // 网络模式下，只创建当前玩家视角的墙体副本
     // 本地模式下，创建两个视角的墙体副本
@@ -1256,120 +1433,11 @@ fn setup_game(
     }
     
     // 添加调试：统计将创建的墙体数量
-    let total_segments = brick_cols * brick_rows * view_layers_to_create.len();
-    println!("[调试] 将创建 {} 个墙体段 ({} 列 × {} 行 × {} 个视角)", 
-             total_segments, brick_cols, brick_rows, view_layers_to_create.len());
-    
-    for row in 0..brick_rows {
-        for col in 0..brick_cols {
-            let x_offset = (col as f32 - (brick_cols as f32 - 1.0) / 2.0) * brick_width;
-            let y_offset = (row as f32 - (brick_rows as f32 - 1.0) / 2.0) * brick_height;
-            let row_offset = if row % 2 == 1 { brick_width / 2.0 } else { 0.0 };
-            let final_x_offset = x_offset + row_offset;
-            let brick_color = brick_colors[(col + row) % brick_colors.len()];
-            
-            // 只创建当前视角的墙
-            for view_layer in view_layers_to_create.iter() {
-                let render_layer = match view_layer {
-                    ViewLayer::AttackerView => RenderLayers::layer(0), // 进攻方视角渲染层
-                    ViewLayer::DefenderView => RenderLayers::layer(1), // 防守方视角渲染层
-                };
-                
-                // 根据视角设置不同的Z轴
-                let wall_z_pos = match view_layer {
-                    ViewLayer::AttackerView => 2.0, // 进攻方视角：墙在Z轴2.0（后渲染，会遮挡人物）
-                    ViewLayer::DefenderView => 1.0, // 防守方视角：墙在Z轴1.0（先渲染，人物在墙之后）
-                };
-                
-                if matches!(view_layer, ViewLayer::AttackerView) {
-                    // 攻击方视角使用黑色背景填充砖块缝隙，防守方视角不需要额外背景
-                let background_z = wall_z_pos - 0.1;
-            commands.spawn((
-                    WallBackground {
-                        position: Vec2::new(final_x_offset, y_offset),
-                            view_layer: *view_layer,
-                    },
-                SpriteBundle {
-                    sprite: Sprite {
-                            color: Color::BLACK,
-                                custom_size: Some(Vec2::new(brick_width, brick_height)),
-                        ..default()
-                    },
-                        transform: Transform::from_translation(Vec3::new(
-                                final_x_offset,
-                                WALL_POSITION.y + y_offset,
-                                background_z,
-                        )),
-                    ..default()
-                },
-                    render_layer,
-                ));
-                }
-                
-                // 再创建砖块（稍小，露出黑色缝隙）
-                // 检查是否需要恢复破碎状态
-                let segment_pos = Vec2::new(final_x_offset, y_offset);
-                let is_broken = if let Some(broken_data) = broken_wall_data.as_ref() {
-                    let is_host = room_info.is_host;
-                    if is_host {
-                        broken_data.host_broken_segments.contains(&segment_pos)
-                    } else {
-                        broken_data.client_broken_segments.contains(&segment_pos)
-                    }
-                } else {
-                    false
-                };
-                
-                let (segment_damaged, segment_color, segment_visibility) = if is_broken {
-                    // 恢复破碎状态
-                    // 调试输出已禁用: println!("[恢复破碎墙体] 位置: {:?}, 视角: {:?}", segment_pos, view_layer);
-                    match view_layer {
-                        ViewLayer::AttackerView => (true, Color::rgba(0.0, 0.0, 0.0, 0.0), Visibility::Hidden),
-                        ViewLayer::DefenderView => (true, Color::rgba(0.0, 0.0, 0.0, 0.8), Visibility::Visible),
-                    }
-                } else {
-                    (false, brick_color, Visibility::Visible)
-                };
-                
-                let segment_entity = commands.spawn((
-                    WallSegment {
-                        wall_entity,
-                        position: segment_pos,
-                        damaged: segment_damaged,
-                        view_layer: *view_layer,
-                    },
-                    SpriteBundle {
-                        sprite: Sprite {
-                            color: segment_color,
-                            custom_size: Some(Vec2::new(brick_width - 2.0, brick_height - 2.0)),
-                            ..default()
-                        },
-                        transform: Transform::from_translation(Vec3::new(
-                            final_x_offset, WALL_POSITION.y + y_offset, wall_z_pos
-                        )),
-                        visibility: segment_visibility,
-                        ..default()
-                    },
-                    Collider { size: Vec2::new(brick_width - 2.0, brick_height - 2.0) }, // 碰撞框与视觉一致
-                    render_layer,
-                )).id();
-                
-                // 如果是防守方视角的破碎墙体，创建破损效果
-                if is_broken && matches!(view_layer, ViewLayer::DefenderView) {
-                    commands.spawn((
-                        SpriteBundle {
-                            sprite: Sprite { color: Color::rgba(0.0, 0.0, 0.0, 0.9), custom_size: Some(Vec2::new(30.0, 30.0)), ..default() },
-                            transform: Transform::from_translation(Vec3::new(
-                                final_x_offset, WALL_POSITION.y + y_offset, wall_z_pos + 0.5
-                            )),
-                            ..default()
-                        },
-                        render_layer,
-                    ));
-                }
-            }
-        }
-    }
+    let total_segments = arena.brick_count() * view_layers_to_create.len();
+    println!("[调试] 将创建 {} 个墙体段 (地图 {}，{} 面墙 × {} 个视角)", 
+             total_segments, arena.map.name, arena.map.walls.len(), view_layers_to_create.len());
+    
+    spawn_arena(&mut commands, &arena, &view_layers_to_create, broken_wall_data.as_deref(), room_info.is_host);
     
     // 调试：验证创建的墙体数量
     // 调试输出已禁用: println!("[调试] 墙体创建完成，共创建 {} 个墙体段", brick_cols * brick_rows * view_layers_to_create.len());
@@ -1453,7 +1521,7 @@ fn setup_game(
     }
     
     // 创建UI（根据游戏模式决定显示方式）
-    setup_ui(&mut commands, font, Some(&*room_info));
+    setup_ui(&mut commands, font, Some(&*room_info), &rules);
     // 调试输出已禁用: println!("=== 游戏设置完成 ===");
     // 调试输出已禁用: println!("当前进攻方: {:?}", current_attacker);
     // 调试输出已禁用: println!("网络模式: {}", is_network_mode);
@@ -1474,7 +1542,7 @@ fn setup_game(
 /// 确保进攻方视角只渲染 layer 0 的实体
 fn cleanup_attacker_view_entities_system(
     mut commands: Commands,
-    room_info: Option<Res<RoomInfo>>,
+    (room_info, arena): (Option<Res<RoomInfo>>, Res<Arena>),
     view_config: Res<ViewConfig>,
     humanoid_query: Query<(Entity, &gameplay::HumanoidPart, Option<&RenderLayers>)>,
     wall_segment_query: Query<(Entity, &gameplay::WallSegment, Option<&RenderLayers>)>,
@@ -1577,8 +1645,8 @@ fn cleanup_attacker_view_entities_system(
         }
     }
     
-    // 如果保留的墙体数量超过预期（30列×12行=360个），说明有重复，删除多余的
-    let expected_wall_count = BRICK_COLS * BRICK_ROWS; // brick_cols * brick_rows
+    // 如果保留的墙体数量超过预期（地图中所有墙的砖块数），说明有重复，删除多余的
+    let expected_wall_count = arena.brick_count();
     if wall_segments_to_keep.len() > expected_wall_count {
         println!("[清理实体] 警告：检测到 {} 个 WallSegment，超过预期的 {} 个！可能有重复创建", 
                  wall_segments_to_keep.len(), expected_wall_count);
@@ -1624,8 +1692,8 @@ fn cleanup_attacker_view_entities_system(
         }
     }
     
-    // 如果保留的墙体背景数量超过预期（30列×12行=360个），说明有重复，删除多余的
-    let expected_wall_count = BRICK_COLS * BRICK_ROWS; // brick_cols * brick_rows
+    // 如果保留的墙体背景数量超过预期（地图中所有墙的砖块数），说明有重复，删除多余的
+    let expected_wall_count = arena.brick_count();
     if wall_backgrounds_to_keep.len() > expected_wall_count {
         println!("[清理实体] 警告：检测到 {} 个 WallBackground，超过预期的 {} 个！可能有重复创建", 
                  wall_backgrounds_to_keep.len(), expected_wall_count);
@@ -2201,6 +2269,169 @@ fn ensure_single_active_camera_system(
     }
 }
 
+/// 创建地图中的所有墙和掩体（每个视角一份副本）
+fn spawn_arena(
+    commands: &mut Commands,
+    arena: &Arena,
+    view_layers: &[ViewLayer],
+    broken_wall_data: Option<&BrokenWallData>,
+    is_host: bool,
+) {
+    for wall in &arena.map.walls {
+        spawn_wall_bricks(commands, wall, view_layers, broken_wall_data, is_host);
+    }
+    for cover in &arena.map.cover {
+        spawn_cover_prop(commands, cover, view_layers);
+    }
+}
+
+/// 创建一面墙：每个视角一份砖块副本，材质由地图决定
+/// 恢复之前打碎或打裂的砖块，地图中的破洞开局就是碎的、裂纹开局就少了耐久（联机时角色切换后重建实体也走这里）
+/// 深度大的墙画在后面（露在前面那层墙的破洞里），每加一层深度 Z 轴退后 0.01，不会越过背景和人物
+fn spawn_wall_bricks(
+    commands: &mut Commands,
+    wall: &WallDef,
+    view_layers: &[ViewLayer],
+    broken_wall_data: Option<&BrokenWallData>,
+    is_host: bool,
+) {
+    let wall_entity = commands.spawn((Wall { damaged: false, damage_positions: Vec2::ZERO },)).id();
+    // 调试输出已禁用: println!("[调试] 墙体实体创建完成，开始创建砖块...");
+    let (cols, rows) = (wall.cols as usize, wall.rows as usize);
+    
+    for row in 0..rows {
+        for col in 0..cols {
+            let brick_pos = arena::brick_center(wall, row, col);
+            let spec = wall.brick_at(row, col);
+            let full_hit_points = spec.hit_points().unwrap_or(0);
+            let starting_hit_points = wall.starting_hit_points(row, col).unwrap_or(0);
+            let is_hole = wall.is_hole(row, col);
+            
+            for view_layer in view_layers {
+                let render_layer = match view_layer {
+                    ViewLayer::AttackerView => RenderLayers::layer(0), // 进攻方视角渲染层
+                    ViewLayer::DefenderView => RenderLayers::layer(1), // 防守方视角渲染层
+                };
+                
+                // 根据视角设置不同的Z轴
+                let wall_z_pos = match view_layer {
+                    ViewLayer::AttackerView => 2.0, // 进攻方视角：墙在Z轴2.0（后渲染，会遮挡人物）
+                    ViewLayer::DefenderView => 1.0, // 防守方视角：墙在Z轴1.0（先渲染，人物在墙之后）
+                } - wall.depth as f32 * 0.01;
+                
+                // 攻击方视角使用黑色背景填充砖块缝隙，防守方视角不需要额外背景
+                // 玻璃后面不放背景，保持透明
+                if matches!(view_layer, ViewLayer::AttackerView) && !spec.material.is_see_through() {
+                    let background_z = wall_z_pos - 0.1;
+                    commands.spawn((
+                        WallBackground {
+                            position: brick_pos,
+                            depth: wall.depth,
+                            view_layer: *view_layer,
+                        },
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::BLACK,
+                                custom_size: Some(Vec2::new(BRICK_WIDTH, BRICK_HEIGHT)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(brick_pos.extend(background_z)),
+                            ..default()
+                        },
+                        render_layer,
+                    ));
+                }
+                
+                // 再创建砖块（稍小，露出黑色缝隙）
+                // 检查是否需要恢复破碎或打裂的状态
+                let segment_pos = brick_pos;
+                let brick_key = (wall.depth, segment_pos);
+                // 修好的砖块不再按地图中的破洞和裂纹恢复
+                let is_repaired = broken_wall_data.is_some_and(|data| data.is_repaired(is_host, brick_key));
+                let is_broken = (is_hole && !is_repaired) || broken_wall_data.is_some_and(|data| data.is_broken(is_host, brick_key));
+                let hit_points = broken_wall_data
+                    .and_then(|data| data.worn_hit_points(is_host, brick_key))
+                    .unwrap_or(if is_repaired { full_hit_points } else { starting_hit_points });
+                
+                let (segment_color, segment_visibility) = if is_broken {
+                    // 恢复破碎状态
+                    // 调试输出已禁用: println!("[恢复破碎墙体] 位置: {:?}, 视角: {:?}", segment_pos, view_layer);
+                    match view_layer {
+                        ViewLayer::AttackerView => (Color::rgba(0.0, 0.0, 0.0, 0.0), Visibility::Hidden),
+                        ViewLayer::DefenderView => (Color::rgba(0.0, 0.0, 0.0, 0.8), Visibility::Visible),
+                    }
+                } else {
+                    let color = BrickSpec::cracked_color(spec.color(row, col), full_hit_points - hit_points);
+                    (color, Visibility::Visible)
+                };
+                
+                commands.spawn((
+                    WallSegment {
+                        wall_entity,
+                        position: segment_pos,
+                        depth: wall.depth,
+                        damaged: is_broken,
+                        view_layer: *view_layer,
+                        spec,
+                        hit_points: if is_broken { 0 } else { hit_points },
+                        intact_color: spec.color(row, col),
+                    },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: segment_color,
+                            custom_size: Some(Vec2::new(BRICK_WIDTH - 2.0, BRICK_HEIGHT - 2.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(brick_pos.extend(wall_z_pos)),
+                        visibility: segment_visibility,
+                        ..default()
+                    },
+                    Collider { size: Vec2::new(BRICK_WIDTH - 2.0, BRICK_HEIGHT - 2.0) }, // 碰撞框与视觉一致
+                    render_layer,
+                ));
+                
+                // 如果是防守方视角的破碎墙体，创建破损效果
+                if is_broken && matches!(view_layer, ViewLayer::DefenderView) {
+                    commands.spawn((
+                        gameplay::BulletHole { position: brick_pos, depth: wall.depth },
+                        SpriteBundle {
+                            sprite: Sprite { color: Color::rgba(0.0, 0.0, 0.0, 0.9), custom_size: Some(Vec2::new(30.0, 30.0)), ..default() },
+                            transform: Transform::from_translation(brick_pos.extend(wall_z_pos + 0.5)),
+                            ..default()
+                        },
+                        render_layer,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// 创建一个掩体（每个视角一份副本）：进攻方视角挡在人前面，防守方视角画在人后面
+fn spawn_cover_prop(commands: &mut Commands, cover: &arena::CoverProp, view_layers: &[ViewLayer]) {
+    let size = Vec2::from(cover.size);
+    let color = match cover.kind {
+        arena::CoverKind::Crate => Color::rgb(0.55, 0.4, 0.22),
+        arena::CoverKind::Pillar => Color::rgb(0.5, 0.5, 0.52),
+    };
+    for view_layer in view_layers {
+        let render_layer = match view_layer {
+            ViewLayer::AttackerView => RenderLayers::layer(0),
+            ViewLayer::DefenderView => RenderLayers::layer(1),
+        };
+        commands.spawn((
+            gameplay::CoverBlock { view_layer: *view_layer },
+            SpriteBundle {
+                sprite: Sprite { color, custom_size: Some(size), ..default() },
+                transform: Transform::from_translation(Vec2::from(cover.position).extend(1.5)),
+                ..default()
+            },
+            Collider { size },
+            render_layer,
+        ));
+    }
+}
+
 /// 角色切换时重建游戏实体系统
 /// 在网络模式下，当角色切换时，需要清理并重新创建游戏实体（因为只创建了当前视角的实体）
 fn recreate_game_entities_on_role_switch_system(
@@ -2208,12 +2439,12 @@ fn recreate_game_entities_on_role_switch_system(
     mut recreate_flag: ResMut<RecreateGameEntitiesOnRoleSwitch>,
     mut camera_state_cache: ResMut<CameraStateCache>,
     room_info: Option<Res<RoomInfo>>,
-    asset_server: Res<AssetServer>,
+    (asset_server, arena): (Res<AssetServer>, Res<Arena>),
     broken_wall_data: Option<Res<BrokenWallData>>,
     player_query: Query<(Entity, &PlayerId, &PlayerRole, &Transform), (With<PlayerId>, Without<gameplay::HumanoidPart>)>,
     humanoid_query: Query<Entity, With<gameplay::HumanoidPart>>,
     wall_segment_query: Query<Entity, With<gameplay::WallSegment>>,
-    wall_background_query: Query<Entity, With<gameplay::WallBackground>>,
+    wall_background_query: Query<Entity, Or<(With<gameplay::WallBackground>, With<gameplay::CoverBlock>, With<gameplay::BulletHole>)>>, // 墙体背景、掩体和弹孔
     wall_query: Query<Entity, With<gameplay::Wall>>, // 也需要清理Wall实体
     crosshair_query: Query<Entity, With<Crosshair>>,
     laser_indicator_query: Query<Entity, With<gameplay::LaserIndicator>>,
@@ -2430,14 +2661,6 @@ fn recreate_game_entities_on_role_switch_system(
         current_player_is_attacker,
     );
     
-    // 创建墙体
-    let brick_cols = BRICK_COLS;
-    let brick_rows = BRICK_ROWS;
-    let brick_width = BRICK_WIDTH;
-    let brick_height = BRICK_HEIGHT;
-    let brick_colors = vec![Color::rgb(0.8, 0.6, 0.5), Color::rgb(0.5, 0.35, 0.25)];
-    let wall_entity = commands.spawn((gameplay::Wall { damaged: false, damage_positions: Vec2::ZERO },)).id();
-    
     // 确定要创建的视角
     let view_layers_to_create: Vec<ViewLayer> = if current_player_is_attacker {
         vec![ViewLayer::AttackerView]
@@ -2445,112 +2668,7 @@ fn recreate_game_entities_on_role_switch_system(
         vec![ViewLayer::DefenderView]
     };
     
-    for row in 0..brick_rows {
-        for col in 0..brick_cols {
-            let x_offset = (col as f32 - (brick_cols as f32 - 1.0) / 2.0) * brick_width;
-            let y_offset = (row as f32 - (brick_rows as f32 - 1.0) / 2.0) * brick_height;
-            let row_offset = if row % 2 == 1 { brick_width / 2.0 } else { 0.0 };
-            let final_x_offset = x_offset + row_offset;
-            let brick_color = brick_colors[(col + row) % brick_colors.len()];
-            
-            for view_layer in view_layers_to_create.iter() {
-                let render_layer = match view_layer {
-                    ViewLayer::AttackerView => RenderLayers::layer(0),
-                    ViewLayer::DefenderView => RenderLayers::layer(1),
-                };
-                
-                let wall_z_pos = match view_layer {
-                    ViewLayer::AttackerView => 2.0,
-                    ViewLayer::DefenderView => 1.0,
-                };
-                
-                if matches!(view_layer, ViewLayer::AttackerView) {
-                    let background_z = wall_z_pos - 0.1;
-                    commands.spawn((
-                        gameplay::WallBackground {
-                            position: Vec2::new(final_x_offset, y_offset),
-                            view_layer: *view_layer,
-                        },
-                        SpriteBundle {
-                            sprite: Sprite {
-                                color: Color::BLACK,
-                                custom_size: Some(Vec2::new(brick_width, brick_height)),
-                                ..default()
-                            },
-                            transform: Transform::from_translation(Vec3::new(
-                                final_x_offset,
-                                WALL_POSITION.y + y_offset,
-                                background_z,
-                            )),
-                            ..default()
-                        },
-                        render_layer,
-                    ));
-                }
-                
-                // 检查是否需要恢复破碎状态
-                let segment_pos = Vec2::new(final_x_offset, y_offset);
-                let is_broken = if let Some(broken_data) = broken_wall_data.as_ref() {
-                    let is_host = room_info.as_ref().unwrap().is_host;
-                    if is_host {
-                        broken_data.host_broken_segments.contains(&segment_pos)
-                    } else {
-                        broken_data.client_broken_segments.contains(&segment_pos)
-                    }
-                } else {
-                    false
-                };
-                
-                let (segment_damaged, segment_color, segment_visibility) = if is_broken {
-                    // 恢复破碎状态
-                    // 调试输出已禁用: println!("[恢复破碎墙体] 位置: {:?}, 视角: {:?}", segment_pos, view_layer);
-                    match view_layer {
-                        ViewLayer::AttackerView => (true, Color::rgba(0.0, 0.0, 0.0, 0.0), Visibility::Hidden),
-                        ViewLayer::DefenderView => (true, Color::rgba(0.0, 0.0, 0.0, 0.8), Visibility::Visible),
-                    }
-                } else {
-                    (false, brick_color, Visibility::Visible)
-                };
-                
-                let segment_entity = commands.spawn((
-                    gameplay::WallSegment {
-                        wall_entity,
-                        position: segment_pos,
-                        damaged: segment_damaged,
-                        view_layer: *view_layer,
-                    },
-                    SpriteBundle {
-                        sprite: Sprite {
-                            color: segment_color,
-                            custom_size: Some(Vec2::new(brick_width - 2.0, brick_height - 2.0)),
-                            ..default()
-                        },
-                        transform: Transform::from_translation(Vec3::new(
-                            final_x_offset, WALL_POSITION.y + y_offset, wall_z_pos
-                        )),
-                        visibility: segment_visibility,
-                        ..default()
-                    },
-                    Collider { size: Vec2::new(brick_width - 2.0, brick_height - 2.0) },
-                    render_layer,
-                )).id();
-                
-                // 如果是防守方视角的破碎墙体，创建破损效果
-                if is_broken && matches!(view_layer, ViewLayer::DefenderView) {
-                    commands.spawn((
-                        SpriteBundle {
-                            sprite: Sprite { color: Color::rgba(0.0, 0.0, 0.0, 0.9), custom_size: Some(Vec2::new(30.0, 30.0)), ..default() },
-                            transform: Transform::from_translation(Vec3::new(
-                                final_x_offset, WALL_POSITION.y + y_offset, wall_z_pos + 0.5
-                            )),
-                            ..default()
-                        },
-                        render_layer,
-                    ));
-                }
-            }
-        }
-    }
+    spawn_arena(&mut commands, &arena, &view_layers_to_create, broken_wall_data.as_deref(), is_host);
     
     // 创建准星和激光指示器
     if current_player_is_attacker {
@@ -3045,7 +3163,7 @@ fn update_network_ui_visibility_once(
 
 /// 设置UI界面
 /// 根据游戏模式决定是双窗口显示还是单窗口显示
-fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&RoomInfo>) {
+fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&RoomInfo>, rules: &GameRules) {
     let text_style = TextStyle {
         font: font.clone(),
         font_size: 24.0, 
@@ -3109,7 +3227,7 @@ fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&Room
                 AttackerUI,
                 AttackerUIRoot, // 标记这是根节点
             )).with_children(|attacker_parent| {
-                    setup_attacker_ui(attacker_parent, font.clone(), text_style.clone(), title_text_style.clone());
+                    setup_attacker_ui(attacker_parent, font.clone(), text_style.clone(), title_text_style.clone(), rules);
                 });
             
             // 防守方UI
@@ -3137,7 +3255,7 @@ fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&Room
                 DefenderUIRoot, // 标记这是根节点
             )).with_children(|defender_parent| {
                     // 不再创建测试UI，直接创建防守方UI
-                    let _child_count = setup_defender_ui(defender_parent, font.clone(), text_style.clone(), title_text_style.clone());
+                    let _child_count = setup_defender_ui(defender_parent, font.clone(), text_style.clone(), title_text_style.clone(), rules);
                     // 调试输出已禁用: println!("[调试] 防守方UI子元素创建完成，共创建 {} 个子元素", _child_count);
                 }).id();
             println!("[调试] 创建防守方UI根节点, is_attacker={}, 初始display={}", 
@@ -3157,7 +3275,7 @@ fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&Room
                 },
                 ..default()
             }).with_children(|attacker_parent| {
-                setup_attacker_ui(attacker_parent, font.clone(), text_style.clone(), title_text_style.clone());
+                setup_attacker_ui(attacker_parent, font.clone(), text_style.clone(), title_text_style.clone(), rules);
             });
             
             // 右侧视角（防守方）的UI容器 - 只占屏幕右半部分（50-100%）
@@ -3172,7 +3290,7 @@ fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&Room
                 },
                 ..default()
             }).with_children(|defender_parent| {
-                setup_defender_ui(defender_parent, font.clone(), text_style.clone(), title_text_style.clone());
+                setup_defender_ui(defender_parent, font.clone(), text_style.clone(), title_text_style.clone(), rules);
             });
         }
         
@@ -3191,11 +3309,14 @@ fn setup_ui(commands: &mut Commands, font: Handle<Font>, room_info: Option<&Room
                 TextSection::new("他们走不了了！".to_string(), text_style.clone()),
             ]));
         });
+        
+        // 比分（赛制、累计伤害、淘汰次数）
+        scoring::spawn_score_text(parent, font.clone(), rules);
     });
 }
 
 /// 设置进攻方视角的UI（左侧视口）
-fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style: TextStyle, title_text_style: TextStyle) {
+fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style: TextStyle, title_text_style: TextStyle, rules: &GameRules) {
     // 血量显示（显示在左侧视口顶部）
     parent.spawn((
         NodeBundle {
@@ -3224,7 +3345,7 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
             hp_bar.spawn((
                 TextBundle::from_sections([
                     TextSection::new("P1 血量: ".to_string(), title_text_style.clone()),
-                    TextSection::new("100".to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
+                    TextSection::new(format!("{:.0}", rules.player_hp), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
                 ]),
                 PlayerHealthDisplay { player_id: PlayerId::Player1 },
                 AttackerUI,
@@ -3239,7 +3360,7 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
             hp_bar.spawn((
                 TextBundle::from_sections([
                     TextSection::new("P2 血量: ".to_string(), title_text_style.clone()),
-                    TextSection::new("100".to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
+                    TextSection::new(format!("{:.0}", rules.player_hp), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
                 ]),
                 PlayerHealthDisplay { player_id: PlayerId::Player2 },
                 AttackerUI,
@@ -3272,7 +3393,7 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
             style: Style { flex_direction: FlexDirection::Row, margin: UiRect::top(Val::Px(5.0)), ..default() },
             ..default()
         }).with_children(|bullets_container| {
-            for i in 0..BULLETS_PER_ROUND {
+            for i in 0..weapons::max_magazine_size(rules) {
                 bullets_container.spawn((
                     TextBundle::from_sections([
                         TextSection::new("● ".to_string(), TextStyle { font: font.clone(), font_size: 30.0, color: Color::rgb(1.0, 0.84, 0.0) }),
@@ -3282,6 +3403,11 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
                 ));
             }
         });
+        weapons::spawn_weapon_text(center, font.clone());
+        scope::spawn_scope_text(center, font.clone());
+        projectile::spawn_travel_time_text(center, font.clone());
+        ballistics::spawn_wind_text(center, font.clone());
+        recon::spawn_recon_text(center, font.clone());
     });
     
     // 时间文本（显示在左侧视口，调整位置避免与子弹UI重叠）
@@ -3289,7 +3415,7 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
         TextBundle {
             text: Text::from_sections([
                 TextSection::new("剩余时间: ".to_string(), TextStyle { font: font.clone(), font_size: 20.0, color: Color::WHITE }),
-                TextSection::new(ROUND_TIME_SECONDS.to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::YELLOW }),
+                TextSection::new(rules.round_time_seconds.to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::YELLOW }),
             ]),
             style: Style {
                 position_type: PositionType::Absolute, 
@@ -3308,7 +3434,7 @@ fn setup_attacker_ui(parent: &mut ChildBuilder, font: Handle<Font>, _text_style:
 
 /// 设置防守方视角的UI（右侧视口）
 /// 返回创建的子元素数量
-fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style: TextStyle, title_text_style: TextStyle) -> usize {
+fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style: TextStyle, title_text_style: TextStyle, rules: &GameRules) -> usize {
     // 血量显示（显示在右侧视口顶部）
     parent.spawn((
         NodeBundle {
@@ -3337,7 +3463,7 @@ fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style:
             hp_bar.spawn((
                 TextBundle::from_sections([
                     TextSection::new("P1 血量: ".to_string(), title_text_style.clone()),
-                    TextSection::new("100".to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
+                    TextSection::new(format!("{:.0}", rules.player_hp), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
                 ]),
                 PlayerHealthDisplay { player_id: PlayerId::Player1 },
                 DefenderUI,
@@ -3352,7 +3478,7 @@ fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style:
             hp_bar.spawn((
                 TextBundle::from_sections([
                     TextSection::new("P2 血量: ".to_string(), title_text_style.clone()),
-                    TextSection::new("100".to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
+                    TextSection::new(format!("{:.0}", rules.player_hp), TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE }),
                 ]),
                 PlayerHealthDisplay { player_id: PlayerId::Player2 },
                 DefenderUI,
@@ -3385,7 +3511,7 @@ fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style:
             style: Style { flex_direction: FlexDirection::Row, margin: UiRect::top(Val::Px(5.0)), ..default() },
             ..default()
         }).with_children(|bullets_container| {
-            for i in 0..BULLETS_PER_ROUND {
+            for i in 0..weapons::max_magazine_size(rules) {
                 bullets_container.spawn((
                     TextBundle::from_sections([
                         TextSection::new("● ".to_string(), TextStyle { font: font.clone(), font_size: 30.0, color: Color::rgb(1.0, 0.84, 0.0) }),
@@ -3402,7 +3528,7 @@ fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style:
         TextBundle {
             text: Text::from_sections([
                 TextSection::new("剩余时间: ".to_string(), TextStyle { font: font.clone(), font_size: 20.0, color: Color::WHITE }),
-                TextSection::new(ROUND_TIME_SECONDS.to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::YELLOW }),
+                TextSection::new(rules.round_time_seconds.to_string(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::YELLOW }),
             ]),
             style: Style {
                 position_type: PositionType::Absolute, 
@@ -3439,8 +3565,17 @@ fn setup_defender_ui(parent: &mut ChildBuilder, font: Handle<Font>, text_style:
         DefenderUI,
     ));
     
-    // 返回创建的直接子元素数量（4个：血量条、子弹数、时间、动作冷却）
-    4
+    // 道具（显示在动作冷却提示下方）
+    utility::spawn_utility_text(parent, font.clone());
+    
+    // 修墙（显示在道具下方）
+    repair::spawn_repair_text(parent, font.clone());
+    
+    // 体力条（显示在动作冷却提示上方）
+    stamina::spawn_stamina_bar(parent, font.clone());
+    
+    // 返回创建的直接子元素数量（7个：血量条、子弹数、时间、动作冷却、道具、修墙、体力条）
+    7
 }
 
 // --- 游玩系统函数已移至 gameplay.rs ---
@@ -0,0 +1,375 @@
+// 地图：墙体布局和场地
+//
+// 地图以 RON 文件保存在 assets/maps/ 下，描述：
+// - 一面或几面墙（位置、深度、行列数、基础材质布局，加上按区域覆盖的材质、开局就有的裂纹和打碎的破洞）
+//   深度 0 的墙离进攻方最近，深度大的墙在后面；不同深度的墙可以前后重叠，视线和子弹要依次穿过每一层
+// - 防守方可以活动的区域、双方的出生点
+// - 防守方区域里的掩体（箱子、柱子），打不穿也打不碎
+// 联机时主机在 StartGame 中把地图连同规则一起发给客户端（生成的地图只发种子，见 mapgen.rs），
+// 因此这个模块编译进库，解码时也要检查地图的大小和取值（整条消息要装进一个数据报）。
+// 坐标单位与游戏世界一致（像素），砖块尺寸由游戏本体决定。
+
+use serde::{Serialize, Deserialize};
+use crate::materials::{BrickSpec, WallLayout};
+
+/// 地图名的最大长度（字节）
+pub const MAX_MAP_NAME_LEN: usize = 32;
+/// 墙的最大数量
+pub const MAX_WALLS: usize = 4;
+/// 墙的最大深度（0 到 MAX_WALL_DEPTH，共四层）
+pub const MAX_WALL_DEPTH: u8 = 3;
+/// 单面墙的最大列数和行数
+pub const MAX_WALL_COLS: u8 = 40;
+pub const MAX_WALL_ROWS: u8 = 16;
+/// 整张地图中材质覆盖、裂纹和破洞区域的总数上限
+pub const MAX_BRICK_RANGES: usize = 32;
+/// 掩体的最大数量
+pub const MAX_COVER: usize = 8;
+/// 坐标的绝对值上限
+pub const MAP_HALF_EXTENT: f32 = 1000.0;
+/// 玩家身体的尺寸（宽、高），防守方身体中心的活动范围按它从区域边缘收缩
+pub const PLAYER_SIZE: [f32; 2] = [50.0, 100.0];
+/// 准星离瞄准原点（进攻方位置）的最大距离
+pub const MAX_AIM_OFFSET: f32 = 500.0;
+
+/// 墙上的一块矩形区域（行列都含两端，行从下往上数）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrickRange {
+    pub rows: [u8; 2],
+    pub cols: [u8; 2],
+}
+
+impl BrickRange {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.rows[0] as usize..=self.rows[1] as usize).contains(&row)
+            && (self.cols[0] as usize..=self.cols[1] as usize).contains(&col)
+    }
+
+    /// 裁掉超出 rows 行 cols 列的部分（墙变小时使用），整块都在墙外时返回 None
+    pub fn clipped(&self, rows: u8, cols: u8) -> Option<BrickRange> {
+        if self.rows[0] >= rows || self.cols[0] >= cols {
+            return None;
+        }
+        Some(BrickRange {
+            rows: [self.rows[0], self.rows[1].min(rows - 1)],
+            cols: [self.cols[0], self.cols[1].min(cols - 1)],
+        })
+    }
+}
+
+/// 把一块区域的砖块换成指定材质
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrickPatch {
+    pub range: BrickRange,
+    pub brick: BrickSpec,
+}
+
+/// 一面墙
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WallDef {
+    pub position: [f32; 2],        // 墙中心
+    pub depth: u8,                 // 深度（0 在最前面；同一深度的墙不要重叠）
+    pub cols: u8,
+    pub rows: u8,
+    pub layout: WallLayout,        // 基础材质布局
+    pub patches: Vec<BrickPatch>,  // 按区域覆盖的材质（后面的覆盖前面的）
+    pub cracks: Vec<BrickRange>,   // 开局就有裂纹的砖块（耐久减半，向上取整）
+    pub holes: Vec<BrickRange>,    // 开局就打碎的砖块
+}
+
+impl Default for WallDef {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            depth: 0,
+            cols: 22,
+            rows: 10,
+            layout: WallLayout::Classic,
+            patches: Vec::new(),
+            cracks: Vec::new(),
+            holes: Vec::new(),
+        }
+    }
+}
+
+impl WallDef {
+    /// 第 row 行第 col 列的砖块
+    pub fn brick_at(&self, row: usize, col: usize) -> BrickSpec {
+        self.patches
+            .iter()
+            .rev()
+            .find(|patch| patch.range.contains(row, col))
+            .map(|patch| patch.brick)
+            .unwrap_or_else(|| self.layout.brick_at(row, col, self.rows as usize, self.cols as usize))
+    }
+
+    /// 开局是否已经打碎（钢板不会碎，破洞区域里的钢板保持完好）
+    pub fn is_hole(&self, row: usize, col: usize) -> bool {
+        self.holes.iter().any(|hole| hole.contains(row, col)) && self.brick_at(row, col).hit_points().is_some()
+    }
+
+    /// 开局时的耐久（有裂纹的砖块减半）；钢板打不碎，返回 None
+    pub fn starting_hit_points(&self, row: usize, col: usize) -> Option<u32> {
+        let hit_points = self.brick_at(row, col).hit_points()?;
+        if self.cracks.iter().any(|crack| crack.contains(row, col)) {
+            Some(hit_points.div_ceil(2))
+        } else {
+            Some(hit_points)
+        }
+    }
+
+    /// 把一块砖换成指定材质（None 表示恢复基础布局），然后把覆盖区域重新合并成矩形（地图编辑器使用）
+    pub fn set_brick(&mut self, row: usize, col: usize, brick: Option<BrickSpec>) {
+        let (rows, cols) = (self.rows as usize, self.cols as usize);
+        let mut cells: Vec<Option<BrickSpec>> = (0..rows * cols)
+            .map(|index| {
+                let (r, c) = (index / cols, index % cols);
+                let spec = self.brick_at(r, c);
+                (spec != self.layout.brick_at(r, c, rows, cols)).then_some(spec)
+            })
+            .collect();
+        let base = self.layout.brick_at(row, col, rows, cols);
+        cells[row * cols + col] = brick.filter(|spec| *spec != base);
+        self.patches = merge_cells(&cells, rows, cols)
+            .into_iter()
+            .map(|(range, brick)| BrickPatch { range, brick })
+            .collect();
+    }
+
+    /// 打碎或补上一块砖，然后把破洞重新合并成矩形（地图编辑器使用）
+    pub fn set_hole(&mut self, row: usize, col: usize, hole: bool) {
+        let (rows, cols) = (self.rows as usize, self.cols as usize);
+        let mut cells: Vec<Option<()>> = (0..rows * cols)
+            .map(|index| self.holes.iter().any(|range| range.contains(index / cols, index % cols)).then_some(()))
+            .collect();
+        cells[row * cols + col] = hole.then_some(());
+        self.holes = merge_cells(&cells, rows, cols).into_iter().map(|(range, ())| range).collect();
+    }
+
+    /// 改变行列数，超出新大小的覆盖区域、裂纹和破洞被裁掉
+    pub fn resize(&mut self, cols: u8, rows: u8) {
+        self.cols = cols.clamp(1, MAX_WALL_COLS);
+        self.rows = rows.clamp(1, MAX_WALL_ROWS);
+        let (rows, cols) = (self.rows, self.cols);
+        self.patches = self
+            .patches
+            .iter()
+            .filter_map(|patch| patch.range.clipped(rows, cols).map(|range| BrickPatch { range, brick: patch.brick }))
+            .collect();
+        self.cracks = self.cracks.iter().filter_map(|crack| crack.clipped(rows, cols)).collect();
+        self.holes = self.holes.iter().filter_map(|hole| hole.clipped(rows, cols)).collect();
+    }
+}
+
+/// 把网格中取值相同的格子合并成尽量少的矩形（逐行扫描，每块先向右、再向上扩展）
+fn merge_cells<T: Copy + PartialEq>(cells: &[Option<T>], rows: usize, cols: usize) -> Vec<(BrickRange, T)> {
+    let mut used = vec![false; cells.len()];
+    let mut ranges = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let Some(value) = cells[row * cols + col] else {
+                continue;
+            };
+            if used[row * cols + col] {
+                continue;
+            }
+            let free = |r: usize, c: usize| !used[r * cols + c] && cells[r * cols + c] == Some(value);
+            let mut end_col = col;
+            while end_col + 1 < cols && free(row, end_col + 1) {
+                end_col += 1;
+            }
+            let mut end_row = row;
+            while end_row + 1 < rows && (col..=end_col).all(|c| free(end_row + 1, c)) {
+                end_row += 1;
+            }
+            for r in row..=end_row {
+                used[r * cols + col..=r * cols + end_col].fill(true);
+            }
+            ranges.push((BrickRange { rows: [row as u8, end_row as u8], cols: [col as u8, end_col as u8] }, value));
+        }
+    }
+    ranges
+}
+
+/// 掩体种类（只影响外观）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverKind {
+    #[default]
+    Crate,
+    Pillar,
+}
+
+impl CoverKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CoverKind::Crate => "箱子",
+            CoverKind::Pillar => "柱子",
+        }
+    }
+
+    pub fn next(&self) -> CoverKind {
+        match self {
+            CoverKind::Crate => CoverKind::Pillar,
+            CoverKind::Pillar => CoverKind::Crate,
+        }
+    }
+}
+
+/// 防守方区域里的掩体：挡住子弹，打不碎
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CoverProp {
+    pub kind: CoverKind,
+    pub position: [f32; 2],  // 中心
+    pub size: [f32; 2],
+}
+
+/// 矩形区域
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MapRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// 地图
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MapDef {
+    pub name: String,
+    pub walls: Vec<WallDef>,
+    pub defender_area: MapRect,     // 防守方身体不能离开的区域
+    pub attacker_spawn: [f32; 2],
+    pub defender_spawn: [f32; 2],
+    pub cover: Vec<CoverProp>,
+}
+
+/// 经典地图（内置默认值，地图文件缺失时使用）：一面 22×10 的砖墙，防守方在墙后活动
+impl Default for MapDef {
+    fn default() -> Self {
+        Self {
+            name: "classic".to_string(),
+            walls: vec![WallDef::default()],
+            // 22 列 × 40 像素、10 行 × 31.25 像素的墙所占的范围
+            defender_area: MapRect { min: [-440.0, -156.25], max: [440.0, 156.25] },
+            attacker_spawn: [0.0, 200.0],
+            defender_spawn: [0.0, -40.0],
+            cover: Vec::new(),
+        }
+    }
+}
+
+impl MapDef {
+    /// 从 RON 文本解析地图并检查取值
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let map: MapDef = ron::from_str(text).map_err(|e| format!("地图格式错误: {}", e))?;
+        map.validate()?;
+        Ok(map)
+    }
+
+    /// 防守方身体中心可以到达的范围（区域比身体还小时收缩到区域中心）
+    pub fn defender_bounds(&self, body_size: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+        let area = &self.defender_area;
+        let mut min = [0.0; 2];
+        let mut max = [0.0; 2];
+        for axis in 0..2 {
+            let center = (area.min[axis] + area.max[axis]) / 2.0;
+            min[axis] = (area.min[axis] + body_size[axis] / 2.0).min(center);
+            max[axis] = (area.max[axis] - body_size[axis] / 2.0).max(center);
+        }
+        (min, max)
+    }
+
+    /// 所有墙上材质覆盖、裂纹和破洞区域的总数（不能超过 MAX_BRICK_RANGES）
+    pub fn brick_range_count(&self) -> usize {
+        self.walls.iter().map(|wall| wall.patches.len() + wall.cracks.len() + wall.holes.len()).sum()
+    }
+
+    /// 把地图写成 RON 文本
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| format!("地图序列化失败: {}", e))
+    }
+
+    /// 检查地图：数量不超过上限，坐标有限且在范围内，区域不越出墙（对方发来的地图同样要经过这里）
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.len() > MAX_MAP_NAME_LEN {
+            return Err(format!("地图名过长: {} 字节", self.name.len()));
+        }
+        if self.walls.is_empty() || self.walls.len() > MAX_WALLS {
+            return Err(format!("墙的数量超出范围 [1, {}]: {}", MAX_WALLS, self.walls.len()));
+        }
+        let ranges = self.brick_range_count();
+        if ranges > MAX_BRICK_RANGES {
+            return Err(format!("材质覆盖、裂纹和破洞区域过多: {} > {}", ranges, MAX_BRICK_RANGES));
+        }
+        if self.cover.len() > MAX_COVER {
+            return Err(format!("掩体过多: {} > {}", self.cover.len(), MAX_COVER));
+        }
+        for wall in &self.walls {
+            check_point("walls.position", wall.position)?;
+            if wall.depth > MAX_WALL_DEPTH {
+                return Err(format!("墙的深度超出范围 [0, {}]: {}", MAX_WALL_DEPTH, wall.depth));
+            }
+            if !(1..=MAX_WALL_COLS).contains(&wall.cols) || !(1..=MAX_WALL_ROWS).contains(&wall.rows) {
+                return Err(format!("墙的大小超出范围: {} 列 × {} 行", wall.cols, wall.rows));
+            }
+            let ranges = wall.patches.iter().map(|patch| &patch.range).chain(&wall.cracks).chain(&wall.holes);
+            for range in ranges {
+                if range.rows[0] > range.rows[1] || range.cols[0] > range.cols[1] || range.rows[1] >= wall.rows || range.cols[1] >= wall.cols {
+                    return Err(format!("砖块区域越出墙: 行 {:?} 列 {:?}", range.rows, range.cols));
+                }
+            }
+        }
+        for cover in &self.cover {
+            check_point("cover.position", cover.position)?;
+            check_size("cover.size", cover.size)?;
+        }
+        check_point("defender_area.min", self.defender_area.min)?;
+        check_point("defender_area.max", self.defender_area.max)?;
+        if (0..2).any(|axis| self.defender_area.min[axis] > self.defender_area.max[axis]) {
+            return Err(format!("防守方活动区域不合法: {:?}", self.defender_area));
+        }
+        if !self.defender_area_contains(self.defender_spawn) {
+            return Err(format!("防守方出生点不在活动区域内: {:?}", self.defender_spawn));
+        }
+        check_point("attacker_spawn", self.attacker_spawn)
+    }
+
+    fn defender_area_contains(&self, point: [f32; 2]) -> bool {
+        let area = &self.defender_area;
+        (0..2).all(|axis| area.min[axis] <= point[axis] && point[axis] <= area.max[axis])
+    }
+}
+
+/// StartGame 中的地图：完整的地图，或生成地图的种子（双方用同一个种子生成同样的地图）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MapSource {
+    Def(MapDef),
+    Seed(u64),
+}
+
+impl MapSource {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            MapSource::Def(map) => map.validate(),
+            MapSource::Seed(_) => Ok(()),
+        }
+    }
+}
+
+fn check_point(field: &str, point: [f32; 2]) -> Result<(), String> {
+    for value in point {
+        if !value.is_finite() || value.abs() > MAP_HALF_EXTENT {
+            return Err(format!("{} 超出范围 [{}, {}]: {}", field, -MAP_HALF_EXTENT, MAP_HALF_EXTENT, value));
+        }
+    }
+    Ok(())
+}
+
+fn check_size(field: &str, size: [f32; 2]) -> Result<(), String> {
+    for value in size {
+        if !value.is_finite() || value <= 0.0 || value > MAP_HALF_EXTENT {
+            return Err(format!("{} 超出范围 (0, {}]: {}", field, MAP_HALF_EXTENT, value));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,646 @@
+// 地图编辑器：在游戏里搭建地图
+//
+// 主菜单点"地图编辑器"进入，编辑当前选择的地图。画面与对局中进攻方看到的一致，
+// 砖块和掩体用同一套生成代码（spawn_arena），每次修改后整张地图重新生成。
+// 按键：
+// - 1 砖块：左键刷材质，右键恢复墙的基础布局；Q 切换材质，R 切换加固
+// - 2 破洞：左键打碎，右键补上
+// - 3 出生点：左键放防守方，右键放进攻方
+// - 4 防守区域：左键依次点两个对角，右键取消
+// - 5 掩体：左键放置，右键删除；K 切换种类
+// - Tab 切换当前墙，N 在鼠标处新建一面墙，Delete 删除当前墙，方向键移动当前墙，
+//   [ ] 减少/增加列数，- = 减少/增加行数，L 切换基础布局，D 切换深度（深度大的墙在后面，可以与前面的墙重叠）
+// - F3 输入地图名，回车保存为 assets/maps/<地图名>.ron；F6 载入下一张地图
+// - F5 用本地双人模式试玩（回到主菜单时自动回到编辑器），Esc 返回主菜单
+// 地图文件中砖块材质和破洞按矩形区域保存，每次修改后重新合并（见 WallDef::set_brick）。
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, ReceivedCharacter};
+use bevy_sniper_duel::map::{MapRect, MAP_HALF_EXTENT, MAX_BRICK_RANGES, MAX_COVER, MAX_MAP_NAME_LEN, MAX_WALLS, MAX_WALL_DEPTH};
+use bevy_sniper_duel::materials::BrickSpec;
+use crate::arena::{self, brick_cell_at, brick_center, Arena, CoverKind, CoverProp, MapChoice, MapDef, WallDef};
+use crate::bot::SinglePlayerSettings;
+use crate::gameplay::{CoverBlock, ViewLayer, Wall, WallBackground, WallSegment};
+use crate::{AppState, FontResource, BRICK_HEIGHT, BRICK_WIDTH, PLAYER_SIZE};
+
+/// 方向键每次移动墙的距离（像素）
+const WALL_MOVE_STEP: f32 = 10.0;
+/// 新建的墙的大小
+const NEW_WALL_COLS: u8 = 8;
+const NEW_WALL_ROWS: u8 = 6;
+/// 辅助线的 Z（在砖块和掩体之上）
+const OVERLAY_Z: f32 = 3.0;
+/// 辅助线的粗细
+const OUTLINE_WIDTH: f32 = 2.0;
+
+/// 编辑工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum EditorTool {
+    #[default]
+    Brick,
+    Hole,
+    Spawn,
+    DefenderArea,
+    Cover,
+}
+
+impl EditorTool {
+    /// 工具对应的数字键
+    const KEYS: [(KeyCode, EditorTool); 5] = [
+        (KeyCode::Digit1, EditorTool::Brick),
+        (KeyCode::Digit2, EditorTool::Hole),
+        (KeyCode::Digit3, EditorTool::Spawn),
+        (KeyCode::Digit4, EditorTool::DefenderArea),
+        (KeyCode::Digit5, EditorTool::Cover),
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            EditorTool::Brick => "砖块材质",
+            EditorTool::Hole => "破洞",
+            EditorTool::Spawn => "出生点",
+            EditorTool::DefenderArea => "防守区域",
+            EditorTool::Cover => "掩体",
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self {
+            EditorTool::Brick => "左键刷材质，右键恢复基础布局（Q 切换材质，R 切换加固）",
+            EditorTool::Hole => "左键打碎，右键补上",
+            EditorTool::Spawn => "左键放防守方，右键放进攻方",
+            EditorTool::DefenderArea => "左键依次点两个对角，右键取消",
+            EditorTool::Cover => "左键放置，右键删除（K 切换种类）",
+        }
+    }
+}
+
+/// 新放置的掩体大小
+fn cover_size(kind: CoverKind) -> [f32; 2] {
+    match kind {
+        CoverKind::Crate => [70.0, 70.0],
+        CoverKind::Pillar => [60.0, 120.0],
+    }
+}
+
+/// 编辑器状态（离开编辑器后保留，试玩结束回来时继续编辑）
+#[derive(Resource, Default)]
+pub struct MapEditor {
+    map: MapDef,                 // 正在编辑的地图
+    wall: usize,                 // 当前墙
+    tool: EditorTool,
+    brush: BrickSpec,            // 砖块工具的材质
+    cover_kind: CoverKind,       // 掩体工具放置的种类
+    area_corner: Option<Vec2>,   // 防守区域：已经点下的第一个角
+    load_index: usize,           // F6 载入的地图在 MapChoice::names 中的位置
+    naming: bool,                // 是否正在输入地图名
+    status: Option<String>,      // 最近一次操作的结果（保存成功/失败等）
+    test_playing: bool,          // 试玩中（回到主菜单时直接回到编辑器）
+}
+
+impl MapEditor {
+    fn current_wall(&self) -> &WallDef {
+        &self.map.walls[self.wall.min(self.map.walls.len() - 1)]
+    }
+
+    fn current_wall_mut(&mut self) -> &mut WallDef {
+        let index = self.wall.min(self.map.walls.len() - 1);
+        &mut self.map.walls[index]
+    }
+
+    fn info_line(&self) -> String {
+        let wall = self.current_wall();
+        let reinforced = if self.brush.reinforced { "（加固）" } else { "" };
+        format!(
+            "工具: {}  画笔: {}{}  掩体: {}\n{}\n墙 {}/{}: {} 列 × {} 行，{}，深度 {}，位置 ({:.0}, {:.0})\n区域 {}/{}  掩体 {}/{}",
+            self.tool.label(),
+            self.brush.material.label(),
+            reinforced,
+            self.cover_kind.label(),
+            self.tool.hint(),
+            self.wall + 1,
+            self.map.walls.len(),
+            wall.cols,
+            wall.rows,
+            wall.layout.label(),
+            wall.depth,
+            wall.position[0],
+            wall.position[1],
+            self.map.brick_range_count(),
+            MAX_BRICK_RANGES,
+            self.map.cover.len(),
+            MAX_COVER,
+        )
+    }
+
+    fn name_line(&self) -> String {
+        format!("地图名: {}{}", self.map.name, if self.naming { "_" } else { "" })
+    }
+
+    fn status_line(&self) -> String {
+        if let Some(status) = &self.status {
+            status.clone()
+        } else if self.map.brick_range_count() > MAX_BRICK_RANGES {
+            format!("材质覆盖、裂纹和破洞区域超过 {} 个，保存前需要简化", MAX_BRICK_RANGES)
+        } else {
+            "F3 输入地图名，回车保存".to_string()
+        }
+    }
+}
+
+/// 编辑器相机（同时渲染UI）
+#[derive(Component)]
+pub struct EditorCamera;
+
+/// 编辑器面板根节点
+#[derive(Component)]
+pub struct MapEditorUI;
+
+/// 面板上需要刷新的文本
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorText {
+    Info,   // 工具和当前墙
+    Name,   // 地图名
+    Status, // 状态
+}
+
+/// 辅助线（出生点、防守区域、当前墙的边框），随地图一起重建
+#[derive(Component)]
+pub struct EditorOverlay;
+
+/// 跟随鼠标的预览框
+#[derive(Component)]
+pub struct EditorCursor;
+
+/// 编辑器重建画面时要清理的实体
+type ArenaEntityFilter = Or<(With<Wall>, With<WallSegment>, With<WallBackground>, With<CoverBlock>, With<EditorOverlay>)>;
+
+/// 离开编辑器时要清理的实体
+type EditorEntityFilter = Or<(ArenaEntityFilter, With<EditorCursor>, With<MapEditorUI>)>;
+
+/// 计算鼠标世界坐标用到的窗口和相机
+type CursorParams<'w, 's> = (
+    Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<EditorCamera>>,
+);
+
+/// 鼠标所在的世界坐标（取整到像素）
+fn cursor_world((windows, cameras): &CursorParams) -> Option<Vec2> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, transform) = cameras.get_single().ok()?;
+    camera.viewport_to_world_2d(transform, cursor).map(|point| point.round())
+}
+
+/// 墙占据的矩形（奇数行向右错开半块砖）
+fn wall_bounds(wall: &WallDef) -> (Vec2, Vec2) {
+    let half = Vec2::new(wall.cols as f32 * BRICK_WIDTH, wall.rows as f32 * BRICK_HEIGHT) / 2.0;
+    let stagger = if wall.rows > 1 { BRICK_WIDTH / 2.0 } else { 0.0 };
+    let center = Vec2::from(wall.position);
+    (center - half, center + half + Vec2::new(stagger, 0.0))
+}
+
+fn spawn_outline(commands: &mut Commands, min: Vec2, max: Vec2, color: Color) {
+    let size = max - min;
+    let center = (min + max) / 2.0;
+    let edges = [
+        (Vec2::new(center.x, max.y), Vec2::new(size.x, OUTLINE_WIDTH)),
+        (Vec2::new(center.x, min.y), Vec2::new(size.x, OUTLINE_WIDTH)),
+        (Vec2::new(min.x, center.y), Vec2::new(OUTLINE_WIDTH, size.y)),
+        (Vec2::new(max.x, center.y), Vec2::new(OUTLINE_WIDTH, size.y)),
+    ];
+    for (position, edge_size) in edges {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite { color, custom_size: Some(edge_size), ..default() },
+                transform: Transform::from_translation(position.extend(OVERLAY_Z)),
+                ..default()
+            },
+            EditorOverlay,
+        ));
+    }
+}
+
+fn spawn_overlays(commands: &mut Commands, editor: &MapEditor) {
+    let (wall_min, wall_max) = wall_bounds(editor.current_wall());
+    spawn_outline(commands, wall_min, wall_max, Color::CYAN);
+
+    let area = &editor.map.defender_area;
+    spawn_outline(commands, Vec2::from(area.min), Vec2::from(area.max), Color::YELLOW);
+
+    let defender = Vec2::from(editor.map.defender_spawn);
+    spawn_outline(commands, defender - PLAYER_SIZE / 2.0, defender + PLAYER_SIZE / 2.0, Color::GREEN);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite { color: Color::RED, custom_size: Some(Vec2::splat(20.0)), ..default() },
+            transform: Transform::from_translation(Vec2::from(editor.map.attacker_spawn).extend(OVERLAY_Z)),
+            ..default()
+        },
+        EditorOverlay,
+    ));
+}
+
+/// 把编辑中的地图保存为文件
+fn save_edited_map(editor: &mut MapEditor, map_choice: &mut MapChoice) {
+    editor.naming = false;
+    let name = editor.map.name.clone();
+    editor.status = Some(match arena::save_map(&name, &editor.map) {
+        Ok(path) => {
+            map_choice.add_name(&name);
+            format!("已保存地图: {}", path.display())
+        }
+        Err(e) => {
+            eprintln!("[地图编辑器] 保存地图失败: {}", e);
+            format!("保存失败: {}", e)
+        }
+    });
+}
+
+/// 载入轮换中的下一张地图（加载失败的地图跳过）
+fn load_next_map(editor: &mut MapEditor, map_choice: &MapChoice) {
+    for step in 1..=map_choice.names.len() {
+        let index = (editor.load_index + step) % map_choice.names.len();
+        let name = &map_choice.names[index];
+        match arena::load_named_map(name) {
+            Ok(map) => {
+                editor.map = map;
+                editor.load_index = index;
+                editor.wall = 0;
+                editor.area_corner = None;
+                editor.status = Some(format!("已载入地图: {}", name));
+                return;
+            }
+            Err(e) => eprintln!("[地图编辑器] 跳过地图 {}: {}", name, e),
+        }
+    }
+}
+
+/// 进入编辑器：换成编辑器相机，生成面板（试玩回来时继续编辑原来的地图）
+pub fn setup_map_editor(
+    mut commands: Commands,
+    mut editor: ResMut<MapEditor>,
+    map_choice: Res<MapChoice>,
+    font_resource: Res<FontResource>,
+    camera_query: Query<Entity, With<Camera>>,
+) {
+    if editor.test_playing {
+        editor.test_playing = false;
+        editor.status = Some("试玩结束".to_string());
+    } else {
+        *editor = MapEditor {
+            map: map_choice.map.clone(),
+            load_index: map_choice.selected,
+            brush: editor.brush,
+            cover_kind: editor.cover_kind,
+            ..default()
+        };
+    }
+
+    // 对局结束后可能留有多台游戏相机，编辑器只用一台相机
+    for entity in camera_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((Camera2dBundle::default(), EditorCamera));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite { color: Color::rgba(1.0, 1.0, 1.0, 0.35), ..default() },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, OVERLAY_Z + 0.5)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        EditorCursor,
+    ));
+
+    let font = font_resource.font.clone();
+    let text_style = TextStyle { font: font.clone(), font_size: 18.0, color: Color::WHITE };
+    let title_style = TextStyle { font: font.clone(), font_size: 22.0, color: Color::YELLOW };
+    let hint_style = TextStyle { font: font.clone(), font_size: 16.0, color: Color::GRAY };
+    let status_style = TextStyle { font, font_size: 18.0, color: Color::YELLOW };
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                width: Val::Px(560.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            ..default()
+        },
+        MapEditorUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("地图编辑器", title_style));
+        parent.spawn(TextBundle::from_section(
+            "1 砖块  2 破洞  3 出生点  4 防守区域  5 掩体\n\
+             Tab 切换墙  N 新建墙  Delete 删除墙  方向键 移动墙\n\
+             [ ] 列数  - = 行数  L 基础布局\n\
+             F3 输入地图名  F6 载入下一张地图  F5 试玩  Esc 返回主菜单",
+            hint_style,
+        ));
+        parent.spawn((TextBundle::from_section(editor.info_line(), text_style.clone()), EditorText::Info));
+        parent.spawn((TextBundle::from_section(editor.name_line(), text_style), EditorText::Name));
+        parent.spawn((TextBundle::from_section(editor.status_line(), status_style), EditorText::Status));
+    });
+}
+
+/// 编辑器的键盘操作（输入地图名时由 editor_name_input_system 处理）
+pub fn editor_keyboard_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<MapEditor>,
+    mut app_state: ResMut<NextState<AppState>>,
+    map_choice: Res<MapChoice>,
+    (mut current_arena, mut single_player): (ResMut<Arena>, ResMut<SinglePlayerSettings>),
+    cursor: CursorParams,
+) {
+    // 没有按键时不碰资源，避免每帧都触发画面重建
+    if editor.naming || keyboard_input.get_just_pressed().next().is_none() {
+        return;
+    }
+    let editor = &mut *editor;
+
+    for (key, tool) in EditorTool::KEYS {
+        if keyboard_input.just_pressed(key) {
+            editor.tool = tool;
+            editor.area_corner = None;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        editor.brush.material = editor.brush.material.next();
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        editor.brush.reinforced = !editor.brush.reinforced;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyK) {
+        editor.cover_kind = editor.cover_kind.next();
+    }
+
+    // 墙的增删和切换
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        editor.wall = (editor.wall + 1) % editor.map.walls.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        if editor.map.walls.len() < MAX_WALLS {
+            let position = cursor_world(&cursor).unwrap_or(Vec2::ZERO);
+            editor.map.walls.push(WallDef { position: position.into(), cols: NEW_WALL_COLS, rows: NEW_WALL_ROWS, ..default() });
+            editor.wall = editor.map.walls.len() - 1;
+        } else {
+            editor.status = Some(format!("最多 {} 面墙", MAX_WALLS));
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Delete) {
+        if editor.map.walls.len() > 1 {
+            let index = editor.wall.min(editor.map.walls.len() - 1);
+            editor.map.walls.remove(index);
+            editor.wall = index.min(editor.map.walls.len() - 1);
+        } else {
+            editor.status = Some("至少要保留一面墙".to_string());
+        }
+    }
+
+    // 当前墙的位置、大小和基础布局
+    let moves = [
+        (KeyCode::ArrowLeft, Vec2::new(-WALL_MOVE_STEP, 0.0)),
+        (KeyCode::ArrowRight, Vec2::new(WALL_MOVE_STEP, 0.0)),
+        (KeyCode::ArrowUp, Vec2::new(0.0, WALL_MOVE_STEP)),
+        (KeyCode::ArrowDown, Vec2::new(0.0, -WALL_MOVE_STEP)),
+    ];
+    for (key, step) in moves {
+        if keyboard_input.just_pressed(key) {
+            let wall = editor.current_wall_mut();
+            let position = (Vec2::from(wall.position) + step).clamp(Vec2::splat(-MAP_HALF_EXTENT), Vec2::splat(MAP_HALF_EXTENT));
+            wall.position = position.into();
+        }
+    }
+    let resizes = [
+        (KeyCode::BracketLeft, -1, 0),
+        (KeyCode::BracketRight, 1, 0),
+        (KeyCode::Minus, 0, -1),
+        (KeyCode::Equal, 0, 1),
+    ];
+    for (key, cols, rows) in resizes {
+        if keyboard_input.just_pressed(key) {
+            let wall = editor.current_wall_mut();
+            let (new_cols, new_rows) = (wall.cols.saturating_add_signed(cols), wall.rows.saturating_add_signed(rows));
+            wall.resize(new_cols, new_rows);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyL) {
+        let wall = editor.current_wall_mut();
+        wall.layout = wall.layout.next();
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyD) {
+        let wall = editor.current_wall_mut();
+        wall.depth = (wall.depth + 1) % (MAX_WALL_DEPTH + 1);
+    }
+
+    // 文件、试玩和退出
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        editor.naming = true;
+        editor.status = None;
+    }
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        load_next_map(editor, &map_choice);
+    }
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        match editor.map.validate() {
+            Ok(()) => {
+                // 只替换本局地图，主菜单里选择的地图不变
+                *current_arena = Arena::new(editor.map.clone());
+                single_player.enabled = false;
+                editor.test_playing = true;
+                app_state.set(AppState::LocalMultiplayer);
+            }
+            Err(e) => editor.status = Some(format!("地图不合法，无法试玩: {}", e)),
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        app_state.set(AppState::MainMenu);
+    }
+}
+
+/// 鼠标操作：按当前工具修改鼠标所在位置
+pub fn editor_mouse_system(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut editor: ResMut<MapEditor>,
+    cursor: CursorParams,
+) {
+    let (left, right) = (MouseButton::Left, MouseButton::Right);
+    if !mouse_input.pressed(left) && !mouse_input.pressed(right) {
+        return;
+    }
+    let Some(point) = cursor_world(&cursor) else {
+        return;
+    };
+
+    match editor.tool {
+        // 砖块和破洞可以按住拖动连续修改，格子没有变化时不改资源
+        EditorTool::Brick | EditorTool::Hole => {
+            let wall = editor.current_wall();
+            let Some((row, col)) = brick_cell_at(wall, point) else {
+                return;
+            };
+            let paint = mouse_input.pressed(left);
+            if editor.tool == EditorTool::Brick {
+                let base = wall.layout.brick_at(row, col, wall.rows as usize, wall.cols as usize);
+                let target = if paint { editor.brush } else { base };
+                if wall.brick_at(row, col) != target {
+                    let brick = paint.then_some(target);
+                    editor.current_wall_mut().set_brick(row, col, brick);
+                }
+            } else if wall.holes.iter().any(|hole| hole.contains(row, col)) != paint {
+                editor.current_wall_mut().set_hole(row, col, paint);
+            }
+        }
+        EditorTool::Spawn => {
+            if mouse_input.just_pressed(left) {
+                editor.map.defender_spawn = point.into();
+            } else if mouse_input.just_pressed(right) {
+                editor.map.attacker_spawn = point.into();
+            }
+        }
+        EditorTool::DefenderArea => {
+            if mouse_input.just_pressed(right) {
+                editor.area_corner = None;
+            } else if mouse_input.just_pressed(left) {
+                match editor.area_corner.take() {
+                    Some(corner) => {
+                        editor.map.defender_area = MapRect { min: corner.min(point).into(), max: corner.max(point).into() };
+                    }
+                    None => editor.area_corner = Some(point),
+                }
+            }
+        }
+        EditorTool::Cover => {
+            if mouse_input.just_pressed(left) {
+                if editor.map.cover.len() < MAX_COVER {
+                    let kind = editor.cover_kind;
+                    editor.map.cover.push(CoverProp { kind, position: point.into(), size: cover_size(kind) });
+                } else {
+                    editor.status = Some(format!("最多 {} 个掩体", MAX_COVER));
+                }
+            } else if mouse_input.just_pressed(right) {
+                let hit = editor.map.cover.iter().rposition(|cover| {
+                    let half = Vec2::from(cover.size) / 2.0;
+                    (point - Vec2::from(cover.position)).abs().cmple(half).all()
+                });
+                if let Some(index) = hit {
+                    editor.map.cover.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// 输入地图名（F3 开始输入，回车保存，Esc 取消）
+pub fn editor_name_input_system(
+    mut editor: ResMut<MapEditor>,
+    mut map_choice: ResMut<MapChoice>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    if !editor.naming {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        for c in event.char.chars() {
+            if (c.is_ascii_alphanumeric() || c == '_' || c == '-') && editor.map.name.len() < MAX_MAP_NAME_LEN {
+                editor.map.name.push(c);
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        editor.map.name.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        save_edited_map(&mut editor, &mut map_choice);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        editor.naming = false;
+    }
+}
+
+/// 地图变化后重新生成墙、掩体和辅助线（与对局中进攻方视角的生成代码相同）
+pub fn rebuild_editor_view_system(
+    mut commands: Commands,
+    editor: Res<MapEditor>,
+    entity_query: Query<Entity, ArenaEntityFilter>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    crate::spawn_arena(&mut commands, &Arena::new(editor.map.clone()), &[ViewLayer::AttackerView], None, false);
+    spawn_overlays(&mut commands, &editor);
+}
+
+/// 预览框：砖块和破洞工具框住鼠标下的砖块，掩体工具显示掩体大小，画防守区域时显示拉出的矩形
+pub fn editor_cursor_system(
+    editor: Res<MapEditor>,
+    cursor: CursorParams,
+    mut cursor_query: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<EditorCursor>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = cursor_query.get_single_mut() else {
+        return;
+    };
+    let preview = cursor_world(&cursor).and_then(|point| match editor.tool {
+        EditorTool::Brick | EditorTool::Hole => {
+            let wall = editor.current_wall();
+            brick_cell_at(wall, point).map(|(row, col)| (brick_center(wall, row, col), Vec2::new(BRICK_WIDTH, BRICK_HEIGHT)))
+        }
+        EditorTool::Spawn => Some((point, PLAYER_SIZE)),
+        EditorTool::DefenderArea => match editor.area_corner {
+            Some(corner) => Some(((corner + point) / 2.0, (point - corner).abs().max(Vec2::splat(OUTLINE_WIDTH)))),
+            None => Some((point, Vec2::splat(10.0))),
+        },
+        EditorTool::Cover => Some((point, Vec2::from(cover_size(editor.cover_kind)))),
+    });
+    match preview {
+        Some((position, size)) => {
+            transform.translation = position.extend(OVERLAY_Z + 0.5);
+            sprite.custom_size = Some(size);
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+/// 更新面板文本（只在编辑器状态变化时运行）
+pub fn update_editor_ui_system(
+    editor: Res<MapEditor>,
+    mut text_query: Query<(&EditorText, &mut Text)>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    for (editor_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match editor_text {
+            EditorText::Info => editor.info_line(),
+            EditorText::Name => editor.name_line(),
+            EditorText::Status => editor.status_line(),
+        };
+    }
+}
+
+/// 离开编辑器：清理地图、辅助线和面板（相机留给主菜单或由对局替换）
+pub fn cleanup_map_editor(
+    mut commands: Commands,
+    entity_query: Query<Entity, EditorEntityFilter>,
+) {
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// 试玩结束回到主菜单时直接回到编辑器
+pub fn return_after_test_play(editor: Res<MapEditor>, mut app_state: ResMut<NextState<AppState>>) {
+    if editor.test_playing {
+        app_state.set(AppState::MapEditor);
+    }
+}
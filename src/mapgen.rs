@@ -0,0 +1,243 @@
+// 程序生成的地图：由一个种子决定整张地图
+//
+// 同一个种子在任何机器上都生成同样的地图，联机时主机只需在 StartGame 中发送种子（MapSource::Seed）。
+// 生成的内容：
+// - 一面宽墙，或中间留缝的两面墙（基础布局在砖墙和混合材质之间随机）
+// - 随机区域换成石膏板、混凝土或加固砖，偶尔有一小块钢板
+// - 加固砖框着的玻璃窗
+// - 开局就有裂纹的区域（耐久减半）和少量破洞
+// - 加厚段：在墙的一部分后面再叠一层深度 1 的墙（视线要两层都打穿才能透过去，子弹要连着打穿两层）
+// - 防守方区域里的箱子和柱子
+// 随机数用自带的 SplitMix64 而不是 rand 的 StdRng：StdRng 的算法可能随版本变化，而种子要能在以后重玩同一张地图。
+
+use crate::map::{BrickPatch, BrickRange, CoverKind, CoverProp, MapDef, MapRect, WallDef};
+use crate::materials::{BrickMaterial, BrickSpec, WallLayout};
+
+/// 随机换掉的区域可用的材质
+const PATCH_BRICKS: [BrickSpec; 4] = [
+    BrickSpec::new(BrickMaterial::Plaster, false),
+    BrickSpec::new(BrickMaterial::Concrete, false),
+    BrickSpec::new(BrickMaterial::Brick, true),
+    BrickSpec::new(BrickMaterial::Concrete, true),
+];
+/// 窗框和窗户
+const WINDOW_FRAME: BrickSpec = BrickSpec::new(BrickMaterial::Brick, true);
+const WINDOW_GLASS: BrickSpec = BrickSpec::new(BrickMaterial::Glass, false);
+const STEEL: BrickSpec = BrickSpec::new(BrickMaterial::Steel, false);
+const CONCRETE: BrickSpec = BrickSpec::new(BrickMaterial::Concrete, false);
+/// 防守方出生点的高度（与经典地图一致）
+const DEFENDER_SPAWN_Y: f32 = -40.0;
+/// 进攻方在墙顶上方的距离（与经典地图一致：156.25 + 43.75 = 200）
+const ATTACKER_ABOVE_WALL: f32 = 43.75;
+
+/// 种子决定的随机数（SplitMix64）
+struct SeedRng(u64);
+
+impl SeedRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [min, max] 中的整数
+    fn range(&mut self, min: u8, max: u8) -> u8 {
+        min + (self.next_u64() % (max - min + 1) as u64) as u8
+    }
+
+    /// [0, 1) 中的小数
+    fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    /// [min, max] 中的坐标（取整到像素）
+    fn between(&mut self, min: f32, max: f32) -> f32 {
+        (min + (max - min) * self.unit()).round()
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.range(0, items.len() as u8 - 1) as usize]
+    }
+}
+
+/// 生成的地图名
+pub fn map_name(seed: u64) -> String {
+    format!("seed-{}", seed)
+}
+
+/// 按种子生成地图；brick_size 是游戏中砖块的宽和高（墙和场地的尺寸由它换算）
+pub fn generate(seed: u64, brick_size: [f32; 2]) -> MapDef {
+    let mut rng = SeedRng(seed);
+    let [brick_width, brick_height] = brick_size;
+    let rows = rng.range(8, 12);
+    let layout = if rng.chance(0.25) { WallLayout::Mixed } else { WallLayout::Classic };
+
+    let mut walls = Vec::new();
+    if rng.chance(0.6) {
+        let cols = rng.range(16, 24);
+        walls.push(front_wall(&mut rng, [0.0, 0.0], cols, rows, layout));
+    } else {
+        // 两面墙中间留一到三块砖宽的缝
+        let cols = rng.range(8, 12);
+        let gap = rng.range(1, 3) as f32 * brick_width;
+        let offset = (cols as f32 * brick_width + gap) / 2.0;
+        for x in [-offset, offset] {
+            walls.push(front_wall(&mut rng, [x, 0.0], cols, rows, layout));
+        }
+    }
+    let front_walls = walls.len();
+    for index in 0..front_walls {
+        if rng.chance(0.4) {
+            let section = backing_section(&mut rng, &walls[index], brick_size);
+            walls.push(section);
+        }
+    }
+
+    // 防守方区域覆盖所有前墙，进攻方在墙顶上方
+    let half_height = rows as f32 * brick_height / 2.0;
+    let half_width = walls[..front_walls]
+        .iter()
+        .map(|wall| wall.position[0].abs() + wall.cols as f32 * brick_width / 2.0)
+        .fold(0.0, f32::max);
+    let defender_area = MapRect { min: [-half_width, -half_height], max: [half_width, half_height] };
+    let spawn_wall = rng.range(0, front_walls as u8 - 1) as usize;
+    let defender_spawn = [walls[spawn_wall].position[0], DEFENDER_SPAWN_Y];
+    let attacker_spawn = [0.0, half_height + ATTACKER_ABOVE_WALL];
+
+    let cover = (0..rng.range(0, 2)).map(|_| cover_prop(&mut rng, &defender_area)).collect();
+
+    MapDef { name: map_name(seed), walls, defender_area, attacker_spawn, defender_spawn, cover }
+}
+
+/// 墙上随机的一块区域（高不超过 max_rows 行，宽不超过 max_cols 列）
+fn random_range(rng: &mut SeedRng, wall: &WallDef, max_rows: u8, max_cols: u8) -> BrickRange {
+    let height = rng.range(1, max_rows.min(wall.rows));
+    let width = rng.range(1, max_cols.min(wall.cols));
+    sized_range(rng, wall, height, width)
+}
+
+/// 墙上随机位置的一块 height 行 × width 列的区域
+fn sized_range(rng: &mut SeedRng, wall: &WallDef, height: u8, width: u8) -> BrickRange {
+    let row = rng.range(0, wall.rows - height);
+    let col = rng.range(0, wall.cols - width);
+    BrickRange { rows: [row, row + height - 1], cols: [col, col + width - 1] }
+}
+
+/// 前墙：随机材质区域、钢板、窗户、裂纹和破洞（墙至少 8 行 8 列，窗框放得下）
+fn front_wall(rng: &mut SeedRng, position: [f32; 2], cols: u8, rows: u8, layout: WallLayout) -> WallDef {
+    let mut wall = WallDef { position, cols, rows, layout, ..Default::default() };
+    for _ in 0..rng.range(1, 3) {
+        let brick = rng.pick(&PATCH_BRICKS);
+        let range = random_range(rng, &wall, 4, 6);
+        wall.patches.push(BrickPatch { range, brick });
+    }
+    if rng.chance(0.3) {
+        let range = random_range(rng, &wall, 1, 2);
+        wall.patches.push(BrickPatch { range, brick: STEEL });
+    }
+    for _ in 0..rng.range(0, 2) {
+        let (height, width) = (rng.range(1, 2), rng.range(2, 3));
+        let frame = sized_range(rng, &wall, height + 2, width + 2);
+        let glass = BrickRange { rows: [frame.rows[0] + 1, frame.rows[1] - 1], cols: [frame.cols[0] + 1, frame.cols[1] - 1] };
+        wall.patches.push(BrickPatch { range: frame, brick: WINDOW_FRAME });
+        wall.patches.push(BrickPatch { range: glass, brick: WINDOW_GLASS });
+    }
+    for _ in 0..rng.range(1, 3) {
+        let range = random_range(rng, &wall, 3, 5);
+        wall.cracks.push(range);
+    }
+    for _ in 0..rng.range(0, 2) {
+        let range = random_range(rng, &wall, 1, 2);
+        wall.holes.push(range);
+    }
+    wall
+}
+
+/// 加厚段：与前墙的一部分重叠、砖块对齐的一小面墙（从偶数行开始，与前墙的错缝一致）
+fn backing_section(rng: &mut SeedRng, front: &WallDef, brick_size: [f32; 2]) -> WallDef {
+    let [brick_width, brick_height] = brick_size;
+    let rows = rng.range(2, 4).min(front.rows);
+    let cols = rng.range(3, 6).min(front.cols);
+    let first_row = rng.range(0, (front.rows - rows) / 2) * 2;
+    let first_col = rng.range(0, front.cols - cols);
+    let offset_x = (first_col as f32 + (cols as f32 - 1.0) / 2.0 - (front.cols as f32 - 1.0) / 2.0) * brick_width;
+    let offset_y = (first_row as f32 + (rows as f32 - 1.0) / 2.0 - (front.rows as f32 - 1.0) / 2.0) * brick_height;
+    let mut section = WallDef {
+        position: [front.position[0] + offset_x, front.position[1] + offset_y],
+        depth: front.depth + 1,
+        cols,
+        rows,
+        layout: WallLayout::Classic,
+        ..Default::default()
+    };
+    if rng.chance(0.5) {
+        let range = BrickRange { rows: [0, rows - 1], cols: [0, cols - 1] };
+        section.patches.push(BrickPatch { range, brick: CONCRETE });
+    }
+    section
+}
+
+/// 防守方区域里的掩体（整个放在区域内）
+fn cover_prop(rng: &mut SeedRng, area: &MapRect) -> CoverProp {
+    let kind = rng.pick(&[CoverKind::Crate, CoverKind::Pillar]);
+    let size = match kind {
+        CoverKind::Crate => {
+            let side = rng.between(60.0, 90.0);
+            [side, side]
+        }
+        CoverKind::Pillar => [rng.between(50.0, 70.0), rng.between(90.0, 130.0)],
+    };
+    let position = [
+        rng.between(area.min[0] + size[0] / 2.0, area.max[0] - size[0] / 2.0),
+        rng.between(area.min[1] + size[1] / 2.0, area.max[1] - size[1] / 2.0),
+    ];
+    CoverProp { kind, position, size }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 游戏中的砖块尺寸（与 main.rs 的 BRICK_WIDTH、BRICK_HEIGHT 一致）
+    const BRICK_SIZE: [f32; 2] = [40.0, 31.25];
+
+    /// 地图编码后的 FNV-1a 指纹（生成算法或地图格式一变就会不同）
+    fn fingerprint(map: &MapDef) -> u64 {
+        let bytes = bincode::serialize(map).expect("地图应能编码");
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    #[test]
+    fn same_seed_generates_same_map() {
+        assert_eq!(generate(42, BRICK_SIZE), generate(42, BRICK_SIZE));
+        assert_ne!(generate(42, BRICK_SIZE), generate(43, BRICK_SIZE));
+    }
+
+    /// 种子要能在以后重玩同一张地图：固定种子生成的地图不能随版本变化
+    #[test]
+    fn fixed_seed_generates_known_map() {
+        let map = generate(42, BRICK_SIZE);
+        assert_eq!(map.name, "seed-42");
+        assert_eq!(map.walls.len(), 2);
+        assert_eq!((map.walls[0].rows, map.walls[0].cols), (11, 16));
+        assert_eq!(map.defender_spawn, [0.0, -40.0]);
+        assert_eq!(map.cover.len(), 1);
+        assert_eq!(fingerprint(&map), 0xf7c5_3418_c40e_2011);
+    }
+
+    #[test]
+    fn generated_maps_are_valid() {
+        for seed in 0..500 {
+            let map = generate(seed, BRICK_SIZE);
+            if let Err(e) = map.validate() {
+                panic!("种子 {} 生成的地图不合法: {}", seed, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// 砖块材质：每块砖有自己的材质、耐久和外观
+//
+// - 石膏板：一枪就碎，挡不住子弹（穿过时不损失伤害）
+// - 砖：经典墙体，一枪就碎，算 1 块砖的穿透
+// - 混凝土：要打好几枪，算 2 块砖的穿透
+// - 钢板：打不碎也打不穿
+// - 玻璃：透明，能看到后面的人，但会挡住子弹（第一枪打碎玻璃，之后才能打穿）
+// 加固砖块的耐久翻三倍，穿透时多算 1 块砖（钢板和玻璃不受影响）。
+// 每面墙的基础布局由地图选择（见 map.rs），联机时地图随 StartGame 同步，双方生成同样的墙。
+
+use bevy::prelude::Color;
+use serde::{Serialize, Deserialize};
+
+/// 加固砖块的耐久倍率
+pub const REINFORCED_HIT_POINTS_SCALE: u32 = 3;
+/// 每损失 1 点耐久，砖块颜色变暗的比例（裂纹）
+const CRACK_DARKEN: f32 = 0.75;
+
+/// 砖块材质
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BrickMaterial {
+    Plaster,
+    #[default]
+    Brick,
+    Concrete,
+    Steel,
+    Glass,
+}
+
+impl BrickMaterial {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BrickMaterial::Plaster => "石膏板",
+            BrickMaterial::Brick => "砖",
+            BrickMaterial::Concrete => "混凝土",
+            BrickMaterial::Steel => "钢板",
+            BrickMaterial::Glass => "玻璃",
+        }
+    }
+
+    /// 下一种材质（地图编辑器中切换画笔）
+    pub fn next(&self) -> BrickMaterial {
+        match self {
+            BrickMaterial::Plaster => BrickMaterial::Brick,
+            BrickMaterial::Brick => BrickMaterial::Concrete,
+            BrickMaterial::Concrete => BrickMaterial::Steel,
+            BrickMaterial::Steel => BrickMaterial::Glass,
+            BrickMaterial::Glass => BrickMaterial::Plaster,
+        }
+    }
+
+    /// 耐久（每发子弹按武器的破墙伤害扣除）；钢板打不碎，返回 None
+    pub fn hit_points(&self) -> Option<u32> {
+        match self {
+            BrickMaterial::Plaster | BrickMaterial::Brick | BrickMaterial::Glass => Some(1),
+            BrickMaterial::Concrete => Some(3),
+            BrickMaterial::Steel => None,
+        }
+    }
+
+    /// 穿透时相当于几块砖；打不穿时返回 None
+    pub fn penetration_cost(&self) -> Option<usize> {
+        match self {
+            BrickMaterial::Plaster => Some(0),
+            BrickMaterial::Brick => Some(1),
+            BrickMaterial::Concrete => Some(2),
+            BrickMaterial::Steel | BrickMaterial::Glass => None,
+        }
+    }
+
+    /// 透明材质：进攻方能透过它看到防守方
+    pub fn is_see_through(&self) -> bool {
+        matches!(self, BrickMaterial::Glass)
+    }
+
+    /// 材质的两种颜色（相邻砖块交替使用）
+    fn colors(&self) -> [Color; 2] {
+        match self {
+            BrickMaterial::Plaster => [Color::rgb(0.92, 0.9, 0.84), Color::rgb(0.85, 0.83, 0.76)],
+            BrickMaterial::Brick => [Color::rgb(0.8, 0.6, 0.5), Color::rgb(0.5, 0.35, 0.25)],
+            BrickMaterial::Concrete => [Color::rgb(0.58, 0.58, 0.56), Color::rgb(0.48, 0.48, 0.47)],
+            BrickMaterial::Steel => [Color::rgb(0.35, 0.4, 0.48), Color::rgb(0.3, 0.34, 0.42)],
+            BrickMaterial::Glass => [Color::rgba(0.6, 0.85, 1.0, 0.3), Color::rgba(0.7, 0.9, 1.0, 0.35)],
+        }
+    }
+}
+
+/// 一块砖的材质和是否加固
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BrickSpec {
+    pub material: BrickMaterial,
+    pub reinforced: bool,
+}
+
+impl BrickSpec {
+    pub const fn new(material: BrickMaterial, reinforced: bool) -> Self {
+        Self { material, reinforced }
+    }
+
+    /// 钢板和玻璃不能加固
+    fn is_reinforced(&self) -> bool {
+        self.reinforced && !matches!(self.material, BrickMaterial::Steel | BrickMaterial::Glass)
+    }
+
+    pub fn hit_points(&self) -> Option<u32> {
+        let scale = if self.is_reinforced() { REINFORCED_HIT_POINTS_SCALE } else { 1 };
+        self.material.hit_points().map(|hp| hp * scale)
+    }
+
+    pub fn penetration_cost(&self) -> Option<usize> {
+        let extra = usize::from(self.is_reinforced());
+        self.material.penetration_cost().map(|cost| cost + extra)
+    }
+
+    /// 完好时的颜色（加固砖块颜色更深）
+    pub fn color(&self, row: usize, col: usize) -> Color {
+        let color = self.material.colors()[(row + col) % 2];
+        if self.is_reinforced() {
+            darken(color, 0.8)
+        } else {
+            color
+        }
+    }
+
+    /// 损失 lost_hit_points 点耐久后的颜色（在 color 的基础上加深）
+    pub fn cracked_color(color: Color, lost_hit_points: u32) -> Color {
+        darken(color, CRACK_DARKEN.powi(lost_hit_points as i32))
+    }
+}
+
+fn darken(color: Color, factor: f32) -> Color {
+    Color::rgba(color.r() * factor, color.g() * factor, color.b() * factor, color.a())
+}
+
+/// 墙体的基础材质布局（地图中每面墙的 layout）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WallLayout {
+    /// 经典：整面砖墙
+    #[default]
+    Classic,
+    /// 混合材质：钢板底座、混凝土柱、加固窗框围着的玻璃窗、顶部的石膏板
+    Mixed,
+}
+
+impl WallLayout {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WallLayout::Classic => "砖墙",
+            WallLayout::Mixed => "混合材质",
+        }
+    }
+
+    pub fn next(&self) -> WallLayout {
+        match self {
+            WallLayout::Classic => WallLayout::Mixed,
+            WallLayout::Mixed => WallLayout::Classic,
+        }
+    }
+
+    /// 第 row 行（从下往上）第 col 列的砖块
+    pub fn brick_at(&self, row: usize, col: usize, rows: usize, cols: usize) -> BrickSpec {
+        match self {
+            WallLayout::Classic => BrickSpec::default(),
+            WallLayout::Mixed => mixed_brick(row, col, rows, cols),
+        }
+    }
+}
+
+fn mixed_brick(row: usize, col: usize, rows: usize, cols: usize) -> BrickSpec {
+    let center = cols / 2;
+    // 离墙中线的列距（左右对称）
+    let from_center = if col < center { center - 1 - col } else { col - center };
+    let window_rows = rows / 2..rows / 2 + 2;
+    let window = window_rows.contains(&row) && from_center < 2;
+    let window_frame = (window_rows.start.saturating_sub(1)..=window_rows.end).contains(&row) && from_center < 3;
+
+    if row == 0 && from_center + 4 >= center {
+        BrickSpec::new(BrickMaterial::Steel, false)
+    } else if window {
+        BrickSpec::new(BrickMaterial::Glass, false)
+    } else if window_frame {
+        BrickSpec::new(BrickMaterial::Brick, true)
+    } else if from_center == center / 2 {
+        BrickSpec::new(BrickMaterial::Concrete, false)
+    } else if row + 2 >= rows {
+        BrickSpec::new(BrickMaterial::Plaster, false)
+    } else {
+        BrickSpec::default()
+    }
+}
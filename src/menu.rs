@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use crate::AppState;
 use crate::FontResource;
+use crate::bot::SinglePlayerSettings;
+use crate::game_rules::{self, GameRules};
+use crate::arena::{self, Arena, MapChoice};
 
 /// 主菜单UI组件
 #[derive(Component)]
@@ -10,9 +13,24 @@ pub struct MainMenuButton;
 #[derive(Component)]
 pub struct MainMenuUI;
 
+/// 选项按钮上的文字（点击后更新）
+#[derive(Component)]
+pub enum MenuOptionText {
+    BotDifficulty,
+    MatchFormat,
+    HitResolution,
+    Map,
+}
+
 #[derive(Component)]
 pub enum MenuButtonType {
+    SinglePlayer,   // 单人练习（对电脑）
+    BotDifficulty,  // 切换电脑难度
     LocalMultiplayer,
+    MatchFormat,    // 切换赛制
+    HitResolution,  // 切换命中判定方式
+    Map,            // 切换地图
+    MapEditor,      // 地图编辑器
     NetworkMatch,
     Settings,
 }
@@ -21,6 +39,9 @@ pub enum MenuButtonType {
 pub fn setup_main_menu(
     mut commands: Commands,
     font_resource: Res<FontResource>,
+    single_player: Res<SinglePlayerSettings>,
+    rules: Res<GameRules>,
+    map_choice: Res<MapChoice>,
 ) {
     let font = font_resource.font.clone();
     
@@ -67,6 +88,69 @@ pub fn setup_main_menu(
             },
             ..default()
         }).with_children(|buttons| {
+            // 单人练习按钮
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(80.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.6, 0.4).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::SinglePlayer,
+            )).with_children(|button| {
+                button.spawn(TextBundle {
+                    text: Text::from_sections([TextSection::new(
+                        "单人练习",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    )]),
+                    ..default()
+                });
+            });
+            
+            // 电脑难度按钮（点击切换）
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.15, 0.35, 0.25).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::BotDifficulty,
+            )).with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_sections([TextSection::new(
+                            format!("电脑难度: {}", single_player.difficulty.label()),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        )]),
+                        ..default()
+                    },
+                    MenuOptionText::BotDifficulty,
+                ));
+            });
+            
             // 本地双人按钮
             buttons.spawn((
                 ButtonBundle {
@@ -127,6 +211,135 @@ pub fn setup_main_menu(
                 });
             });
             
+            // 赛制按钮（点击切换，本地和联机对战都使用；联机时以主机的赛制为准）
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.45, 0.3, 0.15).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::MatchFormat,
+            )).with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_sections([TextSection::new(
+                            format!("赛制: {}", rules.match_format.label()),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        )]),
+                        ..default()
+                    },
+                    MenuOptionText::MatchFormat,
+                ));
+            });
+            
+            // 命中判定按钮（准星判定 / 弹道判定，联机时以主机的规则为准）
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.45, 0.3, 0.15).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::HitResolution,
+            )).with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_sections([TextSection::new(
+                            format!("命中判定: {}", rules.hit_resolution.label()),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        )]),
+                        ..default()
+                    },
+                    MenuOptionText::HitResolution,
+                ));
+            });
+            
+            // 墙体布局按钮（砖墙 / 混合材质，联机时以主机的规则为准）
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.4, 0.4, 0.45).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::Map,
+            )).with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_sections([TextSection::new(
+                            format!("地图: {}", map_choice.label()),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        )]),
+                        ..default()
+                    },
+                    MenuOptionText::Map,
+                ));
+            });
+            
+            // 地图编辑器按钮
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.4, 0.4, 0.45).into(),
+                    ..default()
+                },
+                MainMenuButton,
+                MenuButtonType::MapEditor,
+            )).with_children(|button| {
+                button.spawn(TextBundle {
+                    text: Text::from_sections([TextSection::new(
+                        "地图编辑器",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                    )]),
+                    ..default()
+                });
+            });
+            
             // 设置按钮
             buttons.spawn((
                 ButtonBundle {
@@ -166,11 +379,58 @@ pub fn handle_main_menu_buttons(
         (Changed<Interaction>, With<Button>),
     >,
     mut app_state: ResMut<NextState<AppState>>,
+    mut single_player: ResMut<SinglePlayerSettings>,
+    mut option_text_query: Query<(&mut Text, &MenuOptionText)>,
+    mut rules: ResMut<GameRules>,
+    (mut map_choice, mut current_arena): (ResMut<MapChoice>, ResMut<Arena>),
 ) {
     for (interaction, button_type) in interaction_query.iter_mut() {
         if *interaction == Interaction::Pressed {
             match button_type {
+                MenuButtonType::SinglePlayer => {
+                    // 单人练习沿用本地模式的流程，由 SinglePlayerSettings 区分
+                    single_player.enabled = true;
+                    app_state.set(AppState::LocalMultiplayer);
+                }
+                MenuButtonType::BotDifficulty => {
+                    single_player.difficulty = single_player.difficulty.next();
+                    for (mut text, option) in option_text_query.iter_mut() {
+                        if let MenuOptionText::BotDifficulty = option {
+                            text.sections[0].value = format!("电脑难度: {}", single_player.difficulty.label());
+                        }
+                    }
+                }
+                MenuButtonType::MatchFormat => {
+                    let match_format = rules.match_format.next();
+                    game_rules::select_match_format(&mut rules, match_format);
+                    for (mut text, option) in option_text_query.iter_mut() {
+                        if let MenuOptionText::MatchFormat = option {
+                            text.sections[0].value = format!("赛制: {}", match_format.label());
+                        }
+                    }
+                }
+                MenuButtonType::HitResolution => {
+                    let hit_resolution = rules.hit_resolution.next();
+                    game_rules::select_hit_resolution(&mut rules, hit_resolution);
+                    for (mut text, option) in option_text_query.iter_mut() {
+                        if let MenuOptionText::HitResolution = option {
+                            text.sections[0].value = format!("命中判定: {}", hit_resolution.label());
+                        }
+                    }
+                }
+                MenuButtonType::Map => {
+                    arena::select_next_map(&mut map_choice, &mut current_arena);
+                    for (mut text, option) in option_text_query.iter_mut() {
+                        if let MenuOptionText::Map = option {
+                            text.sections[0].value = format!("地图: {}", map_choice.label());
+                        }
+                    }
+                }
+                MenuButtonType::MapEditor => {
+                    app_state.set(AppState::MapEditor);
+                }
                 MenuButtonType::LocalMultiplayer => {
+                    single_player.enabled = false;
                     app_state.set(AppState::LocalMultiplayer);
                 }
                 MenuButtonType::NetworkMatch => {
@@ -2,92 +2,16 @@ use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use bevy::ecs::system::ParamSet;
 use std::net::{UdpSocket, SocketAddr, Ipv4Addr};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::io;
-use serde::{Serialize, Deserialize};
-use rand::Rng;
 use crate::{AppState, RoomInfo};
-use crate::PlayerId;
-use crate::PlayerRole;
+use crate::capture::CaptureDirection;
 
-/// 网络消息类型
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum NetworkMessage {
-    // 房间发现
-    RoomDiscoveryRequest,  // 请求发现房间
-    RoomDiscoveryResponse { room_id: String, player_name: String },  // 响应房间发现
-    
-    // 连接
-    JoinRequest { room_id: String },  // 请求加入房间
-    JoinAccept { player_id: PlayerId },  // 接受加入
-    JoinReject,  // 拒绝加入
-    
-    // 游戏状态同步
-    GameState { 
-        player_positions: Vec<(PlayerId, [f32; 3])>,  // Vec3 序列化为 [f32; 3]
-        player_roles: Vec<(PlayerId, PlayerRole)>,
-        health: Vec<(PlayerId, f32)>,
-    },
-    
-    // 玩家输入
-    PlayerInput {
-        player_id: PlayerId,
-        movement: Option<[f32; 2]>,  // Vec2 序列化为 [f32; 2]
-        action: Option<String>,
-        crosshair_pos: Option<[f32; 2]>,  // Vec2 序列化为 [f32; 2]
-    },
-    
-    // 游戏事件
-    PlayerHit { player_id: PlayerId, damage: f32 },
-    GameOver { winner: PlayerId },
-    StartGame,
-    
-    // 回合信息同步
-    RoundInfoSync {
-        current_attacker: PlayerId,
-        bullets_left: u32,
-        round_timer_remaining: f32,
-        p1_health: f32,
-        p2_health: f32,
-        bullets_fired: u32,
-        bullets_hit: u32,
-    },
-    
-    // 角色切换
-    SwitchRoles {
-        new_attacker: PlayerId,
-    },
-    
-    // 准星位置同步（进攻方发送给防守方）
-    CrosshairPosition { position: [f32; 2] },
-    
-    // 防守方位置和动作同步（防守方发送给进攻方）
-    DefenderState {
-        position: [f32; 3],
-        dodge_action: String, // "None", "Crouch", "SideLeft", "SideRight"
-    },
-    
-    // 子弹同步（发射子弹时发送）
-    BulletSpawn {
-        bullet_id: u64,  // 子弹同步ID
-        owner: PlayerId,  // 发射者
-        start_pos: [f32; 2],  // 起始位置
-        target_pos: [f32; 2],  // 目标位置
-        velocity: [f32; 2],  // 速度
-    },
-    
-    // 血量更新（被击中时发送）
-    HealthUpdate {
-        player_id: PlayerId,
-        health: f32,
-    },
-    
-    // 再来一局
-    RematchRequest,  // 请求再来一局
-    RematchReady,    // 准备再来一局（双方都点击后）
-}
+// 网络消息类型定义在 protocol.rs（库与游戏共用）
+pub use bevy_sniper_duel::protocol::NetworkMessage;
+use bevy_sniper_duel::protocol::{self, MessageLimits, MAX_DATAGRAM_SIZE};
 
 /// 网络管理器资源
 #[derive(Resource)]
@@ -494,13 +418,17 @@ pub fn create_room(
         }
     }
     
+    // 配置了会合服务器时登记房间，供其他网络的客户端通过房间号打洞或中继
+    let rendezvous_state = crate::rendezvous::SharedRendezvousState::default();
+    crate::rendezvous::start_host_registration(socket_arc.clone(), room_id.clone(), is_running_flag.clone(), rendezvous_state.clone());
+    
     // 启动接收线程
     let message_queue = network_manager.message_queue.clone();
     let room_id_arc = network_manager.room_id.clone();
     let remote_addr_for_thread = network_manager.remote_addr.clone();
     
     thread::spawn(move || {
-        let mut buf = [0u8; 1024];
+        let mut buf = [0u8; MAX_DATAGRAM_SIZE];
         loop {
             if !is_running_flag.load(Ordering::Relaxed) {
                 // 调试输出已禁用: println!("[主机] 接收线程收到停止信号，退出");
@@ -518,10 +446,16 @@ pub fn create_room(
             match recv_result {
                     Ok((size, addr)) => {
                         // println!("[主机] 收到来自 {} 的数据，大小: {} 字节", addr, size); // 已禁用：日志太多
+                    // 会合服务器的控制报文和打洞报文不是游戏对端，不保存远程地址
+                    if protocol::is_rendezvous_datagram(&buf[..size]) {
+                        let current_room_id = room_id_arc.lock().unwrap().clone();
+                        crate::rendezvous::handle_datagram(&buf[..size], addr, &socket_arc, &current_room_id, true, &rendezvous_state);
+                        continue;
+                    }
                     // 立即保存远程地址（用于后续通信）
                     *remote_addr_for_thread.lock().unwrap() = Some(addr);
                         
-                        if let Ok(msg) = bincode::deserialize::<NetworkMessage>(&buf[..size]) {
+                        if let Some(msg) = decode_message(&buf[..size], addr) {
                             // println!("[主机] 收到消息: {:?}", msg); // 已禁用：日志太多
                             match msg {
                                 NetworkMessage::RoomDiscoveryRequest => {
@@ -532,9 +466,9 @@ pub fn create_room(
                                         room_id: current_room_id.clone(),
                                         player_name: "Host".to_string(),
                                     };
-                                    if let Ok(data) = bincode::serialize(&response) {
+                                    if let Ok(data) = protocol::encode_message(&response) {
                                         if let Ok(socket_guard) = socket_arc.lock() {
-                                            match socket_guard.send_to(&data, addr) {
+                                            match send_encoded(&socket_guard, &data, &response, addr) {
                                                 Ok(_) => {}, // 调试输出已禁用: println!("[主机] 已发送房间发现响应到 {}，发送了 {} 字节", addr, sent),
                                                 Err(e) => eprintln!("[主机] 发送房间发现响应失败: {}", e),
                                             }
@@ -556,9 +490,9 @@ pub fn create_room(
                                         let accept = NetworkMessage::JoinAccept {
                                             player_id: crate::PlayerId::Player2,
                                         };
-                                        if let Ok(data) = bincode::serialize(&accept) {
+                                        if let Ok(data) = protocol::encode_message(&accept) {
                                             if let Ok(socket_guard) = socket_arc.lock() {
-                                                match socket_guard.send_to(&data, addr) {
+                                                match send_encoded(&socket_guard, &data, &accept, addr) {
                                                     Ok(_) => {}, // 调试输出已禁用: println!("[主机] 已发送加入接受消息到 {}，发送了 {} 字节", addr, sent),
                                                     Err(e) => eprintln!("[主机] 发送加入接受消息失败: {}", e),
                                                 }
@@ -586,7 +520,7 @@ pub fn create_room(
                                 }
                             }
                         } else {
-                            eprintln!("[主机] 反序列化消息失败，数据大小: {}", size);
+                            // 丢弃原因已由 decode_message 输出
                             // 打印原始数据的前几个字节用于调试
                             let preview = &buf[..size.min(20)];
                             // 调试输出已禁用: println!("[主机] 原始数据预览: {:?}", preview);
@@ -623,8 +557,13 @@ pub fn search_room(
     if let Some(ip_address) = manual_ip {
         // 调试输出已禁用: println!("[客户端] 使用手动输入的IP地址: {}", ip_address);
         
+        // 配置了会合服务器时，纯数字输入视为房间号，目标地址在打洞或中继之后才能确定
+        let rendezvous_room = crate::rendezvous::room_id_from_input(&ip_address);
+        
         // 解析IP地址和端口
-        let target_addr = if ip_address.contains(':') {
+        let target_addr = if let Some(server) = rendezvous_room.as_ref().and(crate::rendezvous::server_addr()) {
+            server
+        } else if ip_address.contains(':') {
             match ip_address.parse::<SocketAddr>() {
                 Ok(addr) => addr,
                 Err(e) => {
@@ -673,20 +612,35 @@ pub fn search_room(
         network_manager.is_running.store(true, Ordering::Relaxed);
         network_manager.socket = Some(socket_arc.clone());
         
-        // 立即保存远程地址
-        *network_manager.remote_addr.lock().unwrap() = Some(target_addr);
+        // 立即保存远程地址（通过房间号连接时，收到主机的报文后再保存）
+        if let Some(room_id) = rendezvous_room.as_ref() {
+            *network_manager.room_id.lock().unwrap() = room_id.clone();
+        } else {
+            *network_manager.remote_addr.lock().unwrap() = Some(target_addr);
+        }
         
         let message_queue = network_manager.message_queue.clone();
         let room_id_arc = network_manager.room_id.clone();
         let remote_addr_arc = network_manager.remote_addr.clone();
         let room_found = Arc::new(Mutex::new(false));
         let is_running_flag = network_manager.is_running.clone();
+        let rendezvous_state = crate::rendezvous::SharedRendezvousState::default();
         
         // 定期发送房间发现请求到指定IP
         let socket_for_send = socket_arc.clone();
-        let target_addr_clone = target_addr;
         let send_running_flag = is_running_flag.clone();
+        let send_rendezvous_state = rendezvous_state.clone();
         thread::spawn(move || {
+            // 通过房间号连接：先经会合服务器打洞，失败则改用中继（之后的报文发给服务器）
+            let target_addr_clone = match rendezvous_room {
+                Some(room_id) => {
+                    match crate::rendezvous::resolve_host(&socket_for_send, &room_id, &send_running_flag, &send_rendezvous_state) {
+                        Some(addr) => addr,
+                        None => return,
+                    }
+                }
+                None => target_addr,
+            };
             let discovery_msg = NetworkMessage::RoomDiscoveryRequest;
             let mut request_count = 0u32;
             loop {
@@ -695,10 +649,10 @@ pub fn search_room(
                     break;
                 }
                 request_count += 1;
-                if let Ok(data) = bincode::serialize(&discovery_msg) {
+                if let Ok(data) = protocol::encode_message(&discovery_msg) {
                     if let Ok(socket_guard) = socket_for_send.lock() {
                         // 直接发送到指定IP地址
-                        match socket_guard.send_to(&data, target_addr_clone) {
+                        match send_encoded(&socket_guard, &data, &discovery_msg, target_addr_clone) {
                             Ok(_sent) => {
                                 if request_count % 10 == 0 {
                                     // 调试输出已禁用: println!("[客户端] 已发送房间发现请求到 {} (第{}次)", target_addr_clone, request_count);
@@ -719,7 +673,7 @@ pub fn search_room(
         // 接收线程（与下面的代码相同）
         let recv_running_flag = is_running_flag.clone();
         thread::spawn(move || {
-            let mut buf = [0u8; 1024];
+            let mut buf = [0u8; MAX_DATAGRAM_SIZE];
             loop {
                 if !recv_running_flag.load(Ordering::Relaxed) {
                     // 调试输出已禁用: println!("[客户端] 接收线程收到停止信号，退出");
@@ -731,10 +685,16 @@ pub fn search_room(
                     match recv_result {
                         Ok((size, addr)) => {
                             // println!("[客户端] 收到来自 {} 的数据，大小: {} 字节", addr, size); // 已禁用：日志太多
+                            // 会合服务器的控制报文和打洞报文不是游戏对端，不保存远程地址
+                            if protocol::is_rendezvous_datagram(&buf[..size]) {
+                                let current_room_id = room_id_arc.lock().unwrap().clone();
+                                crate::rendezvous::handle_datagram(&buf[..size], addr, &socket_arc, &current_room_id, false, &rendezvous_state);
+                                continue;
+                            }
                             // 保存远程地址
                             *remote_addr_arc.lock().unwrap() = Some(addr);
                             
-                            if let Ok(msg) = bincode::deserialize::<NetworkMessage>(&buf[..size]) {
+                            if let Some(msg) = decode_message(&buf[..size], addr) {
                                 // println!("[客户端] 收到消息: {:?}", msg); // 已禁用：日志太多
                                 match msg {
                                     NetworkMessage::RoomDiscoveryResponse { room_id, .. } => {
@@ -748,9 +708,9 @@ pub fn search_room(
                                             let join_msg = NetworkMessage::JoinRequest {
                                                 room_id: room_id.clone(),
                                             };
-                                            if let Ok(data) = bincode::serialize(&join_msg) {
+                                            if let Ok(data) = protocol::encode_message(&join_msg) {
                                                 if let Ok(socket_guard) = socket_arc.lock() {
-                                                    match socket_guard.send_to(&data, addr) {
+                                                    match send_encoded(&socket_guard, &data, &join_msg, addr) {
                                                         Ok(_) => {}, // 调试输出已禁用: println!("[客户端] 已发送加入请求到 {}，发送了 {} 字节", addr, sent),
                                                         Err(e) => eprintln!("[客户端] 发送加入请求失败: {}", e),
                                                     }
@@ -783,7 +743,7 @@ pub fn search_room(
                                     }
                                 }
                             } else {
-                                eprintln!("[客户端] 反序列化消息失败，数据大小: {}", size);
+                                // 丢弃原因已由 decode_message 输出
                             }
                         }
                         Err(e) => {
@@ -860,15 +820,17 @@ pub fn search_room(
                     break;
                 }
                 request_count += 1;
-                if let Ok(data) = bincode::serialize(&discovery_msg) {
+                if let Ok(data) = protocol::encode_message(&discovery_msg) {
                     if let Ok(socket_guard) = socket_for_send.lock() {
                         // 1. 尝试标准广播地址
-                        let broadcast_addresses = vec![
+                        let broadcast_addresses = [
                             SocketAddr::new(Ipv4Addr::BROADCAST.into(), 12345),
                             SocketAddr::new("255.255.255.255".parse().unwrap(), 12345),
                         ];
                         
-                        for broadcast_addr in &broadcast_addresses {
+                        // 广播和子网扫描会发出大量相同的请求，抓包时只按第一个广播地址记录一次
+                        let _ = send_encoded(&socket_guard, &data, &discovery_msg, broadcast_addresses[0]);
+                        for broadcast_addr in &broadcast_addresses[1..] {
                             let _ = socket_guard.send_to(&data, *broadcast_addr);
                         }
                         
@@ -899,7 +861,7 @@ pub fn search_room(
         // 接收线程
         let recv_running_flag = is_running_flag.clone();
         thread::spawn(move || {
-            let mut buf = [0u8; 1024];
+            let mut buf = [0u8; MAX_DATAGRAM_SIZE];
             loop {
                 if !recv_running_flag.load(Ordering::Relaxed) {
                     // 调试输出已禁用: println!("[客户端] 广播接收线程收到停止信号，退出");
@@ -914,7 +876,7 @@ pub fn search_room(
                             // 保存远程地址
                             *remote_addr_arc.lock().unwrap() = Some(addr);
                             
-                            if let Ok(msg) = bincode::deserialize::<NetworkMessage>(&buf[..size]) {
+                            if let Some(msg) = decode_message(&buf[..size], addr) {
                                 // println!("[客户端] 收到消息: {:?}", msg); // 已禁用：日志太多
                                 match msg {
                                     NetworkMessage::RoomDiscoveryResponse { room_id, .. } => {
@@ -928,9 +890,9 @@ pub fn search_room(
                                             let join_msg = NetworkMessage::JoinRequest {
                                                 room_id: room_id.clone(),
                                             };
-                                            if let Ok(data) = bincode::serialize(&join_msg) {
+                                            if let Ok(data) = protocol::encode_message(&join_msg) {
                                                 if let Ok(socket_guard) = socket_arc.lock() {
-                                                    match socket_guard.send_to(&data, addr) {
+                                                    match send_encoded(&socket_guard, &data, &join_msg, addr) {
                                                         Ok(_) => {}, // 调试输出已禁用: println!("[客户端] 已发送加入请求到 {}，发送了 {} 字节", addr, sent),
                                                         Err(e) => eprintln!("[客户端] 发送加入请求失败: {}", e),
                                                     }
@@ -963,7 +925,7 @@ pub fn search_room(
                                     }
                                 }
                             } else {
-                                eprintln!("[客户端] 反序列化消息失败，数据大小: {}", size);
+                                // 丢弃原因已由 decode_message 输出
                                 // 打印原始数据的前几个字节用于调试
                                 let preview = &buf[..size.min(20)];
                                 // 调试输出已禁用: println!("[客户端] 原始数据预览: {:?}", preview);
@@ -1005,10 +967,10 @@ pub fn search_room(
                     // 调试输出已禁用: println!("[客户端] 回退模式发送线程收到停止信号，退出");
                     break;
                 }
-                if let Ok(data) = bincode::serialize(&discovery_msg) {
-                    let broadcast_addr = SocketAddr::new(Ipv4Addr::BROADCAST.into(), 12345);
+                let broadcast_addr = SocketAddr::new(Ipv4Addr::BROADCAST.into(), 12345);
+                if let Ok(data) = protocol::encode_message(&discovery_msg) {
                     if let Ok(socket_guard) = socket_for_send.lock() {
-                        let _ = socket_guard.send_to(&data, broadcast_addr);
+                        let _ = send_encoded(&socket_guard, &data, &discovery_msg, broadcast_addr);
                     }
                 }
                 thread::sleep(std::time::Duration::from_millis(1000));
@@ -1017,7 +979,7 @@ pub fn search_room(
         
         let recv_running_flag = is_running_flag.clone();
         thread::spawn(move || {
-            let mut buf = [0u8; 1024];
+            let mut buf = [0u8; MAX_DATAGRAM_SIZE];
             loop {
                 if !recv_running_flag.load(Ordering::Relaxed) {
                     // 调试输出已禁用: println!("[客户端] 回退模式接收线程收到停止信号，退出");
@@ -1028,7 +990,7 @@ pub fn search_room(
                         drop(socket_guard);
                     if let Ok((size, addr)) = recv_result {
                         *remote_addr_arc.lock().unwrap() = Some(addr);
-                        if let Ok(msg) = bincode::deserialize::<NetworkMessage>(&buf[..size]) {
+                        if let Some(msg) = decode_message(&buf[..size], addr) {
                             match msg {
                                 NetworkMessage::RoomDiscoveryResponse { room_id, .. } => {
                                     let mut found = room_found.lock().unwrap();
@@ -1074,6 +1036,8 @@ pub fn handle_network_messages(
     network_manager: Res<NetworkManager>,
     mut room_info: ResMut<RoomInfo>,
     mut app_state: ResMut<NextState<AppState>>,
+    (mut game_rules, mut arena): (ResMut<GameRules>, ResMut<crate::arena::Arena>),
+    mut tuning_panel: ResMut<crate::tuning::TuningPanel>,
 ) {
     // 处理接收到的消息
     if let Ok(mut queue) = network_manager.message_queue.lock() {
@@ -1097,9 +1061,19 @@ pub fn handle_network_messages(
                         // 调试输出已禁用: println!("[房主] 客户端已加入房间");
                     }
                 }
-                NetworkMessage::StartGame => {
+                NetworkMessage::StartGame { rules, map } => {
                     // 调试输出已禁用: println!("[客户端] 收到开始游戏消息，切换到Playing状态");
                     if !network_manager.is_host {
+                        // 本局按主机的规则和地图进行（StartGame 会重复发送，只在不同时替换）
+                        if *game_rules != rules {
+                            eprintln!("[客户端] 使用主机的对局规则: {:?}", rules);
+                            *game_rules = rules;
+                        }
+                        let hosted = crate::arena::Arena::from_source(map);
+                        if *arena != hosted {
+                            eprintln!("[客户端] 使用主机的地图: {}", hosted.map.name);
+                            *arena = hosted;
+                        }
                         room_info.is_connected = true;
                         let remote_addr = network_manager.remote_addr.lock().unwrap();
                         // 调试输出已禁用: println!("[客户端] remote_addr: {:?}, socket: {:?}", *remote_addr, if network_manager.socket.is_some() { "已设置" } else { "未设置" });
@@ -1109,6 +1083,14 @@ pub fn handle_network_messages(
                         // 调试输出已禁用: println!("[房主] 收到StartGame消息（可能是重复消息）");
                     }
                 }
+                NetworkMessage::RulesRequest if network_manager.is_host => {
+                    // 客户端漏收了 RulesUpdate，重发当前生效的规则
+                    send_network_message(&network_manager, NetworkMessage::RulesUpdate { rules: game_rules.clone() });
+                }
+                NetworkMessage::RulesUpdate { rules } if !network_manager.is_host => {
+                    // 主机换边前发来调参面板的修改（会重复发送）：与主机一样排到下一次回合切换时生效
+                    tuning_panel.queued = Some(rules);
+                }
                 NetworkMessage::SwitchRoles { .. } => {
                     // SwitchRoles消息由handle_game_state_system处理
                     // 将消息放回队列，让handle_game_state_system处理
@@ -1119,6 +1101,9 @@ pub fn handle_network_messages(
                 NetworkMessage::GameState { .. } |
                 NetworkMessage::RoundInfoSync { .. } |
                 NetworkMessage::BulletSpawn { .. } |
+                NetworkMessage::WeaponSelect { .. } |
+                NetworkMessage::DefenderUtility { .. } |
+                NetworkMessage::WallRepair { .. } |
                 NetworkMessage::HealthUpdate { .. } |
                 NetworkMessage::GameOver { .. } => {
                     // 这些消息由专门的系统处理，放回队列
@@ -1135,6 +1120,58 @@ pub fn handle_network_messages(
     }
 }
 
+/// 当前的消息校验范围：启动时和规则或地图变化时由 publish_message_limits 计算（唯一的写入方），
+/// 接收线程每个数据报只复制一份
+static MESSAGE_LIMITS: RwLock<Option<MessageLimits>> = RwLock::new(None);
+
+/// 当前生效的消息校验范围（启动前为 None）
+pub fn message_limits() -> Option<MessageLimits> {
+    match MESSAGE_LIMITS.read() {
+        Ok(limits) => *limits,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+/// 按规则和地图资源计算消息校验范围（启动时运行一次，之后只在资源变化时重新计算）
+pub fn publish_message_limits(rules: Res<GameRules>, arena: Res<crate::arena::Arena>) {
+    if !rules.is_changed() && !arena.is_changed() {
+        return;
+    }
+    if let Ok(mut active) = MESSAGE_LIMITS.write() {
+        *active = Some(protocol::limits_for(&rules, &arena.map));
+    }
+}
+
+/// 解码并校验收到的数据报（开启抓包时同时记录）
+/// 超长、格式错误或取值不合法的数据报一律丢弃
+pub fn decode_message(data: &[u8], peer: SocketAddr) -> Option<NetworkMessage> {
+    let msg = match protocol::decode_datagram(data) {
+        Ok(msg) => msg,
+        Err(e) => {
+            eprintln!("[网络] 丢弃来自 {} 的无法解码的数据报（{} 字节）: {}", peer, data.len(), e);
+            crate::capture::record_undecodable(peer, data, &e.to_string());
+            return None;
+        }
+    };
+    crate::capture::record(CaptureDirection::Received, peer, &msg);
+    let Some(limits) = message_limits() else {
+        eprintln!("[网络] 消息校验范围尚未计算，丢弃来自 {} 的消息", peer);
+        return None;
+    };
+    if let Err(e) = protocol::validate_message(&msg, &limits) {
+        eprintln!("[网络] 丢弃来自 {} 的非法消息: {}", peer, e);
+        return None;
+    }
+    Some(msg)
+}
+
+/// 发送编码好的消息，发送成功后才记入抓包（发送失败的报文对方不可能收到，记下来会让 diff 误判为丢包）
+fn send_encoded(socket: &UdpSocket, data: &[u8], message: &NetworkMessage, peer: SocketAddr) -> std::io::Result<usize> {
+    let sent = socket.send_to(data, peer)?;
+    crate::capture::record(CaptureDirection::Sent, peer, message);
+    Ok(sent)
+}
+
 /// 发送网络消息
 pub fn send_network_message(
     network_manager: &NetworkManager,
@@ -1143,9 +1180,9 @@ pub fn send_network_message(
     if let Some(socket_arc) = &network_manager.socket {
         if let Ok(remote_addr_guard) = network_manager.remote_addr.lock() {
             if let Some(remote_addr) = *remote_addr_guard {
-                if let Ok(data) = bincode::serialize(&message) {
+                if let Ok(data) = protocol::encode_message(&message) {
                     if let Ok(socket_guard) = socket_arc.lock() {
-                        match socket_guard.send_to(&data, remote_addr) {
+                        match send_encoded(&socket_guard, &data, &message, remote_addr) {
                             Ok(_sent) => {
                                 // 调试：每60帧打印一次（约1秒）
                                 // println!("[网络] 发送消息成功: {:?} -> {} ({} 字节)", std::any::type_name_of_val(&message), remote_addr, _sent);
@@ -1190,7 +1227,9 @@ pub fn cleanup_network(
 
 // ========== 游戏状态同步系统 ==========
 
-use crate::gameplay::{Health, RoundInfo, CursorPosition, CrosshairOffset, DodgeAction};
+use crate::gameplay::{Health, RoundInfo, CursorPosition, CrosshairOffset, DodgeAction, ActionCooldown};
+use crate::anti_cheat::AntiCheatMonitor;
+use crate::game_rules::GameRules;
 use crate::ViewConfig;
 
 /// 主机：发送游戏状态（位置、血量、角色等）
@@ -1200,6 +1239,7 @@ pub fn sync_game_state_system(
     round_info: Res<RoundInfo>,
     time: Res<Time>,
     mut sync_timer: Local<f32>,
+    rules: Res<GameRules>,
 ) {
     // 只有主机才发送游戏状态
     if !network_manager.is_host {
@@ -1240,6 +1280,7 @@ pub fn sync_game_state_system(
     send_network_message(&*network_manager, game_state);
     
     // 发送回合信息
+    let rules_hash = rules.fingerprint();
     let round_info_sync = NetworkMessage::RoundInfoSync {
         current_attacker: round_info.current_attacker,
         bullets_left: round_info.bullets_left as u32,
@@ -1248,6 +1289,9 @@ pub fn sync_game_state_system(
         p2_health: round_info.p2_health,
         bullets_fired: round_info.bullets_fired_this_round as u32,
         bullets_hit: round_info.bullets_hit_defender as u32,
+        score: round_info.score,
+        wind: round_info.wind.to_array(),
+        rules_hash,
     };
     send_network_message(&*network_manager, round_info_sync);
 }
@@ -1258,6 +1302,7 @@ pub fn force_sync_game_state(
     network_manager: &NetworkManager,
     player_data: &[(crate::PlayerId, [f32; 3], f32, crate::PlayerRole)], // (player_id, position, health, role)
     round_info: &RoundInfo,
+    rules_hash: u64,
 ) {
     // 只有主机才发送游戏状态
     if !network_manager.is_host {
@@ -1297,6 +1342,9 @@ pub fn force_sync_game_state(
         p2_health: round_info.p2_health,
         bullets_fired: round_info.bullets_fired_this_round as u32,
         bullets_hit: round_info.bullets_hit_defender as u32,
+        score: round_info.score,
+        wind: round_info.wind.to_array(),
+        rules_hash,
     };
     send_network_message(network_manager, round_info_sync);
     
@@ -1312,6 +1360,7 @@ pub fn handle_game_state_system(
     mut view_config: ResMut<ViewConfig>,
     mut camera_switch_writer: EventWriter<crate::gameplay::CameraSwitchEvent>,
     all_cameras_query: Query<Entity, With<Camera2d>>, // 用于移除组件
+    (arena, mut host_rules): (Res<crate::arena::Arena>, ResMut<crate::tuning::HostRulesHash>),
 ) {
     // 只有客户端才处理游戏状态
     if network_manager.is_host {
@@ -1369,10 +1418,10 @@ pub fn handle_game_state_system(
                                     // 角色切换时，更新位置
                                     match role {
                                         crate::PlayerRole::Attacker => {
-                                            transform.translation = crate::ATTACKER_START_POS;
+                                            transform.translation = arena.attacker_spawn();
                                         }
                                         crate::PlayerRole::Defender => {
-                                            transform.translation = crate::DEFENDER_START_POS;
+                                            transform.translation = arena.defender_spawn();
                                         }
                                     }
                                     // 更新视图配置：根据本地玩家（Player2）的角色来更新视图
@@ -1415,6 +1464,9 @@ pub fn handle_game_state_system(
                     p2_health,
                     bullets_fired,
                     bullets_hit,
+                    score,
+                    wind,
+                    rules_hash,
                 } => {
                     round_info.current_attacker = current_attacker;
                     round_info.bullets_left = bullets_left as i32;
@@ -1422,6 +1474,11 @@ pub fn handle_game_state_system(
                     round_info.p2_health = p2_health;
                     round_info.bullets_fired_this_round = bullets_fired as i32;
                     round_info.bullets_hit_defender = bullets_hit as i32;
+                    round_info.score = score;
+                    round_info.wind = Vec2::from_array(wind);
+                    if host_rules.0 != Some(rules_hash) {
+                        host_rules.0 = Some(rules_hash);
+                    }
                 }
                 NetworkMessage::SwitchRoles { new_attacker } => {
                     // 调试输出已禁用: println!("[客户端] 收到角色切换消息，新的进攻方: {:?}", new_attacker);
@@ -1546,8 +1603,9 @@ pub fn sync_player_input_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     _cursor_pos: Res<CursorPosition>,
     _crosshair_offset: Res<CrosshairOffset>,
-    player_query: Query<(&crate::PlayerId, &Transform, &DodgeAction, &crate::PlayerRole), (With<crate::PlayerId>, With<crate::PlayerRole>)>,
+    player_query: Query<(&crate::PlayerId, &Transform, &DodgeAction, &crate::PlayerRole, &crate::stamina::Stamina)>,
     mut input_timer: Local<f32>,
+    mut last_sent_action: Local<DodgeAction>,
     time: Res<Time>,
     view_config: Res<ViewConfig>,
     room_info: Res<crate::RoomInfo>,
@@ -1598,40 +1656,34 @@ pub fn sync_player_input_system(
         }
     }
     
-    // 收集防守方的动作（K键触发技能）
-    let action = if keyboard_input.just_pressed(KeyCode::KeyK) {
-        // K键随机触发下蹲或侧躲
-        if rand::thread_rng().gen_bool(0.5) {
-            Some("crouch".to_string())
-        } else {
-            Some("dodge".to_string())
-        }
-    } else {
-        None
-    };
-    
     // 获取防守方的位置和动作状态
     let mut defender_pos = None;
-    let mut defender_action = "None".to_string();
-    for (player_id, transform, dodge_action, role) in player_query.iter() {
+    let mut defender_action = DodgeAction::None;
+    let mut defender_stamina = 0.0;
+    for (player_id, transform, dodge_action, role, stamina) in player_query.iter() {
         if *player_id == local_player_id && matches!(role, crate::PlayerRole::Defender) {
             let pos = transform.translation;
             defender_pos = Some([pos.x, pos.y, pos.z]);
-            defender_action = match dodge_action {
-                DodgeAction::None => "None".to_string(),
-                DodgeAction::Crouch => "Crouch".to_string(),
-                DodgeAction::SideLeft => "SideLeft".to_string(),
-                DodgeAction::SideRight => "SideRight".to_string(),
-            };
+            defender_action = *dodge_action;
+            defender_stamina = stamina.current;
             break;
         }
     }
     
-    // 发送防守方状态
+    // 收集防守方的动作：动作由 defender_action_system 决定，这里只在动作开始时上报一次
+    let action = if defender_action != *last_sent_action && defender_action != DodgeAction::None {
+        Some(defender_action)
+    } else {
+        None
+    };
+    *last_sent_action = defender_action;
+    
+    // 发送防守方状态（体力随位置一起按固定频率发送，变化时不额外发消息）
     if let Some(pos) = defender_pos {
         let defender_state = NetworkMessage::DefenderState {
             position: pos,
-            dodge_action: defender_action.clone(),
+            dodge_action: defender_action,
+            stamina: defender_stamina,
         };
         // 检查网络状态
         let remote_addr_ok = network_manager.remote_addr.lock().unwrap().is_some();
@@ -1719,13 +1771,15 @@ pub fn sync_crosshair_position_system(
 /// 基于视图配置（角色），而不是基于玩家身份（房主/客户端）
 pub fn handle_player_input_system(
     network_manager: Res<NetworkManager>,
-    mut player_query: Query<(Entity, &crate::PlayerId, &mut Transform, &mut DodgeAction, &mut crate::PlayerRole), (With<crate::PlayerId>, With<crate::PlayerRole>)>,
-    mut _round_info: ResMut<RoundInfo>,
+    mut player_query: Query<(Entity, &crate::PlayerId, &mut Transform, &mut DodgeAction, &mut crate::PlayerRole, &mut ActionCooldown, &mut crate::stamina::Stamina)>,
+    round_info: Res<RoundInfo>,
     cursor_pos: ResMut<CursorPosition>,
     room_info: Res<crate::RoomInfo>,
     view_config: Res<ViewConfig>,
     time: Res<Time>,
     app_state: Res<State<crate::AppState>>,
+    mut warned_unknown_action: Local<bool>,
+    (mut anti_cheat, rules): (ResMut<AntiCheatMonitor>, Res<GameRules>),
 ) {
     // 只有网络模式才处理
     if !room_info.is_connected {
@@ -1750,27 +1804,47 @@ pub fn handle_player_input_system(
         crate::PlayerId::Player2
     };
     
+    // 反作弊：进攻方是防守方输入的权威方，检查移动速度和动作冷却
+    let now = time.elapsed_seconds_f64();
+    anti_cheat.sync_round(round_info.current_attacker, now);
+    let peer = network_manager.remote_addr.lock().ok().and_then(|addr| *addr);
+    
     // 处理接收到的消息（只处理DefenderState，CrosshairPosition由handle_crosshair_position_system处理）
     if let Ok(mut queue) = network_manager.message_queue.lock() {
         let mut messages_to_keep = Vec::new();
         for msg in queue.drain(..) {
             match msg {
-                NetworkMessage::DefenderState { position, dodge_action } => {
+                NetworkMessage::PlayerInput { .. } => {
+                    // 动作以 DefenderState 为准（在那里检查冷却），PlayerInput 只需消费掉，避免在队列中堆积
+                }
+                NetworkMessage::DefenderState { position, dodge_action, stamina } => {
                     // 更新防守方位置和动作
                     // 防守方状态总是来自对方玩家（不是本地玩家）
                     // 初始：客户端（Player2，防守方）发送给主机，主机更新Player2
                     // 切换后：房主（Player1，防守方）发送给客户端，客户端更新Player1
                     let mut updated = false;
-                    for (_entity, pid, mut transform, mut dodge_action_comp, role) in player_query.iter_mut() {
+                    for (_entity, pid, mut transform, mut dodge_action_comp, role, mut cooldown, mut stamina_comp) in player_query.iter_mut() {
                         // 更新对方玩家的防守方位置（不是本地玩家）
                         if *pid != local_player_id && matches!(*role, crate::PlayerRole::Defender) {
-                            transform.translation = Vec3::new(position[0], position[1], position[2]);
-                            *dodge_action_comp = match dodge_action.as_str() {
-                                "Crouch" => DodgeAction::Crouch,
-                                "SideLeft" => DodgeAction::SideLeft,
-                                "SideRight" => DodgeAction::SideRight,
-                                _ => DodgeAction::None,
-                            };
+                            // 先检查动作（侧躲会增加本次允许的位移），再截断超速的位移，最后检查体力
+                            // （侧躲和冲刺都按这条消息之前的体力判断）
+                            let dodge_accepted = dodge_action == DodgeAction::Unknown
+                                || anti_cheat.check_dodge(&rules, now, dodge_action, &mut cooldown, peer);
+                            let reported = Vec3::new(position[0], position[1], position[2]);
+                            let previous = transform.translation;
+                            transform.translation = anti_cheat.check_defender_move(&rules, now, previous, reported, peer);
+                            let moved = (transform.translation - previous).truncate().length();
+                            stamina_comp.current = anti_cheat.check_stamina(&rules, now, stamina, moved, peer);
+                            if dodge_action == DodgeAction::Unknown {
+                                // 对方版本更新，发来了本版本不认识的动作：保持当前动作，不当作"无动作"
+                                if !*warned_unknown_action {
+                                    *warned_unknown_action = true;
+                                    eprintln!("[进攻方] 收到未知的防守方动作，对方可能使用了更新的版本");
+                                }
+                            } else if dodge_accepted {
+                                // 冷却中被拒绝的躲避保持当前动作
+                                *dodge_action_comp = dodge_action;
+                            }
                             updated = true;
                             // println!("[进攻方] 收到DefenderState: 更新玩家 {:?} 位置=({:.1}, {:.1}, {:.1}), 动作={}", 
                             //          pid, position[0], position[1], position[2], dodge_action); // 已禁用：日志太多
@@ -1800,6 +1874,8 @@ pub fn handle_crosshair_position_system(
     room_info: Res<crate::RoomInfo>,
     view_config: Res<ViewConfig>,
     time: Res<Time>,
+    round_info: Res<RoundInfo>,
+    (mut anti_cheat, rules, arena): (ResMut<AntiCheatMonitor>, Res<GameRules>, Res<crate::arena::Arena>),
 ) {
     // 只有网络模式才处理
     if !room_info.is_connected {
@@ -1812,13 +1888,21 @@ pub fn handle_crosshair_position_system(
         return;
     }
     
+    // 反作弊：防守方是准星位置的权威方，检查准星移动速度
+    let now = time.elapsed_seconds_f64();
+    anti_cheat.sync_round(round_info.current_attacker, now);
+    let peer = network_manager.remote_addr.lock().ok().and_then(|addr| *addr);
+    let aim_origin = arena.aim_origin();
+    
     // 处理接收到的消息（只处理CrosshairPosition）
     if let Ok(mut queue) = network_manager.message_queue.lock() {
         let mut messages_to_keep = Vec::new();
         for msg in queue.drain(..) {
             match msg {
                 NetworkMessage::CrosshairPosition { position } => {
-                    cursor_pos.0 = Vec2::new(position[0], position[1]);
+                    // 越界太多的准星在解码时已丢弃，这里再按 MAX_AIM_OFFSET 精确截断
+                    let offset = (Vec2::new(position[0], position[1]) - aim_origin).clamp_length_max(crate::MAX_AIM_OFFSET);
+                    cursor_pos.0 = anti_cheat.check_crosshair_move(&rules, now, cursor_pos.0, aim_origin + offset, peer);
                     // println!("[防守方] 收到CrosshairPosition: ({:.1}, {:.1})", position[0], position[1]); // 已禁用：日志太多
                 }
                 _ => {
@@ -1839,13 +1923,30 @@ pub fn handle_bullet_spawn_system(
     network_manager: Res<NetworkManager>,
     room_info: Res<crate::RoomInfo>,
     mut round_info: ResMut<crate::gameplay::RoundInfo>,
-    bullet_query: Query<&crate::gameplay::BulletSyncId, With<crate::gameplay::Bullet>>,
+    (bullet_query, wall_query, cover_query, mut utility_query): (
+        Query<&crate::gameplay::BulletSyncId, With<crate::gameplay::Bullet>>,
+        Query<(&Transform, &crate::gameplay::WallSegment, &crate::gameplay::Collider)>,
+        crate::gameplay::CoverQuery,
+        Query<&mut crate::utility::UtilityEffect>,
+    ),
+    time: Res<Time>,
+    (mut anti_cheat, rules, mut loadout): (ResMut<AntiCheatMonitor>, Res<GameRules>, ResMut<crate::weapons::Loadout>),
 ) {
     // 只有网络模式才处理
     if !room_info.is_connected {
         return;
     }
     
+    // 反作弊：检查射击者身份、剩余子弹、射速和中途换枪
+    let now = time.elapsed_seconds_f64();
+    anti_cheat.sync_round(round_info.current_attacker, now);
+    let peer = network_manager.remote_addr.lock().ok().and_then(|addr| *addr);
+    let local_player_id = if network_manager.is_host {
+        crate::PlayerId::Player1
+    } else {
+        crate::PlayerId::Player2
+    };
+    
     // 处理接收到的消息
     if let Ok(mut queue) = network_manager.message_queue.lock() {
         let mut messages_to_keep = Vec::new();
@@ -1857,7 +1958,22 @@ pub fn handle_bullet_spawn_system(
                     start_pos,
                     target_pos,
                     velocity,
+                    weapon,
+                    turn,
                 } => {
+                    // 落点按本机的风计算（风由主机生成，不采用开火方的），先确认本机的风属于开火时的进攻回合
+                    let local_turn = round_info.score.turns_played;
+                    if turn != local_turn {
+                        if !network_manager.is_host && turn > local_turn {
+                            // 客户端还没收到这个进攻回合的 RoundInfoSync（风还是上一回合的），同步后再处理
+                            messages_to_keep.push(msg);
+                        } else {
+                            // 主机只接受当前进攻回合的射击（过时或伪造的回合号直接丢弃）
+                            eprintln!("[网络] 丢弃第 {} 个进攻回合的子弹 {}（本机在第 {} 个进攻回合）", turn, bullet_id, local_turn);
+                        }
+                        continue;
+                    }
+
                     // 检查是否已经存在相同ID的子弹（避免重复创建）
                     let mut bullet_exists = false;
                     for sync_id in bullet_query.iter() {
@@ -1867,11 +1983,23 @@ pub fn handle_bullet_spawn_system(
                         }
                     }
                     
-                    if !bullet_exists {
-                        // 创建子弹（使用网络消息中的bullet_id）
+                    if bullet_exists {
+                        // 调试输出已禁用: println!("[网络] 警告：收到BulletSpawn但子弹已存在: bullet_id={}", bullet_id);
+                    } else if anti_cheat.check_shot(&crate::weapons::Weapon::of(weapon, &rules), now, owner, round_info.current_attacker, local_player_id, peer) {
+                        // WeaponSelect 丢失时以第一枪所用的武器为准
+                        crate::weapons::apply_remote_weapon(&mut loadout, &mut round_info, &rules, owner, weapon);
+                        
+                        // 创建子弹（使用网络消息中的bullet_id）；不合法的射击直接丢弃，不创建子弹也不扣子弹
                         let attacker_pos = Vec2::new(start_pos[0], start_pos[1]);
-                        let target = Vec2::new(target_pos[0], target_pos[1]);
                         let vel = Vec2::new(velocity[0], velocity[1]);
+                        // 弹道规则：按同样的规则和本进攻回合的风算出落点，破墙位置与开火方一致
+                        let target = crate::ballistics::landing_point(
+                            attacker_pos,
+                            Vec2::new(target_pos[0], target_pos[1]),
+                            vel.length(),
+                            &rules,
+                            round_info.wind,
+                        );
                         
                         crate::gameplay::spawn_bullet_with_id(
                             &mut commands,
@@ -1880,8 +2008,22 @@ pub fn handle_bullet_spawn_system(
                             target,
                             vel,
                             bullet_id,
+                            weapon,
                         );
                         
+                        // 护盾和假人按落点判定：接收方是防守方，按自己视角的墙判断这一发能否打到墙后
+                        let fired_weapon = crate::weapons::Weapon::of(weapon, &rules);
+                        let view_layer = crate::gameplay::ViewLayer::DefenderView;
+                        if crate::gameplay::shot_damage_scale(target, view_layer, &fired_weapon, &wall_query, &cover_query).is_some() {
+                            crate::utility::absorb_shot(target, &mut utility_query);
+                        }
+                        
+                        // 弹道判定：显示对方这一枪的落点和剩余飞行时间（命中由开火的一方结算）
+                        if rules.hit_resolution == crate::game_rules::HitResolution::Projectile {
+                            let seconds = crate::projectile::travel_seconds(attacker_pos, target, vel.length());
+                            crate::projectile::spawn_shot_markers(&mut commands, target, seconds);
+                        }
+                        
                         // 调试输出已禁用: println!("[网络] 收到BulletSpawn: bullet_id={}, owner={:?}, start_pos=({:.1}, {:.1}), target_pos=({:.1}, {:.1})", bullet_id, owner, start_pos[0], start_pos[1], target_pos[0], target_pos[1]);
 
                         // 更新回合信息（用于主机/客户端同步子弹数量）
@@ -1891,8 +2033,12 @@ pub fn handle_bullet_spawn_system(
                             }
                             round_info.bullets_fired_this_round += 1;
                         }
-                    } else {
-                        // 调试输出已禁用: println!("[网络] 警告：收到BulletSpawn但子弹已存在: bullet_id={}", bullet_id);
+                    }
+                }
+                NetworkMessage::WeaponSelect { player_id, weapon } => {
+                    // 对方进攻方开火前换枪（自己的选择以本地为准）
+                    if player_id != local_player_id {
+                        crate::weapons::apply_remote_weapon(&mut loadout, &mut round_info, &rules, player_id, weapon);
                     }
                 }
                 _ => {
@@ -1931,6 +2077,10 @@ pub fn handle_health_update_system(
                     // 更新玩家血量（包括本地玩家和对方玩家）
                     for (pid, mut health_comp) in player_query.iter_mut() {
                         if *pid == player_id {
+                            // 血量下降的部分记为对方造成的伤害（客户端的比分随后会被主机的 RoundInfoSync 覆盖）
+                            if health < health_comp.0 {
+                                round_info.score.add_damage(crate::scoring::opponent(player_id), health_comp.0 - health);
+                            }
                             health_comp.0 = health;
                             // 同时更新回合信息中的血量
                             match player_id {
@@ -0,0 +1,197 @@
+// 弹道判定：子弹飞到瞄准点时才结算命中（规则 hit_resolution: Projectile）
+//
+// 开火时不结算伤害，而是记下瞄准点和武器，按子弹速度算出飞行时间；
+// 飞行时间到了再用防守方当时的位置判定命中，防守方在开火后仍然可以躲开。
+// 和准星判定一样由开火的一方结算，联机时通过 HealthUpdate 同步血量。
+// 穿墙的伤害衰减按开火时落点处的砖块计算（子弹出膛后砖块就会被打碎）。
+// 瞄准点上会显示一个逐渐收缩的落点标记（双方视角都能看到），
+// 进攻方的 HUD 上显示子弹飞到当前准星位置需要的时间。
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::game_rules::{GameRules, HitResolution};
+use crate::gameplay::{apply_hit, hitbox_at, CursorPosition, DodgeAction, Health, PlayerHitEvent, RoundInfo};
+use crate::weapons::{Loadout, Weapon, WeaponEffects};
+use crate::{FontResource, PlayerId, PlayerRole};
+use crate::arena::Arena;
+
+/// 落点标记开始和结束时的边长
+const MARKER_START_SIZE: f32 = 40.0;
+const MARKER_END_SIZE: f32 = 8.0;
+
+/// 飞行中、尚未结算的射击（只存在于开火的一方）
+#[derive(Component)]
+pub struct PendingShot {
+    pub attacker_id: PlayerId,
+    pub target_pos: Vec2,
+    pub weapon: Weapon,
+    pub damage_scale: f32, // 穿墙后剩余的伤害比例
+    pub networked: bool, // 联机时结算后发送 HealthUpdate
+    pub timer: Timer,
+}
+
+/// 瞄准点上的落点标记（随飞行时间收缩，子弹到达时消失）
+#[derive(Component)]
+pub struct ShotMarker {
+    pub timer: Timer,
+}
+
+/// 进攻方 HUD 上的飞行时间文字
+#[derive(Component)]
+pub struct TravelTimeText;
+
+/// 子弹从起点飞到瞄准点需要的时间（秒）
+pub fn travel_seconds(start_pos: Vec2, target_pos: Vec2, bullet_speed: f32) -> f32 {
+    if bullet_speed <= 0.0 {
+        return 0.0;
+    }
+    (target_pos - start_pos).length() / bullet_speed
+}
+
+/// 开火时记下这一枪，等子弹飞到瞄准点再结算
+pub fn spawn_pending_shot(
+    commands: &mut Commands,
+    attacker_id: PlayerId,
+    (attacker_pos, target_pos): (Vec2, Vec2),
+    weapon: Weapon,
+    damage_scale: f32,
+    networked: bool,
+) {
+    let seconds = travel_seconds(attacker_pos, target_pos, weapon.bullet_speed);
+    commands.spawn(PendingShot {
+        attacker_id,
+        target_pos,
+        weapon,
+        damage_scale,
+        networked,
+        timer: Timer::from_seconds(seconds, TimerMode::Once),
+    });
+    spawn_shot_markers(commands, target_pos, seconds);
+}
+
+/// 在瞄准点放置落点标记（进攻方和防守方视角各一个）
+/// 对方开火时由 handle_bullet_spawn_system 调用，双方看到的飞行时间一致
+pub fn spawn_shot_markers(commands: &mut Commands, target_pos: Vec2, seconds: f32) {
+    for layer in [0, 1] {
+        commands.spawn((
+            ShotMarker { timer: Timer::from_seconds(seconds, TimerMode::Once) },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 0.2, 0.1, 0.35),
+                    custom_size: Some(Vec2::splat(MARKER_START_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(target_pos.extend(7.0)),
+                ..default()
+            },
+            RenderLayers::layer(layer),
+        ));
+    }
+}
+
+/// 子弹到达瞄准点时按防守方当时的位置结算命中
+pub fn resolve_pending_shots_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shot_query: Query<(Entity, &mut PendingShot)>,
+    mut player_query: Query<(&Transform, &PlayerId, &PlayerRole, &DodgeAction, &mut Health)>,
+    mut round_info: ResMut<RoundInfo>,
+    mut events: EventWriter<PlayerHitEvent>,
+    network_manager: Option<Res<crate::network_game::NetworkManager>>,
+) {
+    for (entity, mut shot) in shot_query.iter_mut() {
+        shot.timer.tick(time.delta());
+        if !shot.timer.finished() {
+            continue;
+        }
+        commands.entity(entity).despawn();
+
+        for (transform, defender_id, role, dodge_action, mut health) in player_query.iter_mut() {
+            if !matches!(role, PlayerRole::Defender) || *defender_id == shot.attacker_id {
+                continue;
+            }
+            let Some(hitbox_type) = hitbox_at(shot.target_pos, transform.translation.truncate(), dodge_action) else {
+                // 调试输出已禁用: println!("[弹道] 子弹到达 ({:.1}, {:.1})，防守方已经躲开", shot.target_pos.x, shot.target_pos.y);
+                continue;
+            };
+            apply_hit(
+                &mut round_info,
+                shot.attacker_id,
+                (*defender_id, &mut health),
+                shot.weapon.damage(hitbox_type) * shot.damage_scale,
+                hitbox_type,
+                &mut events,
+                network_manager.as_deref().filter(|_| shot.networked),
+            );
+        }
+    }
+}
+
+/// 落点标记随剩余飞行时间收缩，到达后消失
+pub fn update_shot_markers_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut marker_query: Query<(Entity, &mut ShotMarker, &mut Sprite)>,
+) {
+    for (entity, mut marker, mut sprite) in marker_query.iter_mut() {
+        marker.timer.tick(time.delta());
+        if marker.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = marker.timer.fraction();
+        let size = MARKER_START_SIZE + (MARKER_END_SIZE - MARKER_START_SIZE) * progress;
+        sprite.custom_size = Some(Vec2::splat(size));
+        sprite.color.set_a(0.35 + 0.45 * progress);
+    }
+}
+
+/// 回合切换时清理还没结算的射击和落点标记
+pub fn cleanup_shots_on_switch(
+    mut commands: Commands,
+    shot_query: Query<Entity, With<PendingShot>>,
+    marker_query: Query<Entity, With<ShotMarker>>,
+) {
+    for entity in shot_query.iter().chain(marker_query.iter()) {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 创建飞行时间文字（放在瞄准镜文字下方）
+pub fn spawn_travel_time_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(String::new(), TextStyle { font, font_size: 16.0, color: Color::rgb(1.0, 0.6, 0.5) }),
+            style: Style { margin: UiRect::top(Val::Px(3.0)), ..default() },
+            ..default()
+        },
+        TravelTimeText,
+        crate::AttackerUI,
+    ));
+}
+
+/// 更新飞行时间文字：弹道判定下显示子弹飞到当前准星需要的时间，准星判定下不显示
+pub fn update_travel_time_display_system(
+    cursor_pos: Res<CursorPosition>,
+    round_info: Res<RoundInfo>,
+    loadout: Res<Loadout>,
+    (rules, arena): (Res<GameRules>, Res<Arena>),
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<TravelTimeText>>,
+) {
+    let summary = if rules.hit_resolution == HitResolution::Projectile {
+        let weapon = loadout.attacker_weapon(&round_info, &rules);
+        let seconds = travel_seconds(arena.aim_origin(), cursor_pos.0, weapon.bullet_speed);
+        format!("弹道飞行 {:.2} 秒", seconds)
+    } else {
+        String::new()
+    };
+    for mut text in text_query.iter_mut() {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value == summary {
+            continue;
+        }
+        section.value = summary.clone();
+        section.style.font = font_resource.font.clone();
+    }
+}
@@ -0,0 +1,658 @@
+// 网络协议：线上传输的类型、有界解码和消息校验
+//
+// 这个模块同时编译进库（供 fuzz 目标使用）和游戏本体，
+// 因此只依赖外部 crate，不引用 main.rs 中的常量，校验范围通过 MessageLimits 传入。
+
+use bevy::prelude::Component;
+use bincode::Options;
+use serde::{Serialize, Deserialize};
+use std::net::SocketAddr;
+use crate::map::{MapDef, MapSource, MAX_AIM_OFFSET, PLAYER_SIZE};
+use crate::rules::{GameRules, MAX_REPAIRS_PER_ROUND, MAX_UTILITY_CHARGES};
+use crate::weapon_stats;
+
+/// 单个数据报的最大字节数（接收缓冲区大小，同时也是解码的内存上限）
+pub const MAX_DATAGRAM_SIZE: usize = 1024;
+
+/// 字符串字段（房间ID、玩家名）的最大长度
+pub const MAX_STRING_LEN: usize = 64;
+
+/// GameState 中每个列表最多包含的条目数（每个玩家一条）
+pub const MAX_PLAYERS: usize = 2;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerId {
+    Player1,
+    Player2,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerRole {
+    Attacker,
+    Defender,
+}
+
+/// 防守方动作（组件，同时直接用于网络同步）
+/// 线上按变体序号编码：新动作只能追加在末尾，不能调整已有变体的顺序
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DodgeAction {
+    #[default]
+    None,
+    Crouch,
+    SideLeft,
+    SideRight,
+    /// 对方版本更新、本版本不认识的动作（只会由解码产生，本地不会主动使用）
+    #[serde(other)]
+    Unknown,
+}
+
+/// 进攻方使用的狙击枪（数值由游戏本体按对局规则换算）
+/// 线上按变体序号编码：新武器只能追加在末尾，不能调整已有变体的顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WeaponKind {
+    #[default]
+    BoltAction,    // 栓动步枪
+    SemiAuto,      // 半自动步枪
+    AntiMateriel,  // 反器材步枪
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 3] = [WeaponKind::BoltAction, WeaponKind::SemiAuto, WeaponKind::AntiMateriel];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeaponKind::BoltAction => "栓动步枪",
+            WeaponKind::SemiAuto => "半自动步枪",
+            WeaponKind::AntiMateriel => "反器材步枪",
+        }
+    }
+
+    /// 配装时按顺序切换到下一把
+    pub fn next(&self) -> WeaponKind {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// 防守方道具（效果由游戏本体实现，见 utility.rs）
+/// 线上按变体序号编码：新道具只能追加在末尾，不能调整已有变体的顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UtilityKind {
+    #[default]
+    Smoke,   // 烟雾弹
+    Decoy,   // 假人
+    Shield,  // 可部署护盾
+}
+
+impl UtilityKind {
+    pub const ALL: [UtilityKind; 3] = [UtilityKind::Smoke, UtilityKind::Decoy, UtilityKind::Shield];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UtilityKind::Smoke => "烟雾弹",
+            UtilityKind::Decoy => "假人",
+            UtilityKind::Shield => "护盾",
+        }
+    }
+
+    /// 按顺序切换到下一种道具
+    pub fn next(&self) -> UtilityKind {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// 比赛比分（主机随 RoundInfoSync 下发给客户端）
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MatchScore {
+    pub p1_damage: f32,        // 玩家1累计造成的伤害
+    pub p2_damage: f32,
+    pub p1_eliminations: u32,  // 玩家1淘汰对方的次数
+    pub p2_eliminations: u32,
+    pub turns_played: u32,     // 已结束的进攻回合数（双方合计）
+    pub overtime: bool,        // 是否进入加时赛
+}
+
+impl MatchScore {
+    pub fn damage(&self, player_id: PlayerId) -> f32 {
+        match player_id {
+            PlayerId::Player1 => self.p1_damage,
+            PlayerId::Player2 => self.p2_damage,
+        }
+    }
+
+    pub fn eliminations(&self, player_id: PlayerId) -> u32 {
+        match player_id {
+            PlayerId::Player1 => self.p1_eliminations,
+            PlayerId::Player2 => self.p2_eliminations,
+        }
+    }
+
+    /// 记录 attacker 造成的伤害
+    pub fn add_damage(&mut self, attacker: PlayerId, damage: f32) {
+        match attacker {
+            PlayerId::Player1 => self.p1_damage += damage,
+            PlayerId::Player2 => self.p2_damage += damage,
+        }
+    }
+
+    pub fn add_elimination(&mut self, attacker: PlayerId) {
+        match attacker {
+            PlayerId::Player1 => self.p1_eliminations += 1,
+            PlayerId::Player2 => self.p2_eliminations += 1,
+        }
+    }
+}
+
+/// 网络消息类型
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum NetworkMessage {
+    // 房间发现
+    RoomDiscoveryRequest,  // 请求发现房间
+    RoomDiscoveryResponse { room_id: String, player_name: String },  // 响应房间发现
+
+    // 连接
+    JoinRequest { room_id: String },  // 请求加入房间
+    JoinAccept { player_id: PlayerId },  // 接受加入
+    JoinReject,  // 拒绝加入
+
+    // 游戏状态同步
+    GameState {
+        player_positions: Vec<(PlayerId, [f32; 3])>,  // Vec3 序列化为 [f32; 3]
+        player_roles: Vec<(PlayerId, PlayerRole)>,
+        health: Vec<(PlayerId, f32)>,
+    },
+
+    // 玩家输入
+    PlayerInput {
+        player_id: PlayerId,
+        movement: Option<[f32; 2]>,  // Vec2 序列化为 [f32; 2]
+        action: Option<DodgeAction>,  // 本帧开始的动作
+        crosshair_pos: Option<[f32; 2]>,  // Vec2 序列化为 [f32; 2]
+    },
+
+    // 游戏事件
+    PlayerHit { player_id: PlayerId, damage: f32 },
+    GameOver { winner: PlayerId },
+    StartGame { rules: GameRules, map: MapSource },  // 主机的对局规则和地图（生成的地图只有种子），客户端按这份规则和地图进行本局
+
+    // 回合信息同步
+    RoundInfoSync {
+        current_attacker: PlayerId,
+        bullets_left: u32,
+        round_timer_remaining: f32,
+        p1_health: f32,
+        p2_health: f32,
+        bullets_fired: u32,
+        bullets_hit: u32,
+        score: MatchScore,
+        wind: [f32; 2],  // 本回合的风（弹道规则）
+        rules_hash: u64, // 主机当前规则的指纹（GameRules::fingerprint），客户端据此发现漏收的 RulesUpdate
+    },
+
+    // 角色切换
+    SwitchRoles {
+        new_attacker: PlayerId,
+    },
+
+    // 准星位置同步（进攻方发送给防守方）
+    CrosshairPosition { position: [f32; 2] },
+
+    // 防守方位置和动作同步（防守方发送给进攻方）
+    DefenderState {
+        position: [f32; 3],
+        dodge_action: DodgeAction,
+        stamina: f32,  // 剩余体力（随每次位置同步固定频率发送，不单独发消息）
+    },
+
+    // 子弹同步（发射子弹时发送）
+    BulletSpawn {
+        bullet_id: u64,  // 子弹同步ID
+        owner: PlayerId,  // 发射者
+        start_pos: [f32; 2],  // 起始位置
+        target_pos: [f32; 2],  // 目标位置
+        velocity: [f32; 2],  // 速度
+        weapon: WeaponKind,  // 发射所用的武器（决定破墙范围，接收方据此检查射速和弹匣）
+        turn: u32,  // 开火时已结束的进攻回合数（MatchScore::turns_played），接收方据此确认自己的风属于同一个进攻回合
+    },
+
+    // 血量更新（被击中时发送）
+    HealthUpdate {
+        player_id: PlayerId,
+        health: f32,
+    },
+
+    // 再来一局
+    RematchRequest,  // 请求再来一局
+    RematchReady,    // 准备再来一局（双方都点击后）
+
+    // 规则更新（主机在调参面板中修改的规则，回合切换时下发）
+    RulesUpdate { rules: GameRules },
+
+    // 配装（进攻方在本回合开火前切换武器时发送）
+    WeaponSelect { player_id: PlayerId, weapon: WeaponKind },
+
+    // 防守方使用道具（会重复发送几次，charge 为本回合第几次使用，接收方据此去重）
+    DefenderUtility { player_id: PlayerId, kind: UtilityKind, position: [f32; 2], charge: u32 },
+
+    // 防守方修好了一块砖（会重复发送几次，repair 为本回合第几次修墙，接收方据此去重）
+    WallRepair { player_id: PlayerId, depth: u8, position: [f32; 2], repair: u32 },
+
+    // 客户端的规则与 RoundInfoSync 中主机的规则指纹不一致（RulesUpdate 全部丢失）时请求主机重发规则
+    RulesRequest,
+}
+
+/// 线上编码配置：与 bincode::serialize 的格式完全一致（定长整数、小端、允许尾部多余字节），
+/// 但限制总字节数，超长的 Vec/String 长度前缀会直接报错而不会先分配内存
+fn wire_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_DATAGRAM_SIZE as u64)
+}
+
+/// 编码消息，超过 MAX_DATAGRAM_SIZE 的消息会编码失败（对方也无法接收）
+pub fn encode_message(message: &NetworkMessage) -> bincode::Result<Vec<u8>> {
+    wire_options().serialize(message)
+}
+
+/// 有界解码收到的数据报（不做取值校验）
+pub fn decode_datagram(data: &[u8]) -> bincode::Result<NetworkMessage> {
+    if data.len() > MAX_DATAGRAM_SIZE {
+        return Err(Box::new(bincode::ErrorKind::SizeLimit));
+    }
+    wire_options().deserialize(data)
+}
+
+/// 消息取值的合法范围（由游戏常量和当前对局规则构造）
+#[derive(Debug, Clone, Copy)]
+pub struct MessageLimits {
+    pub aim_origin: [f32; 2],          // 瞄准原点（进攻方位置）
+    pub max_aim_offset: f32,           // 准星离瞄准原点的最大距离
+    pub defender_min: [f32; 2],        // 防守方可移动区域（墙体范围）
+    pub defender_max: [f32; 2],
+    pub world_half_extent: f32,        // 其他坐标（玩家、子弹起点）的绝对值上限
+    pub max_bullet_speed: f32,
+    pub max_health: f32,
+    pub max_damage: f32,
+    pub max_bullets_per_round: u32,
+    pub max_round_time: f32,
+    pub max_wind: f32,                 // 风的最大强度
+    pub max_stamina: f32,              // 防守方体力上限
+    pub max_eliminations: u32,         // 一名玩家最多的淘汰次数（按赛制）
+}
+
+/// 按对局规则和地图构造校验范围（游戏本体和 fuzz 目标共用）
+pub fn limits_for(rules: &GameRules, map: &MapDef) -> MessageLimits {
+    // 侧躲会让防守方短暂越过移动边界，横向额外留出侧躲距离
+    let (defender_min, defender_max) = map.defender_bounds(PLAYER_SIZE);
+    MessageLimits {
+        aim_origin: map.attacker_spawn,
+        max_aim_offset: MAX_AIM_OFFSET,
+        defender_min: [defender_min[0] - rules.side_dodge_distance, defender_min[1]],
+        defender_max: [defender_max[0] + rules.side_dodge_distance, defender_max[1]],
+        // 子弹飞出 1500 后销毁，坐标不会超过这个范围太多
+        world_half_extent: 2000.0,
+        max_bullet_speed: weapon_stats::max_bullet_speed(rules) * 1.01,
+        max_health: rules.player_hp,
+        max_damage: weapon_stats::max_damage(rules),
+        max_bullets_per_round: weapon_stats::max_magazine_size(rules) as u32,
+        max_round_time: rules.round_time_seconds,
+        max_wind: rules.wind_strength * 1.01,
+        max_stamina: rules.stamina_max,
+        max_eliminations: rules.match_format.max_eliminations(),
+    }
+}
+
+/// 校验解码后的消息：浮点数必须有限，坐标在合法范围内，列表中的玩家不能重复
+pub fn validate_message(message: &NetworkMessage, limits: &MessageLimits) -> Result<(), String> {
+    match message {
+        NetworkMessage::RoomDiscoveryRequest
+        | NetworkMessage::JoinReject
+        | NetworkMessage::RematchRequest
+        | NetworkMessage::RematchReady
+        | NetworkMessage::RulesRequest
+        | NetworkMessage::JoinAccept { .. }
+        | NetworkMessage::GameOver { .. }
+        | NetworkMessage::SwitchRoles { .. }
+        | NetworkMessage::WeaponSelect { .. } => Ok(()),
+        NetworkMessage::RoomDiscoveryResponse { room_id, player_name } => {
+            check_string("room_id", room_id)?;
+            check_string("player_name", player_name)
+        }
+        NetworkMessage::JoinRequest { room_id } => check_string("room_id", room_id),
+        NetworkMessage::StartGame { rules, map } => {
+            rules.validate()?;
+            map.validate()
+        }
+        NetworkMessage::RulesUpdate { rules } => rules.validate(),
+        NetworkMessage::GameState { player_positions, player_roles, health } => {
+            check_player_list("player_positions", player_positions.iter().map(|(id, _)| *id))?;
+            check_player_list("player_roles", player_roles.iter().map(|(id, _)| *id))?;
+            check_player_list("health", health.iter().map(|(id, _)| *id))?;
+            for (_, position) in player_positions {
+                check_world_position("player_positions", &position[..2], limits)?;
+                check_finite("player_positions.z", position[2])?;
+            }
+            for (_, value) in health {
+                check_range("health", *value, 0.0, limits.max_health)?;
+            }
+            Ok(())
+        }
+        NetworkMessage::PlayerInput { movement, crosshair_pos, .. } => {
+            if let Some(movement) = movement {
+                for value in movement {
+                    check_range("movement", *value, -1.0, 1.0)?;
+                }
+            }
+            if let Some(position) = crosshair_pos {
+                check_aim_position("crosshair_pos", *position, limits)?;
+            }
+            Ok(())
+        }
+        NetworkMessage::PlayerHit { damage, .. } => check_range("damage", *damage, 0.0, limits.max_damage),
+        NetworkMessage::RoundInfoSync {
+            bullets_left,
+            round_timer_remaining,
+            p1_health,
+            p2_health,
+            bullets_fired,
+            bullets_hit,
+            score,
+            wind,
+            ..
+        } => {
+            if *bullets_left > limits.max_bullets_per_round || *bullets_hit > *bullets_fired {
+                return Err(format!(
+                    "子弹计数不合法: left={}, fired={}, hit={}",
+                    bullets_left, bullets_fired, bullets_hit
+                ));
+            }
+            check_range("round_timer_remaining", *round_timer_remaining, 0.0, limits.max_round_time)?;
+            check_range("p1_health", *p1_health, 0.0, limits.max_health)?;
+            check_range("p2_health", *p2_health, 0.0, limits.max_health)?;
+            if score.p1_eliminations > limits.max_eliminations || score.p2_eliminations > limits.max_eliminations {
+                return Err(format!(
+                    "淘汰次数超出范围 [0, {}]: p1={}, p2={}",
+                    limits.max_eliminations, score.p1_eliminations, score.p2_eliminations
+                ));
+            }
+            // 已结束的回合数不做上限检查：经典淘汰、先淘汰N次和回合制的加时赛都可以一直打下去，规则没有保证任何上限。
+            // 伤害按回合数约束：每名玩家最多进攻过一半已结束的回合再加上当前回合，每回合最多打出一整个弹匣的最高伤害
+            let max_attack_turns = (score.turns_played / 2 + 1) as f32;
+            let max_score_damage = limits.max_damage * limits.max_bullets_per_round as f32 * max_attack_turns;
+            check_range("score.p1_damage", score.p1_damage, 0.0, max_score_damage)?;
+            check_range("score.p2_damage", score.p2_damage, 0.0, max_score_damage)?;
+            check_finite("wind", wind[0])?;
+            check_finite("wind", wind[1])?;
+            check_range("wind", wind[0].hypot(wind[1]), 0.0, limits.max_wind)
+        }
+        NetworkMessage::CrosshairPosition { position } => check_aim_position("position", *position, limits),
+        NetworkMessage::DefenderState { position, stamina, .. } => {
+            check_finite("position.z", position[2])?;
+            check_range("position.x", position[0], limits.defender_min[0], limits.defender_max[0])?;
+            check_range("position.y", position[1], limits.defender_min[1], limits.defender_max[1])?;
+            check_range("stamina", *stamina, 0.0, limits.max_stamina)
+        }
+        NetworkMessage::BulletSpawn { start_pos, target_pos, velocity, .. } => {
+            check_world_position("start_pos", start_pos, limits)?;
+            check_aim_position("target_pos", *target_pos, limits)?;
+            check_finite("velocity", velocity[0])?;
+            check_finite("velocity", velocity[1])?;
+            let speed = velocity[0].hypot(velocity[1]);
+            check_range("velocity", speed, 0.0, limits.max_bullet_speed)
+        }
+        NetworkMessage::HealthUpdate { health, .. } => check_range("health", *health, 0.0, limits.max_health),
+        NetworkMessage::DefenderUtility { position, charge, .. } => {
+            // 这里只排除任何规则下都不可能的次数，本回合的道具次数由反作弊按当前规则统计（超出时记为违规）
+            if *charge >= MAX_UTILITY_CHARGES {
+                return Err(format!("道具次数超出范围 [0, {}): {}", MAX_UTILITY_CHARGES, charge));
+            }
+            check_range("position.x", position[0], limits.defender_min[0], limits.defender_max[0])?;
+            check_range("position.y", position[1], limits.defender_min[1], limits.defender_max[1])
+        }
+        NetworkMessage::WallRepair { depth, position, repair, .. } => {
+            // 同道具次数，本回合的修墙次数由反作弊统计
+            if *repair >= MAX_REPAIRS_PER_ROUND {
+                return Err(format!("修墙次数超出范围 [0, {}): {}", MAX_REPAIRS_PER_ROUND, repair));
+            }
+            if *depth > crate::map::MAX_WALL_DEPTH {
+                return Err(format!("墙的深度超出范围 [0, {}]: {}", crate::map::MAX_WALL_DEPTH, depth));
+            }
+            check_world_position("position", position, limits)
+        }
+    }
+}
+
+fn check_finite(field: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{} 不是有限数: {}", field, value))
+    }
+}
+
+fn check_range(field: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    check_finite(field, value)?;
+    if value < min || value > max {
+        return Err(format!("{} 超出范围 [{}, {}]: {}", field, min, max, value));
+    }
+    Ok(())
+}
+
+fn check_string(field: &str, value: &str) -> Result<(), String> {
+    if value.len() > MAX_STRING_LEN {
+        return Err(format!("{} 过长: {} 字节", field, value.len()));
+    }
+    Ok(())
+}
+
+fn check_world_position(field: &str, position: &[f32], limits: &MessageLimits) -> Result<(), String> {
+    for value in position {
+        check_range(field, *value, -limits.world_half_extent, limits.world_half_extent)?;
+    }
+    Ok(())
+}
+
+fn check_aim_position(field: &str, position: [f32; 2], limits: &MessageLimits) -> Result<(), String> {
+    check_finite(field, position[0])?;
+    check_finite(field, position[1])?;
+    let distance = (position[0] - limits.aim_origin[0]).hypot(position[1] - limits.aim_origin[1]);
+    // 留一点余量，避免浮点误差把边缘上的合法准星判为越界
+    if distance > limits.max_aim_offset + 1.0 {
+        return Err(format!("{} 超出瞄准范围: 距离 {:.1} > {}", field, distance, limits.max_aim_offset));
+    }
+    Ok(())
+}
+
+fn check_player_list(field: &str, ids: impl Iterator<Item = PlayerId>) -> Result<(), String> {
+    let mut seen = Vec::with_capacity(MAX_PLAYERS);
+    for id in ids {
+        if seen.contains(&id) {
+            return Err(format!("{} 中玩家 {:?} 重复", field, id));
+        }
+        if seen.len() >= MAX_PLAYERS {
+            return Err(format!("{} 条目过多", field));
+        }
+        seen.push(id);
+    }
+    Ok(())
+}
+
+/// 会合/中继服务器控制报文的前缀
+/// 游戏消息的前 4 字节是变体序号（远小于该值），两类报文不会混淆
+pub const RENDEZVOUS_MAGIC: [u8; 4] = *b"SDRV";
+
+/// 会合/中继服务器的控制消息（游戏消息不经过这里，中继时原样转发）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RendezvousMessage {
+    Register { room_id: String, secret: u64 },         // 主机 -> 服务器：登记房间（定期重发作为保活），secret 为 Registered 中拿到的房间密钥（还没有时为 0）
+    Registered { public_addr: SocketAddr, secret: u64 }, // 服务器 -> 主机：服务器看到的主机公网地址和房间密钥（主机换地址时凭密钥重新登记）
+    Connect { room_id: String },                       // 客户端 -> 服务器：请求连接房间
+    RoomNotFound { room_id: String },                  // 服务器 -> 客户端：房间未登记
+    PeerInfo { room_id: String, peer: SocketAddr },    // 服务器 -> 双方：对方的公网地址，开始打洞
+    Punch { room_id: String },                         // 双方互发：打洞报文
+    RelayRequest { room_id: String },                  // 客户端 -> 服务器：打洞失败，改用中继
+    RelayReady { room_id: String },                    // 服务器 -> 双方：中继已就绪，之后游戏报文发给服务器
+    RoomTaken { room_id: String },                     // 服务器 -> 客户端：房间已经有客户端了
+}
+
+impl RendezvousMessage {
+    /// 消息中的房间ID
+    pub fn room_id(&self) -> Option<&str> {
+        match self {
+            RendezvousMessage::Registered { .. } => None,
+            RendezvousMessage::Register { room_id, .. }
+            | RendezvousMessage::Connect { room_id }
+            | RendezvousMessage::RoomNotFound { room_id }
+            | RendezvousMessage::PeerInfo { room_id, .. }
+            | RendezvousMessage::Punch { room_id }
+            | RendezvousMessage::RelayRequest { room_id }
+            | RendezvousMessage::RelayReady { room_id }
+            | RendezvousMessage::RoomTaken { room_id } => Some(room_id),
+        }
+    }
+}
+
+/// 编码控制报文（带 RENDEZVOUS_MAGIC 前缀）
+pub fn encode_rendezvous(message: &RendezvousMessage) -> bincode::Result<Vec<u8>> {
+    let mut data = RENDEZVOUS_MAGIC.to_vec();
+    data.extend(wire_options().serialize(message)?);
+    Ok(data)
+}
+
+/// 数据报是否为控制报文
+pub fn is_rendezvous_datagram(data: &[u8]) -> bool {
+    data.starts_with(&RENDEZVOUS_MAGIC)
+}
+
+/// 解码控制报文，房间ID过长的报文视为非法
+pub fn decode_rendezvous(data: &[u8]) -> Result<RendezvousMessage, String> {
+    let Some(payload) = data.strip_prefix(&RENDEZVOUS_MAGIC) else {
+        return Err("缺少控制报文前缀".to_string());
+    };
+    if payload.len() > MAX_DATAGRAM_SIZE {
+        return Err("控制报文过长".to_string());
+    }
+    let message: RendezvousMessage = wire_options().deserialize(payload).map_err(|e| e.to_string())?;
+    if let Some(room_id) = message.room_id() {
+        check_string("room_id", room_id)?;
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 经典规则和内置经典地图的校验范围（与 fuzz 目标一致）
+    fn classic_limits() -> MessageLimits {
+        limits_for(&GameRules::default(), &MapDef::default())
+    }
+
+    /// 解码并校验，返回失败原因（与游戏本体接收数据报时的处理一致）
+    fn accept(data: &[u8]) -> Result<NetworkMessage, String> {
+        let message = decode_datagram(data).map_err(|e| e.to_string())?;
+        validate_message(&message, &classic_limits())?;
+        Ok(message)
+    }
+
+    /// fuzz 语料库中的 bad_* 必须被拒绝，valid_* 必须被接受（不装 cargo-fuzz 也能回归检查）
+    #[test]
+    fn fuzz_corpus_is_classified() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/decode_message");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(dir).expect("应能读取语料库目录") {
+            let path = entry.expect("应能读取语料库文件").path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+            let data = std::fs::read(&path).expect("应能读取语料库文件");
+            if name.starts_with("bad_") {
+                assert!(accept(&data).is_err(), "{} 应被拒绝", name);
+            } else if name.starts_with("valid_") {
+                if let Err(e) = accept(&data) {
+                    panic!("{} 应被接受: {}", name, e);
+                }
+            } else {
+                panic!("语料库文件 {} 应以 bad_ 或 valid_ 开头", name);
+            }
+            checked += 1;
+        }
+        assert!(checked > 0, "语料库为空");
+    }
+
+    #[test]
+    fn oversize_vec_length_is_rejected() {
+        let mut data = encode_message(&NetworkMessage::GameState {
+            player_positions: Vec::new(),
+            player_roles: Vec::new(),
+            health: Vec::new(),
+        })
+        .unwrap();
+        // 变体序号（u32）之后是第一个列表的长度（u64），改成巨大的长度不能按前缀分配内存
+        data[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(decode_datagram(&data).is_err());
+        data[4..12].copy_from_slice(&(MAX_DATAGRAM_SIZE as u64).to_le_bytes());
+        assert!(decode_datagram(&data).is_err());
+
+        // 长度合法但玩家重复的列表在校验时拒绝
+        let duplicated = NetworkMessage::GameState {
+            player_positions: vec![(PlayerId::Player1, [0.0; 3]); 3],
+            player_roles: Vec::new(),
+            health: Vec::new(),
+        };
+        assert!(accept(&encode_message(&duplicated).unwrap()).is_err());
+
+        // 超过一个数据报的消息编码失败，超长的数据报直接拒绝
+        let oversized = NetworkMessage::RoomDiscoveryResponse { room_id: String::new(), player_name: "x".repeat(MAX_DATAGRAM_SIZE) };
+        assert!(encode_message(&oversized).is_err());
+        assert!(decode_datagram(&vec![0; MAX_DATAGRAM_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn non_finite_floats_are_rejected() {
+        let origin = classic_limits().aim_origin;
+        assert!(accept(&encode_message(&NetworkMessage::CrosshairPosition { position: origin }).unwrap()).is_ok());
+        for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let crosshair = NetworkMessage::CrosshairPosition { position: [bad, origin[1]] };
+            assert!(accept(&encode_message(&crosshair).unwrap()).is_err(), "准星坐标 {} 应被拒绝", bad);
+            let health = NetworkMessage::HealthUpdate { player_id: PlayerId::Player1, health: bad };
+            assert!(accept(&encode_message(&health).unwrap()).is_err(), "血量 {} 应被拒绝", bad);
+        }
+    }
+
+    /// 回合数不设上限（加时赛和不限回合的赛制可以一直打下去），累计伤害仍按回合数约束
+    #[test]
+    fn long_match_score_is_bounded_by_damage_only() {
+        let sync = |score: MatchScore| NetworkMessage::RoundInfoSync {
+            current_attacker: PlayerId::Player1,
+            bullets_left: 3,
+            round_timer_remaining: 10.0,
+            p1_health: 100.0,
+            p2_health: 100.0,
+            bullets_fired: 0,
+            bullets_hit: 0,
+            score,
+            wind: [0.0, 0.0],
+            rules_hash: 0,
+        };
+        let limits = classic_limits();
+        let turns_played = 100_000;
+        let max_damage = limits.max_damage * limits.max_bullets_per_round as f32 * (turns_played / 2 + 1) as f32;
+        let score = MatchScore { turns_played, p1_damage: max_damage, overtime: true, ..Default::default() };
+        assert!(validate_message(&sync(score), &limits).is_ok());
+        let score = MatchScore { p1_damage: max_damage * 1.01, ..score };
+        assert!(validate_message(&sync(score), &limits).is_err());
+    }
+
+    #[test]
+    fn unknown_enum_tag_is_rejected() {
+        let mut data = encode_message(&NetworkMessage::JoinAccept { player_id: PlayerId::Player2 }).unwrap();
+        assert!(accept(&data).is_ok());
+        // 玩家ID只有两个变体
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        assert!(decode_datagram(&data).is_err());
+        // 消息本身的变体序号超出范围
+        data[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_datagram(&data).is_err());
+    }
+}
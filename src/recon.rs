@@ -0,0 +1,287 @@
+// 进攻方侦察：热成像扫描和脚步提示
+//
+// 墙体打碎之前进攻方什么都看不到，侦察在 wall_visibility_update_system 的基础上给进攻方视角多加一层信息：
+// - 热成像（按 T）：每回合 thermal_scans 次，回合开始和每次扫描后要等 thermal_cooldown_seconds 秒，
+//   在 thermal_seconds 秒内把防守方的身体轮廓画在完好的砖块上面（不透明度 thermal_strength，逐渐变淡）。
+//   轮廓跟着防守方移动、下蹲和侧躲；假人没有体温，扫描中不会出现；烟雾仍然挡得住轮廓。
+// - 脚步提示：防守方每走出一段距离，进攻方准星周围朝防守方方向的位置闪一下（不透明度 footstep_strength，
+//   两次提示至少隔 footstep_interval_seconds 秒）。下蹲移动没有脚步声。
+// 两者都只用本机已有的防守方位置（联机时来自对方的 PlayerInput），只在本机有进攻方视角时显示，不需要额外的网络消息。
+// 次数、冷却和提示在换回合时清空。
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::game_rules::GameRules;
+use crate::gameplay::{AttackerAI, CursorPosition, DodgeAction, HumanoidPart, RoundInfo, ViewConfig, ViewLayer};
+use crate::{FontResource, PlayerId, RoomInfo};
+
+/// 热成像扫描的按键
+const THERMAL_KEY: KeyCode = KeyCode::KeyT;
+/// 热成像轮廓的 Z（在所有墙层和掩体之上，在烟雾之下）
+const THERMAL_Z: f32 = 2.4;
+/// 热成像轮廓比身体部位大出的像素（看起来像一圈热晕）
+const THERMAL_GLOW: f32 = 6.0;
+/// 防守方每走出这么远算一步（像素）
+const FOOTSTEP_STRIDE: f32 = 40.0;
+/// 脚步提示离准星的距离（像素）
+const FOOTSTEP_CUE_RADIUS: f32 = 110.0;
+/// 脚步提示的大小（垂直于方向的一小段弧）
+const FOOTSTEP_CUE_SIZE: Vec2 = Vec2::new(8.0, 36.0);
+/// 脚步提示显示的时间（秒）
+const FOOTSTEP_CUE_SECONDS: f32 = 0.6;
+/// 脚步提示的 Z（在烟雾和准星之上）
+const FOOTSTEP_CUE_Z: f32 = 3.0;
+
+/// 本回合的侦察状态
+#[derive(Resource, Default, Debug)]
+pub struct ReconState {
+    pub scans_used: u32,
+    pub last_scan_at: f32,            // 上一次扫描时回合已进行的秒数（回合开始时为 0）
+    pub scan_timer: Option<Timer>,    // 正在显示的热成像轮廓
+    last_step_pos: Option<Vec2>,      // 上一次算作一步时防守方的位置
+    last_cue_at: f64,                 // 上一次脚步提示的时间
+}
+
+/// 热成像轮廓（每个防守方身体部位一个，跟随 source 部位）
+#[derive(Component)]
+pub struct ThermalOutline {
+    source: Entity,
+}
+
+/// 脚步提示（跟随准星，朝向防守方的方向）
+#[derive(Component)]
+pub struct FootstepCue {
+    direction: Vec2,
+    timer: Timer,
+    alpha: f32,
+}
+
+/// 热成像的次数和冷却（进攻方视角）
+#[derive(Component)]
+pub struct ReconText;
+
+/// 热成像要跟随的防守方身体部位（不含轮廓自己）
+type OutlineSourceFilter = (With<HumanoidPart>, Without<ThermalOutline>);
+/// 换回合时要清掉的侦察实体
+type ReconEntityFilter = Or<(With<ThermalOutline>, With<FootstepCue>)>;
+
+/// 本机是否有进攻方视角：本地模式总是有，联机时只有本机是进攻方时才有
+fn has_attacker_view(room_info: Option<&RoomInfo>, view_config: &ViewConfig) -> bool {
+    !room_info.is_some_and(|room| room.is_connected) || view_config.is_attacker_view
+}
+
+/// 本回合是否还能侦察
+fn can_recon(round_info: &RoundInfo) -> bool {
+    !round_info.is_switching && !round_info.round_timer.finished()
+}
+
+/// 距离下一次能扫描还要等的秒数（0 表示就绪）
+fn scan_cooldown_left(recon: &ReconState, round_info: &RoundInfo, rules: &GameRules) -> f32 {
+    (recon.last_scan_at + rules.thermal_cooldown_seconds - round_info.round_timer.elapsed_secs()).max(0.0)
+}
+
+/// 进攻方：按 T 扫描，把防守方在进攻方视角中的身体部位画成热成像轮廓
+pub fn thermal_scan_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut recon: ResMut<ReconState>,
+    (round_info, rules): (Res<RoundInfo>, Res<GameRules>),
+    part_query: Query<(Entity, &HumanoidPart)>,
+    bot_query: Query<&PlayerId, With<AttackerAI>>,
+    room_info: Option<Res<RoomInfo>>,
+) {
+    if !keyboard_input.just_pressed(THERMAL_KEY) || !can_recon(&round_info) || recon.scan_timer.is_some() {
+        return;
+    }
+    if recon.scans_used >= rules.thermal_scans || scan_cooldown_left(&recon, &round_info, &rules) > 0.0 {
+        return;
+    }
+    // 电脑进攻方不扫描；联机时只有本机是进攻方时才能扫描
+    let attacker = round_info.current_attacker;
+    if bot_query.iter().any(|id| *id == attacker) {
+        return;
+    }
+    let is_network_mode = room_info.as_ref().is_some_and(|room| room.is_connected);
+    if is_network_mode {
+        let local_player = if room_info.as_ref().is_some_and(|room| room.is_host) { PlayerId::Player1 } else { PlayerId::Player2 };
+        if local_player != attacker {
+            return;
+        }
+    }
+
+    recon.scans_used += 1;
+    recon.last_scan_at = round_info.round_timer.elapsed_secs();
+    recon.scan_timer = Some(Timer::from_seconds(rules.thermal_seconds, TimerMode::Once));
+    let defender = crate::scoring::opponent(attacker);
+    for (entity, part) in part_query.iter() {
+        if part.player_id != defender || part.view_layer != ViewLayer::AttackerView {
+            continue;
+        }
+        commands.spawn((
+            ThermalOutline { source: entity },
+            SpriteBundle {
+                sprite: Sprite { color: Color::rgba(1.0, 0.35, 0.1, rules.thermal_strength), ..default() },
+                visibility: Visibility::Hidden, // 第一次跟随部位之后再显示
+                ..default()
+            },
+            RenderLayers::layer(0), // 只在进攻方视角
+        ));
+    }
+    // 调试输出已禁用: println!("[侦察] {:?} 热成像扫描（本回合第 {} 次）", attacker, recon.scans_used);
+}
+
+/// 热成像轮廓跟随防守方的身体部位（含下蹲和侧躲），到时间前逐渐变淡
+pub fn thermal_outline_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    rules: Res<GameRules>,
+    mut recon: ResMut<ReconState>,
+    part_query: Query<(&Transform, &Sprite), OutlineSourceFilter>,
+    mut outline_query: Query<(Entity, &ThermalOutline, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let fade = match recon.scan_timer.as_mut() {
+        Some(timer) => {
+            timer.tick(time.delta());
+            (!timer.finished()).then(|| timer.remaining_secs() / timer.duration().as_secs_f32().max(f32::EPSILON))
+        }
+        None => None,
+    };
+    if fade.is_none() {
+        recon.scan_timer = None;
+    }
+    for (entity, outline, mut transform, mut sprite, mut visibility) in outline_query.iter_mut() {
+        // 扫描结束或部位已经被重建（换角色）时移除
+        let (Some(fade), Ok((part_transform, part_sprite))) = (fade, part_query.get(outline.source)) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        transform.translation = part_transform.translation.truncate().extend(THERMAL_Z);
+        sprite.custom_size = part_sprite.custom_size.map(|size| size + Vec2::splat(THERMAL_GLOW));
+        sprite.color.set_a(rules.thermal_strength * fade);
+        *visibility = Visibility::Visible;
+    }
+}
+
+/// 防守方移动时在进攻方准星周围显示朝向防守方的脚步提示
+pub fn footstep_cue_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut recon: ResMut<ReconState>,
+    (round_info, rules, view_config): (Res<RoundInfo>, Res<GameRules>, Res<ViewConfig>),
+    defender_query: Query<(&Transform, &PlayerId, &DodgeAction), Without<HumanoidPart>>,
+    cursor_pos: Res<CursorPosition>,
+    room_info: Option<Res<RoomInfo>>,
+) {
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+    let Some((transform, _, dodge_action)) = defender_query.iter().find(|(_, id, _)| **id == defender) else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let Some(last_step) = recon.last_step_pos else {
+        recon.last_step_pos = Some(position);
+        return;
+    };
+    if position.distance(last_step) < FOOTSTEP_STRIDE {
+        return;
+    }
+    recon.last_step_pos = Some(position);
+    // 下蹲移动没有脚步声
+    if matches!(dodge_action, DodgeAction::Crouch) || rules.footstep_strength <= 0.0 || !can_recon(&round_info) {
+        return;
+    }
+    if !has_attacker_view(room_info.as_deref(), &view_config) {
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    if now - recon.last_cue_at < rules.footstep_interval_seconds as f64 {
+        return;
+    }
+    recon.last_cue_at = now;
+
+    let direction = (position - cursor_pos.0).normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return;
+    }
+    commands.spawn((
+        FootstepCue { direction, timer: Timer::from_seconds(FOOTSTEP_CUE_SECONDS, TimerMode::Once), alpha: rules.footstep_strength },
+        SpriteBundle {
+            sprite: Sprite { color: Color::rgba(1.0, 0.95, 0.7, rules.footstep_strength), custom_size: Some(FOOTSTEP_CUE_SIZE), ..default() },
+            transform: Transform::from_translation((cursor_pos.0 + direction * FOOTSTEP_CUE_RADIUS).extend(FOOTSTEP_CUE_Z))
+                .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))),
+            ..default()
+        },
+        RenderLayers::layer(0), // 只在进攻方视角
+    ));
+}
+
+/// 脚步提示跟着准星移动，逐渐变淡后移除
+pub fn update_footstep_cues_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    cursor_pos: Res<CursorPosition>,
+    mut cue_query: Query<(Entity, &mut FootstepCue, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut cue, mut transform, mut sprite) in cue_query.iter_mut() {
+        cue.timer.tick(time.delta());
+        if cue.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation = (cursor_pos.0 + cue.direction * FOOTSTEP_CUE_RADIUS).extend(FOOTSTEP_CUE_Z);
+        sprite.color.set_a(cue.alpha * cue.timer.remaining_secs() / FOOTSTEP_CUE_SECONDS);
+    }
+}
+
+/// 换回合时恢复扫描次数，清掉轮廓和提示
+pub fn reset_recon_on_switch(
+    mut commands: Commands,
+    mut recon: ResMut<ReconState>,
+    query: Query<Entity, ReconEntityFilter>,
+) {
+    *recon = ReconState::default();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 创建侦察文字（放在瞄准镜文字下方）
+pub fn spawn_recon_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(String::new(), TextStyle { font, font_size: 16.0, color: Color::GRAY }),
+            style: Style { margin: UiRect::top(Val::Px(3.0)), ..default() },
+            ..default()
+        },
+        ReconText,
+        crate::AttackerUI,
+    ));
+}
+
+/// 更新侦察文字（只在内容变化时写入）
+pub fn update_recon_display_system(
+    recon: Res<ReconState>,
+    (round_info, rules): (Res<RoundInfo>, Res<GameRules>),
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<ReconText>>,
+) {
+    let remaining = rules.thermal_scans.saturating_sub(recon.scans_used);
+    let cooldown = scan_cooldown_left(&recon, &round_info, &rules);
+    let state = if recon.scan_timer.is_some() {
+        "扫描中".to_string()
+    } else if remaining == 0 {
+        "已用完".to_string()
+    } else if cooldown > 0.0 {
+        format!("冷却 {:.0} 秒", cooldown.ceil())
+    } else {
+        "就绪（T 扫描）".to_string()
+    };
+    let summary = format!("热成像 {}/{}：{}", remaining, rules.thermal_scans, state);
+    for mut text in text_query.iter_mut() {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value == summary {
+            continue;
+        }
+        section.value = summary.clone();
+        section.style.font = font_resource.font.clone();
+    }
+}
@@ -0,0 +1,284 @@
+// 跨网络联机：通过会合/中继服务器打洞，失败时自动改用中继
+//
+// 默认关闭。启动时通过 `--rendezvous <地址:端口>` 参数或环境变量 `SNIPER_RENDEZVOUS` 指定服务器
+// （服务器见 src/bin/rendezvous_server.rs）。开启后：
+// - 主机创建房间时向服务器登记房间号，并定期保活
+// - 客户端在加入页输入房间号中的数字（而不是 IP），先尝试打洞直连，超时后改用服务器中继
+// 中继对游戏逻辑透明：远程地址变成服务器地址，报文由服务器原样转发。
+// 加 `--force-relay`（或 `SNIPER_FORCE_RELAY=1`）可跳过打洞，用于在本机测试中继。
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use bevy_sniper_duel::protocol::{self, RendezvousMessage};
+
+/// 服务器地址的环境变量名
+pub const RENDEZVOUS_ENV_VAR: &str = "SNIPER_RENDEZVOUS";
+/// 强制中继的环境变量名
+pub const FORCE_RELAY_ENV_VAR: &str = "SNIPER_FORCE_RELAY";
+
+/// 房间ID前缀（房间号为 ROOM_ 加数字，客户端只需输入数字部分）
+const ROOM_ID_PREFIX: &str = "ROOM_";
+/// 主机登记保活间隔（需要短于服务器的房间过期时间，同时保持 NAT 映射）
+const REGISTER_INTERVAL: Duration = Duration::from_secs(5);
+/// 控制报文重发间隔
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+/// 打洞报文发送间隔
+const PUNCH_INTERVAL: Duration = Duration::from_millis(200);
+/// 等待服务器回复对方地址的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 打洞超时，超时后改用中继
+const PUNCH_TIMEOUT: Duration = Duration::from_secs(3);
+/// 等待中继就绪的超时
+const RELAY_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct RendezvousConfig {
+    server: Option<SocketAddr>,
+    force_relay: bool,
+}
+
+static RENDEZVOUS_CONFIG: OnceLock<RendezvousConfig> = OnceLock::new();
+
+/// 初始化会合服务器配置：优先使用命令行参数，其次使用环境变量
+pub fn init_rendezvous(args: &[String]) {
+    let server = args
+        .iter()
+        .position(|arg| arg == "--rendezvous")
+        .and_then(|index| args.get(index + 1).cloned())
+        .or_else(|| std::env::var(RENDEZVOUS_ENV_VAR).ok())
+        .filter(|addr| !addr.is_empty())
+        .and_then(|addr| match addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+            Ok(Some(resolved)) => {
+                eprintln!("[中继] 使用会合服务器: {} ({})", addr, resolved);
+                Some(resolved)
+            }
+            Ok(None) | Err(_) => {
+                eprintln!("[中继] 无法解析会合服务器地址: {}", addr);
+                None
+            }
+        });
+    let force_relay = args.iter().any(|arg| arg == "--force-relay")
+        || std::env::var(FORCE_RELAY_ENV_VAR).is_ok_and(|value| value == "1");
+    let _ = RENDEZVOUS_CONFIG.set(RendezvousConfig { server, force_relay });
+}
+
+/// 已配置的会合服务器地址
+pub fn server_addr() -> Option<SocketAddr> {
+    RENDEZVOUS_CONFIG.get().and_then(|config| config.server)
+}
+
+fn force_relay() -> bool {
+    RENDEZVOUS_CONFIG.get().is_some_and(|config| config.force_relay)
+}
+
+/// 如果输入是房间号（纯数字）且配置了会合服务器，返回完整的房间ID
+pub fn room_id_from_input(input: &str) -> Option<String> {
+    server_addr()?;
+    let digits = input.trim().strip_prefix(ROOM_ID_PREFIX).unwrap_or(input.trim());
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}{}", ROOM_ID_PREFIX, digits))
+}
+
+/// 一次连接过程中的会合状态（接收线程写入，发送线程读取）
+#[derive(Debug, Default)]
+pub struct RendezvousState {
+    pub public_addr: Option<SocketAddr>, // 服务器看到的本机公网地址
+    pub peer: Option<SocketAddr>,        // 对方地址（打洞成功后为实际通信的地址）
+    pub punched: bool,                   // 是否收到过对方的打洞报文
+    pub relay_ready: bool,               // 服务器中继是否就绪
+    pub room_not_found: bool,            // 服务器上没有这个房间
+    pub room_taken: bool,                // 房间已经有别的客户端
+    pub secret: u64,                     // 主机：服务器分配的房间密钥（登记成功前为 0）
+}
+
+pub type SharedRendezvousState = Arc<Mutex<RendezvousState>>;
+
+fn send_control(socket: &Arc<Mutex<UdpSocket>>, message: &RendezvousMessage, addr: SocketAddr) {
+    let data = match protocol::encode_rendezvous(message) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("[中继] 序列化控制报文失败: {}", e);
+            return;
+        }
+    };
+    let Ok(socket_guard) = socket.lock() else {
+        return;
+    };
+    if let Err(e) = socket_guard.send_to(&data, addr) {
+        eprintln!("[中继] 发送控制报文到 {} 失败: {}", addr, e);
+    }
+}
+
+/// 在一段时间内持续向对方发送打洞报文
+fn spawn_punch_burst(socket: Arc<Mutex<UdpSocket>>, room_id: String, peer: SocketAddr) {
+    thread::spawn(move || {
+        let punch = RendezvousMessage::Punch { room_id };
+        let started = Instant::now();
+        while started.elapsed() < PUNCH_TIMEOUT {
+            send_control(&socket, &punch, peer);
+            thread::sleep(PUNCH_INTERVAL);
+        }
+    });
+}
+
+/// 处理收到的控制报文（接收线程在保存远程地址之前调用，控制报文不会被当成游戏对端）
+pub fn handle_datagram(
+    data: &[u8],
+    from: SocketAddr,
+    socket: &Arc<Mutex<UdpSocket>>,
+    room_id: &str,
+    is_host: bool,
+    state: &SharedRendezvousState,
+) {
+    let message = match protocol::decode_rendezvous(data) {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("[中继] 丢弃来自 {} 的非法控制报文: {}", from, e);
+            return;
+        }
+    };
+    if message.room_id().is_some_and(|id| id != room_id) {
+        // 其他房间的报文（例如上一次连接残留的打洞报文）
+        return;
+    }
+    let from_server = server_addr() == Some(from);
+    let Ok(mut state) = state.lock() else {
+        return;
+    };
+    match message {
+        RendezvousMessage::Registered { public_addr, secret } if from_server => {
+            if state.public_addr != Some(public_addr) {
+                // 调试输出已禁用: println!("[主机] 已在会合服务器登记，公网地址: {}", public_addr);
+                state.public_addr = Some(public_addr);
+            }
+            state.secret = secret;
+        }
+        RendezvousMessage::PeerInfo { peer, .. } if from_server => {
+            if state.peer != Some(peer) {
+                state.peer = Some(peer);
+                state.punched = false;
+                spawn_punch_burst(socket.clone(), room_id.to_string(), peer);
+            }
+        }
+        RendezvousMessage::Punch { .. } => {
+            if is_host {
+                // 主机回应打洞报文，客户端收到后即确认直连可用
+                send_control(socket, &RendezvousMessage::Punch { room_id: room_id.to_string() }, from);
+            }
+            state.punched = true;
+            state.peer = Some(from);
+        }
+        RendezvousMessage::RelayReady { .. } if from_server => {
+            state.relay_ready = true;
+        }
+        RendezvousMessage::RoomNotFound { .. } if from_server => {
+            state.room_not_found = true;
+        }
+        RendezvousMessage::RoomTaken { .. } if from_server => {
+            state.room_taken = true;
+        }
+        other => {
+            eprintln!("[中继] 忽略来自 {} 的控制报文: {:?}", from, other);
+        }
+    }
+}
+
+/// 主机：定期向会合服务器登记房间（服务器未配置时不做任何事）
+/// 登记时带上服务器分配的房间密钥，主机的公网地址变化后服务器凭它确认还是同一个主机
+pub fn start_host_registration(socket: Arc<Mutex<UdpSocket>>, room_id: String, is_running: Arc<AtomicBool>, state: SharedRendezvousState) {
+    let Some(server) = server_addr() else {
+        return;
+    };
+    thread::spawn(move || {
+        while is_running.load(Ordering::Relaxed) {
+            let secret = state.lock().map(|state| state.secret).unwrap_or(0);
+            let register = RendezvousMessage::Register { room_id: room_id.clone(), secret };
+            send_control(&socket, &register, server);
+            thread::sleep(REGISTER_INTERVAL);
+        }
+    });
+}
+
+/// 客户端连接过程中用到的 socket 和状态
+struct ControlChannel<'a> {
+    socket: &'a Arc<Mutex<UdpSocket>>,
+    is_running: &'a AtomicBool,
+    state: &'a SharedRendezvousState,
+}
+
+impl ControlChannel<'_> {
+    /// 每隔一段时间重发控制报文，直到条件满足、超时或网络线程停止
+    fn wait_until(
+        &self,
+        message: &RendezvousMessage,
+        target: SocketAddr,
+        interval: Duration,
+        timeout: Duration,
+        done: impl Fn(&RendezvousState) -> bool,
+    ) -> bool {
+        let started = Instant::now();
+        let mut last_sent: Option<Instant> = None;
+        while started.elapsed() < timeout && self.is_running.load(Ordering::Relaxed) {
+            if self.state.lock().is_ok_and(|state| done(&state)) {
+                return true;
+            }
+            if last_sent.is_none_or(|t| t.elapsed() >= interval) {
+                last_sent = Some(Instant::now());
+                send_control(self.socket, message, target);
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        self.state.lock().is_ok_and(|state| done(&state))
+    }
+}
+
+/// 客户端：通过会合服务器找到主机，先打洞，失败后改用中继
+/// 返回之后游戏报文应发送到的地址（主机地址或服务器地址）
+pub fn resolve_host(
+    socket: &Arc<Mutex<UdpSocket>>,
+    room_id: &str,
+    is_running: &AtomicBool,
+    state: &SharedRendezvousState,
+) -> Option<SocketAddr> {
+    let server = server_addr()?;
+    let room_id = room_id.to_string();
+    let channel = ControlChannel { socket, is_running, state };
+
+    // 1. 向服务器请求主机地址
+    let connect = RendezvousMessage::Connect { room_id: room_id.clone() };
+    let found = channel.wait_until(&connect, server, RETRY_INTERVAL, CONNECT_TIMEOUT, |s| {
+        s.peer.is_some() || s.room_not_found || s.room_taken
+    });
+    if state.lock().is_ok_and(|s| s.room_taken) {
+        eprintln!("[客户端] 会合服务器 {} 上的房间 {} 已经有客户端了", server, room_id);
+        return None;
+    }
+    let peer = state.lock().ok().and_then(|s| if s.room_not_found { None } else { s.peer });
+    let Some(peer) = peer.filter(|_| found) else {
+        eprintln!("[客户端] 会合服务器 {} 上找不到房间 {}（主机未登记或已过期）", server, room_id);
+        return None;
+    };
+
+    // 2. 打洞（收到 PeerInfo 时已经开始发送打洞报文，这里继续发并等待对方的回应）
+    if !force_relay() {
+        let punch = RendezvousMessage::Punch { room_id: room_id.clone() };
+        if channel.wait_until(&punch, peer, PUNCH_INTERVAL, PUNCH_TIMEOUT, |s| s.punched) {
+            let direct = state.lock().ok().and_then(|s| s.peer).unwrap_or(peer);
+            // 调试输出已禁用: println!("[客户端] 打洞成功，直连主机 {}", direct);
+            return Some(direct);
+        }
+    }
+
+    // 3. 打洞失败，改用中继
+    eprintln!("[客户端] 无法直连主机 {}，改用会合服务器 {} 中继", peer, server);
+    let relay = RendezvousMessage::RelayRequest { room_id: room_id.clone() };
+    if channel.wait_until(&relay, server, RETRY_INTERVAL, RELAY_TIMEOUT, |s| s.relay_ready) {
+        return Some(server);
+    }
+    eprintln!("[客户端] 会合服务器 {} 没有响应中继请求", server);
+    None
+}
@@ -0,0 +1,321 @@
+// 防守方修墙
+//
+// 砖块碎了以后原本要到下一局 setup_game 才会恢复。防守方站在碎砖旁边按住 H，站着不动 repair_seconds 秒，
+// 就能把离自己最近（repair_reach 以内）的一块碎砖修好：恢复完整耐久，两个视角中都变回完好的样子。
+// 每回合最多修 repairs_per_round 块（对局规则）；松开按键、移动、下蹲/侧躲或被打中都会打断修补，进度清零。
+// 联机时修好后发送 WallRepair（重复发送几次，按 repair 去重），对方修好自己视角中的同一块砖，
+// 进攻方按对局规则检查次数和防守方离砖块的距离。修好的砖块记在 BrokenWallData 中，换角色重建墙体时保持完好。
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::game_rules::GameRules;
+use crate::gameplay::{BulletHole, DefenderAI, DodgeAction, Health, HumanoidPart, RoundInfo, ViewConfig, WallBackground, WallSegment};
+use crate::network_game::NetworkManager;
+use crate::{BrickKey, BrokenWallData, FontResource, PlayerId, RoomInfo};
+use bevy_sniper_duel::protocol::NetworkMessage;
+
+/// 修墙的按键（按住）
+const REPAIR_KEY: KeyCode = KeyCode::KeyH;
+/// WallRepair 的发送次数（防止丢包，接收方按 repair 去重）
+const REPAIR_SEND_REPEAT: usize = 3;
+/// 防守方离开修补位置超过这个距离就打断修补（像素）
+const REPAIR_MOVE_TOLERANCE: f32 = 1.0;
+/// 按位置找同一块砖时允许的误差（像素）
+const BRICK_MATCH_DISTANCE: f32 = 1.0;
+/// 修补进度条的大小（防守方视角中，画在砖块上）
+const PROGRESS_BAR_SIZE: Vec2 = Vec2::new(36.0, 5.0);
+/// 进度条的 Z（在墙和人物之上）
+const PROGRESS_BAR_Z: f32 = 3.0;
+
+/// 本地防守方的修墙状态
+#[derive(Resource, Default, Debug)]
+pub struct WallRepair {
+    pub used: u32,                  // 本回合已修好的砖块数
+    pub target: Option<BrickKey>,   // 正在修补的砖块
+    pub progress: f32,              // 已经修了多少秒
+    anchor: Vec2,                   // 开始修补时防守方的位置
+    start_health: f32,              // 开始修补时防守方的血量（掉血说明被打中了）
+}
+
+impl WallRepair {
+    fn cancel(&mut self) {
+        self.target = None;
+        self.progress = 0.0;
+    }
+}
+
+/// 修补进度条（防守方视角）
+#[derive(Component)]
+pub struct RepairProgressBar;
+
+/// 修墙次数和进度（防守方视角）
+#[derive(Component)]
+pub struct RepairText;
+
+/// 玩家自己操作的防守方实体（不含电脑防守方和身体部位）
+type HumanDefenderFilter = (Without<DefenderAI>, Without<HumanoidPart>);
+
+/// 修墙要改动的实体：砖块、砖块后面的黑色背景、防守方视角的弹孔
+pub type BrickRepairQueries<'w, 's> = (
+    Query<'w, 's, (&'static mut WallSegment, &'static mut Sprite, &'static mut Visibility, &'static Transform)>,
+    Query<'w, 's, (&'static WallBackground, &'static mut Visibility), Without<WallSegment>>,
+    Query<'w, 's, (Entity, &'static BulletHole)>,
+);
+
+/// 本回合是否还能修墙
+fn can_repair(round_info: &RoundInfo) -> bool {
+    !round_info.is_switching && !round_info.round_timer.finished()
+}
+
+/// 离 position 最近、够得着的碎砖（钢板不会碎，不用考虑）
+fn nearest_broken_brick(position: Vec2, reach: f32, segments: &BrickRepairQueries) -> Option<BrickKey> {
+    segments
+        .0
+        .iter()
+        .filter(|(segment, ..)| segment.damaged && segment.spec.hit_points().is_some())
+        .map(|(segment, _, _, transform)| (segment.depth, transform.translation.truncate()))
+        .filter(|(_, brick_pos)| brick_pos.distance(position) <= reach)
+        .min_by(|a, b| a.1.distance(position).total_cmp(&b.1.distance(position)))
+}
+
+/// 把 brick 在本机所有视角中恢复成完好的砖块，返回是否真的修好了碎砖
+pub fn restore_brick(
+    commands: &mut Commands,
+    brick: BrickKey,
+    (segment_query, background_query, hole_query): &mut BrickRepairQueries,
+    broken_wall_data: Option<&mut BrokenWallData>,
+    is_host: Option<bool>,
+) -> bool {
+    let (depth, position) = brick;
+    let mut restored = false;
+    for (mut segment, mut sprite, mut visibility, transform) in segment_query.iter_mut() {
+        if !segment.damaged || segment.depth != depth || transform.translation.truncate().distance(position) > BRICK_MATCH_DISTANCE {
+            continue;
+        }
+        segment.repair();
+        sprite.color = segment.intact_color;
+        *visibility = Visibility::Visible;
+        restored = true;
+    }
+    if !restored {
+        return false;
+    }
+    // wall_visibility_update_system 在没有碎砖时不运行，砖块后面的背景在这里直接恢复
+    for (background, mut visibility) in background_query.iter_mut() {
+        if background.depth == depth && background.position.distance(position) < BRICK_MATCH_DISTANCE {
+            *visibility = Visibility::Visible;
+        }
+    }
+    for (entity, hole) in hole_query.iter() {
+        if hole.depth == depth && hole.position.distance(position) < BRICK_MATCH_DISTANCE {
+            commands.entity(entity).despawn();
+        }
+    }
+    if let (Some(broken_data), Some(is_host)) = (broken_wall_data, is_host) {
+        broken_data.record_repaired(is_host, brick);
+    }
+    true
+}
+
+/// 防守方：按住 H 修补身边的碎砖，移动、躲避、被打中或松开按键都会打断（联机时修好后通知对方）
+pub fn defender_repair_system(
+    mut commands: Commands,
+    (time, keyboard_input): (Res<Time>, Res<ButtonInput<KeyCode>>),
+    (mut repair, mut broken_wall_data): (ResMut<WallRepair>, Option<ResMut<BrokenWallData>>),
+    (round_info, rules, view_config): (Res<RoundInfo>, Res<GameRules>, Res<ViewConfig>),
+    defender_query: Query<(&Transform, &PlayerId, &DodgeAction, &Health), HumanDefenderFilter>,
+    mut bricks: BrickRepairQueries,
+    (room_info, network_manager): (Option<Res<RoomInfo>>, Option<Res<NetworkManager>>),
+) {
+    if !keyboard_input.pressed(REPAIR_KEY) || !can_repair(&round_info) || repair.used >= rules.repairs_per_round {
+        repair.cancel();
+        return;
+    }
+    // 电脑防守方不修墙；联机时只有本机是防守方时才能修
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+    let Some((transform, _, dodge_action, health)) = defender_query.iter().find(|(_, id, ..)| **id == defender) else {
+        repair.cancel();
+        return;
+    };
+    let is_network_mode = room_info.as_ref().is_some_and(|room| room.is_connected);
+    if is_network_mode {
+        let local_player = if room_info.as_ref().is_some_and(|room| room.is_host) { PlayerId::Player1 } else { PlayerId::Player2 };
+        if local_player != defender || view_config.is_attacker_view {
+            repair.cancel();
+            return;
+        }
+    }
+
+    let position = transform.translation.truncate();
+    let interrupted = *dodge_action != DodgeAction::None
+        || position.distance(repair.anchor) > REPAIR_MOVE_TOLERANCE
+        || health.0 < repair.start_health;
+    if repair.target.is_some() && interrupted {
+        // 调试输出已禁用: println!("[修墙] {:?} 的修补被打断", defender);
+        repair.cancel();
+        return;
+    }
+    if repair.target.is_none() {
+        if *dodge_action != DodgeAction::None {
+            return;
+        }
+        let Some(brick) = nearest_broken_brick(position, rules.repair_reach, &bricks) else {
+            return;
+        };
+        repair.target = Some(brick);
+        repair.progress = 0.0;
+        repair.anchor = position;
+        repair.start_health = health.0;
+    }
+
+    repair.progress += time.delta_seconds();
+    if repair.progress < rules.repair_seconds {
+        return;
+    }
+    let Some(brick) = repair.target else {
+        return;
+    };
+    repair.cancel();
+    let is_host = room_info.as_ref().map(|room| room.is_host);
+    if !restore_brick(&mut commands, brick, &mut bricks, broken_wall_data.as_deref_mut(), is_host) {
+        return;
+    }
+    let repair_index = repair.used;
+    repair.used += 1;
+    // 调试输出已禁用: println!("[修墙] {:?} 修好了砖块 {:?}（第 {} 次）", defender, brick, repair.used);
+
+    if let Some(network_manager) = network_manager.as_ref().filter(|_| is_network_mode) {
+        for _ in 0..REPAIR_SEND_REPEAT {
+            crate::network_game::send_network_message(
+                network_manager,
+                NetworkMessage::WallRepair { player_id: defender, depth: brick.0, position: brick.1.to_array(), repair: repair_index },
+            );
+        }
+    }
+}
+
+/// 接收对方防守方修好的砖块，在自己的视角中恢复
+pub fn handle_remote_repair_system(
+    mut commands: Commands,
+    network_manager: Res<NetworkManager>,
+    (room_info, round_info, rules): (Res<RoomInfo>, Res<RoundInfo>, Res<GameRules>),
+    (mut anti_cheat, time): (ResMut<crate::anti_cheat::AntiCheatMonitor>, Res<Time>),
+    defender_query: Query<(&Transform, &PlayerId), Without<HumanoidPart>>,
+    mut bricks: BrickRepairQueries,
+    mut broken_wall_data: Option<ResMut<BrokenWallData>>,
+) {
+    if !room_info.is_connected {
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    anti_cheat.sync_round(round_info.current_attacker, now);
+    let peer = network_manager.remote_addr.lock().ok().and_then(|addr| *addr);
+    let local_player = if network_manager.is_host { PlayerId::Player1 } else { PlayerId::Player2 };
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+    let defender_pos = defender_query.iter().find(|(_, id)| **id == defender).map(|(transform, _)| transform.translation.truncate());
+
+    let Ok(mut queue) = network_manager.message_queue.lock() else {
+        return;
+    };
+    let mut messages_to_keep = Vec::new();
+    for msg in queue.drain(..) {
+        match msg {
+            NetworkMessage::WallRepair { player_id, depth, position, repair } => {
+                // 只接受对方作为当前防守方修的砖（换回合前的迟到消息直接丢弃）
+                if player_id != defender || player_id == local_player || !can_repair(&round_info) {
+                    continue;
+                }
+                let brick_pos = Vec2::from(position);
+                let distance = defender_pos.map_or(0.0, |pos| pos.distance(brick_pos));
+                if anti_cheat.check_repair(&rules, now, repair, distance, peer) {
+                    restore_brick(&mut commands, (depth, brick_pos), &mut bricks, broken_wall_data.as_deref_mut(), Some(room_info.is_host));
+                }
+            }
+            _ => messages_to_keep.push(msg),
+        }
+    }
+    queue.extend(messages_to_keep);
+}
+
+/// 修补进度条：画在正在修的砖块上，修好或被打断时移除
+pub fn update_repair_progress_system(
+    mut commands: Commands,
+    repair: Res<WallRepair>,
+    rules: Res<GameRules>,
+    mut bar_query: Query<(Entity, &mut Transform, &mut Sprite), With<RepairProgressBar>>,
+) {
+    let Some((_, position)) = repair.target else {
+        for (entity, ..) in bar_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+    let fraction = (repair.progress / rules.repair_seconds).clamp(0.0, 1.0);
+    let width = PROGRESS_BAR_SIZE.x * fraction;
+    // 进度条从左往右长
+    let translation = Vec3::new(position.x - (PROGRESS_BAR_SIZE.x - width) / 2.0, position.y, PROGRESS_BAR_Z);
+    if let Some((_, mut transform, mut sprite)) = bar_query.iter_mut().next() {
+        transform.translation = translation;
+        sprite.custom_size = Some(Vec2::new(width, PROGRESS_BAR_SIZE.y));
+        return;
+    }
+    commands.spawn((
+        RepairProgressBar,
+        SpriteBundle {
+            sprite: Sprite { color: Color::rgb(0.3, 0.9, 0.3), custom_size: Some(Vec2::new(width, PROGRESS_BAR_SIZE.y)), ..default() },
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        RenderLayers::layer(1), // 只在防守方视角
+    ));
+}
+
+/// 换回合时恢复修墙次数
+pub fn reset_repairs_on_switch(mut repair: ResMut<WallRepair>) {
+    *repair = WallRepair::default();
+}
+
+/// 创建修墙文字（放在道具文字下方）
+pub fn spawn_repair_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(String::new(), TextStyle { font, font_size: 16.0, color: Color::GRAY }),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(14.0),
+                left: Val::Percent(50.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(9999), // 与防守方其他UI一样最后渲染
+            transform: Transform::from_xyz(-100.0, 0.0, 0.0),
+            ..default()
+        },
+        RepairText,
+        crate::DefenderUI,
+    ));
+}
+
+/// 更新修墙文字（只在内容变化时写入）
+pub fn update_repair_display_system(
+    repair: Res<WallRepair>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<RepairText>>,
+) {
+    let remaining = rules.repairs_per_round.saturating_sub(repair.used);
+    let summary = if repair.target.is_some() {
+        format!("修墙中 {:.0}%", (repair.progress / rules.repair_seconds).min(1.0) * 100.0)
+    } else if remaining > 0 {
+        format!("修墙: 剩余 {}/{}（站在碎砖旁按住 H）", remaining, rules.repairs_per_round)
+    } else {
+        format!("修墙: 剩余 0/{}", rules.repairs_per_round)
+    };
+    for mut text in text_query.iter_mut() {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value == summary {
+            continue;
+        }
+        section.value = summary.clone();
+        section.style.font = font_resource.font.clone();
+    }
+}
@@ -3,6 +3,7 @@ use crate::AppState;
 use crate::FontResource;
 use crate::RoomInfo;
 use crate::network_game::{NetworkManager, NetworkMessage};
+use crate::arena::{self, Arena, MapChoice};
 use bincode;
 
 /// 房间UI组件
@@ -27,12 +28,20 @@ pub struct PlayerCountText;
 #[derive(Component)]
 pub struct HostIpText;
 
+/// 房主切换地图的按钮和上面的文字
+#[derive(Component)]
+pub struct MapSelectButton;
+
+#[derive(Component)]
+pub struct MapSelectText;
+
 /// 设置创建房间页面（等待页面）- 简化版（自动发现）
 pub fn setup_creating_room(
     mut commands: Commands,
     font_resource: Res<FontResource>,
     room_info: Res<RoomInfo>,
     network_manager: Res<NetworkManager>,
+    map_choice: Res<MapChoice>,
 ) {
     let font = font_resource.font.clone();
     // 从网络管理器获取房间ID
@@ -179,6 +188,38 @@ pub fn setup_creating_room(
             PlayerCountText,
         ));
         
+        // 地图选择按钮（开始游戏时随 StartGame 发给客户端）
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: Color::rgb(0.4, 0.4, 0.45).into(),
+                ..default()
+            },
+            MapSelectButton,
+        )).with_children(|button| {
+            button.spawn((
+                TextBundle {
+                    text: Text::from_sections([TextSection::new(
+                        format!("地图: {}", map_choice.label()),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                    )]),
+                    ..default()
+                },
+                MapSelectText,
+            ));
+        });
+        
         // 开始游戏按钮（只有房主可以看到）
         parent.spawn((
             ButtonBundle {
@@ -321,7 +362,7 @@ pub fn setup_joining_room_simple(
         // IP输入提示文字
         parent.spawn(TextBundle {
             text: Text::from_sections([TextSection::new(
-                "输入IP地址（格式：IP:端口，使用数字键和分号键输入，按Enter确认）",
+                "输入IP地址（格式：IP:端口，使用数字键和分号键输入，按Enter确认）；配置了会合服务器时也可只输入房间号数字",
                 TextStyle {
                     font: font.clone(),
                     font_size: 18.0,
@@ -694,7 +735,12 @@ pub fn update_host_ip_display(
     mut ip_text_query: Query<&mut Text, (With<HostIpText>, Without<RoomCodeText>)>,
 ) {
     if let Some(local_ip) = network_manager.local_ip.lock().unwrap().as_ref() {
-        let ip_text = format!("其他玩家请连接到: {}:12345", local_ip);
+        let mut ip_text = format!("其他玩家请连接到: {}:12345", local_ip);
+        // 配置了会合服务器时，其他网络的玩家可以直接输入房间号数字
+        if crate::rendezvous::server_addr().is_some() {
+            let room_id = network_manager.room_id.lock().unwrap().clone();
+            ip_text.push_str(&format!("\n或输入房间号: {}", room_id.trim_start_matches("ROOM_")));
+        }
         for mut text in ip_text_query.iter_mut() {
             if text.sections.len() > 0 {
                 text.sections[0].value = ip_text.clone();
@@ -763,6 +809,7 @@ pub fn handle_room_buttons_creating(
     mut network_manager: ResMut<NetworkManager>,
     mut app_state: ResMut<NextState<AppState>>,
     mut room_info: ResMut<RoomInfo>,
+    (game_rules, current_arena): (Res<crate::game_rules::GameRules>, Res<Arena>),
 ) {
     for (interaction, entity) in interaction_query.iter() {
         if *interaction == Interaction::Pressed {
@@ -798,10 +845,10 @@ pub fn handle_room_buttons_creating(
                             // 房主点击开始游戏
                             room_info.is_connected = true;
                             // 调试输出已禁用: println!("[房主] 开始游戏，remote_addr: {:?}, socket: {:?}", *remote_addr, if network_manager.socket.is_some() { "已设置" } else { "未设置" });
-                            // 发送开始游戏消息给客户端（发送多次以确保客户端收到）
+                            // 发送开始游戏消息给客户端（附带本机的对局规则和地图，发送多次以确保客户端收到）
                             drop(remote_addr); // 释放锁
                             for i in 0..3 {
-                                crate::network_game::send_network_message(&*network_manager, NetworkMessage::StartGame);
+                                crate::network_game::send_network_message(&*network_manager, NetworkMessage::StartGame { rules: game_rules.clone(), map: current_arena.source() });
                                 // 调试输出已禁用: println!("[房主] 已发送第 {} 次 StartGame 消息", i + 1);
                             }
                             // 调试输出已禁用: println!("[房主] StartGame消息已发送，切换到Playing状态");
@@ -825,6 +872,21 @@ pub fn handle_room_buttons_creating(
     }
 }
 
+/// 房主在创建房间的界面切换地图
+pub fn handle_room_map_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<MapSelectButton>)>,
+    mut text_query: Query<&mut Text, With<MapSelectText>>,
+    (mut map_choice, mut current_arena): (ResMut<MapChoice>, ResMut<Arena>),
+) {
+    if !interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+    arena::select_next_map(&mut map_choice, &mut current_arena);
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("地图: {}", map_choice.label());
+    }
+}
+
 /// 设置客户端在房间内等待的UI
 pub fn setup_in_room(
     mut commands: Commands,
@@ -903,6 +965,23 @@ pub fn setup_in_room(
             ..default()
         });
         
+        // 地图提示
+        parent.spawn(TextBundle {
+            text: Text::from_sections([TextSection::new(
+                "地图由房主选择，开始游戏时同步",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    color: Color::CYAN,
+                },
+            )]),
+            style: Style {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        });
+        
         // 等待提示
         parent.spawn(TextBundle {
             text: Text::from_sections([TextSection::new(
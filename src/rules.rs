@@ -0,0 +1,283 @@
+// 对局规则：血量、回合时间、子弹数、冷却、伤害、速度等平衡数值
+//
+// 规则以 RON 文件保存在 assets/rules/ 下，每个文件是一个命名预设（classic、hardcore……），
+// 文件中省略的字段取经典规则的数值。联机时主机在 StartGame 中把自己的规则发给客户端，
+// 因此这个模块和 protocol 一起编译进库，解码时也要对收到的规则做合法性检查。
+
+use bevy::prelude::Resource;
+use serde::{Serialize, Deserialize};
+
+/// 每回合子弹数的上限（子弹图标、RoundInfoSync 校验都依赖这个范围）
+pub const MAX_BULLETS_PER_ROUND: i32 = 20;
+
+/// 防守方每回合道具次数的上限（DefenderUtility 校验依赖这个范围）
+pub const MAX_UTILITY_CHARGES: u32 = 5;
+
+/// 进攻方每回合热成像扫描次数的上限
+pub const MAX_THERMAL_SCANS: u32 = 5;
+
+/// 防守方每回合修墙次数的上限（WallRepair 校验依赖这个范围）
+pub const MAX_REPAIRS_PER_ROUND: u32 = 10;
+
+/// 赛制中回合数、淘汰次数的上限
+pub const MAX_MATCH_LENGTH: u32 = 20;
+
+/// 比赛赛制
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchFormat {
+    /// 经典：一直打到一方血量归零
+    #[default]
+    Elimination,
+    /// 每人进攻 turns 个回合，累计伤害多者获胜；打平进入加时赛，先拉开伤害差距者获胜
+    /// 血量跨回合保留，中途被打到 0 血直接判负
+    AttackTurns { turns: u32 },
+    /// 先淘汰对方 eliminations 次者获胜，每个进攻回合开始时双方回满血
+    FirstTo { eliminations: u32 },
+}
+
+impl MatchFormat {
+    /// 主菜单中可以切换的赛制（依次循环）
+    pub const CHOICES: [MatchFormat; 3] = [
+        MatchFormat::Elimination,
+        MatchFormat::AttackTurns { turns: 3 },
+        MatchFormat::FirstTo { eliminations: 3 },
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            MatchFormat::Elimination => "经典淘汰".to_string(),
+            MatchFormat::AttackTurns { turns } => format!("每人进攻{}回合", turns),
+            MatchFormat::FirstTo { eliminations } => format!("先淘汰{}次", eliminations),
+        }
+    }
+
+    /// 下一个可选赛制（不在列表中的自定义赛制回到第一个）
+    pub fn next(&self) -> MatchFormat {
+        let index = Self::CHOICES.iter().position(|format| format == self);
+        match index {
+            Some(index) => Self::CHOICES[(index + 1) % Self::CHOICES.len()],
+            None => Self::CHOICES[0],
+        }
+    }
+
+    /// 一名玩家最多淘汰对方几次（只有先淘汰N次赛制计淘汰，达到 N 次比赛结束）
+    pub fn max_eliminations(&self) -> u32 {
+        match self {
+            MatchFormat::FirstTo { eliminations } => *eliminations,
+            MatchFormat::Elimination | MatchFormat::AttackTurns { .. } => 0,
+        }
+    }
+
+    /// 每个进攻回合开始时是否回满血
+    pub fn resets_health(&self) -> bool {
+        matches!(self, MatchFormat::FirstTo { .. })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (field, value) = match self {
+            MatchFormat::Elimination => return Ok(()),
+            MatchFormat::AttackTurns { turns } => ("match_format.turns", *turns),
+            MatchFormat::FirstTo { eliminations } => ("match_format.eliminations", *eliminations),
+        };
+        if !(1..=MAX_MATCH_LENGTH).contains(&value) {
+            return Err(format!("{} 超出范围 [1, {}]: {}", field, MAX_MATCH_LENGTH, value));
+        }
+        Ok(())
+    }
+}
+
+/// 命中判定方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HitResolution {
+    /// 经典：开火瞬间按准星位置判定命中，子弹只是视觉效果
+    #[default]
+    Crosshair,
+    /// 弹道：子弹飞到瞄准点时按防守方当时的位置判定，开火后仍然可以躲开
+    Projectile,
+}
+
+impl HitResolution {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HitResolution::Crosshair => "准星判定",
+            HitResolution::Projectile => "弹道判定",
+        }
+    }
+
+    pub fn next(&self) -> HitResolution {
+        match self {
+            HitResolution::Crosshair => HitResolution::Projectile,
+            HitResolution::Projectile => HitResolution::Crosshair,
+        }
+    }
+}
+
+/// 对局规则
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GameRules {
+    pub player_hp: f32,
+    pub round_time_seconds: f32,
+    pub bullets_per_round: i32,
+    pub dodge_cooldown_seconds: f32,
+    pub action_duration_seconds: f32,     // 下蹲/侧躲持续时间
+    pub shoot_cooldown_seconds: f32,      // 两次射击的最短间隔
+    pub damage_head: f32,
+    pub damage_torso: f32,
+    pub damage_legs: f32,
+    pub bullet_speed: f32,
+    pub player_move_speed: f32,           // 防守方按该速度的 80% 移动
+    pub aim_speed: f32,
+    pub side_dodge_distance: f32,
+    pub crosshair_damage_range: f32,      // 准心附近墙破坏范围
+    pub match_format: MatchFormat,
+    pub hit_resolution: HitResolution,
+    pub ballistics: bool,                 // 子弹下坠和风偏
+    pub gravity: f32,                     // 子弹下坠的加速度（像素/秒²）
+    pub wind_strength: f32,               // 每回合随机风的最大加速度（像素/秒²）
+    pub utility_charges: u32,             // 防守方每回合可以使用道具的次数（0 表示不能用道具）
+    pub smoke_seconds: f32,               // 烟雾弹持续时间
+    pub decoy_seconds: f32,               // 假人持续时间
+    pub thermal_scans: u32,               // 进攻方每回合热成像扫描次数（0 表示不能扫描）
+    pub thermal_seconds: f32,             // 热成像轮廓显示的时间
+    pub thermal_strength: f32,            // 热成像轮廓的不透明度（0 到 1）
+    pub thermal_cooldown_seconds: f32,    // 回合开始后、两次扫描之间要等待的时间
+    pub footstep_strength: f32,           // 脚步提示的不透明度（0 表示关闭）
+    pub footstep_interval_seconds: f32,   // 两次脚步提示的最短间隔
+    pub repairs_per_round: u32,           // 防守方每回合可以修补的砖块数（0 表示不能修墙）
+    pub repair_seconds: f32,              // 修补一块砖要站着不动的时间
+    pub repair_reach: f32,                // 能修补的砖块离防守方中心的最大距离
+    pub stamina_max: f32,                 // 防守方体力上限
+    pub sprint_multiplier: f32,           // 冲刺时的移动速度倍数
+    pub sprint_stamina_per_second: f32,   // 冲刺每秒消耗的体力
+    pub dodge_stamina: f32,               // 侧躲一次消耗的体力（不够时只能下蹲）
+    pub stand_stamina: f32,               // 下蹲后站起来消耗的体力
+    pub stamina_regen_per_second: f32,    // 站着不动时每秒恢复的体力
+}
+
+/// 经典规则（内置默认值，规则文件缺失或字段省略时使用）
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            player_hp: 100.0,
+            round_time_seconds: 30.0,
+            bullets_per_round: 3,
+            dodge_cooldown_seconds: 5.0,
+            action_duration_seconds: 1.0,
+            shoot_cooldown_seconds: 1.0,
+            damage_head: 100.0,
+            damage_torso: 40.0,
+            damage_legs: 30.0,
+            bullet_speed: 1200.0,
+            player_move_speed: 300.0,
+            aim_speed: 300.0,
+            side_dodge_distance: 30.0,
+            crosshair_damage_range: 50.0,
+            match_format: MatchFormat::Elimination,
+            hit_resolution: HitResolution::Crosshair,
+            ballistics: false,
+            gravity: 900.0,
+            wind_strength: 400.0,
+            utility_charges: 2,
+            smoke_seconds: 5.0,
+            decoy_seconds: 8.0,
+            thermal_scans: 1,
+            thermal_seconds: 1.5,
+            thermal_strength: 0.6,
+            thermal_cooldown_seconds: 5.0,
+            footstep_strength: 0.8,
+            footstep_interval_seconds: 0.4,
+            repairs_per_round: 2,
+            repair_seconds: 2.5,
+            repair_reach: 60.0,
+            stamina_max: 100.0,
+            sprint_multiplier: 1.5,
+            sprint_stamina_per_second: 35.0,
+            dodge_stamina: 30.0,
+            stand_stamina: 15.0,
+            stamina_regen_per_second: 25.0,
+        }
+    }
+}
+
+impl GameRules {
+    /// 规则的指纹（编码后的 FNV-1a 哈希），联机时用来核对双方的规则是否一致
+    pub fn fingerprint(&self) -> u64 {
+        let bytes = bincode::serialize(self).unwrap_or_default();
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    /// 从 RON 文本解析规则并检查取值
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let rules: GameRules = ron::from_str(text).map_err(|e| format!("规则格式错误: {}", e))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// 把规则写成 RON 文本（保存预设用）
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| format!("规则序列化失败: {}", e))
+    }
+
+    /// 检查规则取值：浮点数必须有限且在合理范围内（对方发来的规则同样要经过这里）
+    pub fn validate(&self) -> Result<(), String> {
+        check("player_hp", self.player_hp, 1.0, 10000.0)?;
+        check("round_time_seconds", self.round_time_seconds, 5.0, 600.0)?;
+        if !(1..=MAX_BULLETS_PER_ROUND).contains(&self.bullets_per_round) {
+            return Err(format!(
+                "bullets_per_round 超出范围 [1, {}]: {}",
+                MAX_BULLETS_PER_ROUND, self.bullets_per_round
+            ));
+        }
+        check("dodge_cooldown_seconds", self.dodge_cooldown_seconds, 0.0, 60.0)?;
+        check("action_duration_seconds", self.action_duration_seconds, 0.1, 10.0)?;
+        check("shoot_cooldown_seconds", self.shoot_cooldown_seconds, 0.1, 10.0)?;
+        check("damage_head", self.damage_head, 0.0, 10000.0)?;
+        check("damage_torso", self.damage_torso, 0.0, 10000.0)?;
+        check("damage_legs", self.damage_legs, 0.0, 10000.0)?;
+        check("bullet_speed", self.bullet_speed, 100.0, 10000.0)?;
+        check("player_move_speed", self.player_move_speed, 10.0, 2000.0)?;
+        check("aim_speed", self.aim_speed, 10.0, 2000.0)?;
+        check("side_dodge_distance", self.side_dodge_distance, 0.0, 200.0)?;
+        check("crosshair_damage_range", self.crosshair_damage_range, 0.0, 500.0)?;
+        check("gravity", self.gravity, 0.0, 5000.0)?;
+        check("wind_strength", self.wind_strength, 0.0, 5000.0)?;
+        if self.utility_charges > MAX_UTILITY_CHARGES {
+            return Err(format!("utility_charges 超出范围 [0, {}]: {}", MAX_UTILITY_CHARGES, self.utility_charges));
+        }
+        check("smoke_seconds", self.smoke_seconds, 0.5, 60.0)?;
+        check("decoy_seconds", self.decoy_seconds, 0.5, 60.0)?;
+        if self.thermal_scans > MAX_THERMAL_SCANS {
+            return Err(format!("thermal_scans 超出范围 [0, {}]: {}", MAX_THERMAL_SCANS, self.thermal_scans));
+        }
+        check("thermal_seconds", self.thermal_seconds, 0.2, 10.0)?;
+        check("thermal_strength", self.thermal_strength, 0.0, 1.0)?;
+        check("thermal_cooldown_seconds", self.thermal_cooldown_seconds, 0.0, 600.0)?;
+        check("footstep_strength", self.footstep_strength, 0.0, 1.0)?;
+        check("footstep_interval_seconds", self.footstep_interval_seconds, 0.1, 5.0)?;
+        if self.repairs_per_round > MAX_REPAIRS_PER_ROUND {
+            return Err(format!("repairs_per_round 超出范围 [0, {}]: {}", MAX_REPAIRS_PER_ROUND, self.repairs_per_round));
+        }
+        check("repair_seconds", self.repair_seconds, 0.2, 30.0)?;
+        check("repair_reach", self.repair_reach, 10.0, 300.0)?;
+        check("stamina_max", self.stamina_max, 1.0, 1000.0)?;
+        check("sprint_multiplier", self.sprint_multiplier, 1.0, 3.0)?;
+        check("sprint_stamina_per_second", self.sprint_stamina_per_second, 0.0, 1000.0)?;
+        check("dodge_stamina", self.dodge_stamina, 0.0, self.stamina_max)?;
+        check("stand_stamina", self.stand_stamina, 0.0, self.stamina_max)?;
+        check("stamina_regen_per_second", self.stamina_regen_per_second, 0.0, 1000.0)?;
+        self.match_format.validate()
+    }
+
+    /// 单次命中的最大伤害
+    pub fn max_damage(&self) -> f32 {
+        self.damage_head.max(self.damage_torso).max(self.damage_legs)
+    }
+}
+
+fn check(field: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    if !value.is_finite() || value < min || value > max {
+        return Err(format!("{} 超出范围 [{}, {}]: {}", field, min, max, value));
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+// 瞄准镜：晃动、屏息和倍镜
+//
+// - 晃动：武器自带的晃动幅度（weapons::Weapon::scope_sway）在移动准星时逐渐放大，停下后慢慢恢复
+// - 屏息：按住 E 键大幅减小晃动，最多坚持 BREATH_HOLD_SECONDS 秒；憋到底会喘气，
+//   喘气期间晃动更大且不能屏息，直到气息恢复一半
+// - 倍镜：按 Q 键在三档倍率间切换，放大倍率会缩小进攻方相机的视野，同时降低瞄准速度和晃动
+// 倍镜只影响本机画面，对方看到的仍是同步过来的准星位置。
+
+use bevy::prelude::*;
+use crate::gameplay::DefenderCamera;
+use crate::FontResource;
+
+/// 屏息按键（按住）
+const HOLD_BREATH_KEY: KeyCode = KeyCode::KeyE;
+/// 切换倍镜按键
+const ZOOM_KEY: KeyCode = KeyCode::KeyQ;
+
+/// 进攻方相机在 1 倍镜下的缩放（与创建相机时一致）
+const ATTACKER_CAMERA_SCALE: f32 = 0.5;
+/// 各档倍镜的相机缩放倍率、瞄准速度倍率和晃动倍率
+const ZOOM_LEVELS: [(f32, f32, f32); 3] = [
+    (1.0, 1.0, 1.0),
+    (2.0 / 3.0, 0.7, 0.75),
+    (0.5, 0.45, 0.55),
+];
+
+/// 移动时晃动的最大放大量（满值时晃动为静止时的 1 + 2.5 倍）
+const MOVING_SWAY_GAIN: f32 = 2.5;
+/// 移动时晃动增长、停下后回落的速度（每秒）
+const MOVING_SWAY_RISE: f32 = 1.5;
+const MOVING_SWAY_FALL: f32 = 0.8;
+
+/// 最长屏息时间（秒）和屏息结束后每秒恢复的气息
+const BREATH_HOLD_SECONDS: f32 = 3.0;
+const BREATH_RECOVERY_RATE: f32 = 0.75;
+/// 屏息和喘气时的晃动倍率
+const HOLDING_SWAY_SCALE: f32 = 0.2;
+const WINDED_SWAY_SCALE: f32 = 1.6;
+
+/// 本机进攻方的瞄准镜状态（回合切换时重置）
+#[derive(Resource, Default, Debug)]
+pub struct ScopeState {
+    pub zoom_level: usize,
+    pub moving_sway: f32,  // 0~1，移动准星时增长
+    pub breath_used: f32,  // 已消耗的屏息时间（秒）
+    pub holding_breath: bool,
+    pub winded: bool,      // 憋气到底后喘气，恢复一半前不能再屏息
+}
+
+impl ScopeState {
+    /// 按本帧输入更新晃动、屏息和倍镜
+    pub fn update(&mut self, moving: bool, hold_breath: bool, cycle_zoom: bool, delta_seconds: f32) {
+        if cycle_zoom {
+            self.zoom_level = (self.zoom_level + 1) % ZOOM_LEVELS.len();
+        }
+
+        self.moving_sway = if moving {
+            (self.moving_sway + MOVING_SWAY_RISE * delta_seconds).min(1.0)
+        } else {
+            (self.moving_sway - MOVING_SWAY_FALL * delta_seconds).max(0.0)
+        };
+
+        self.holding_breath = hold_breath && !self.winded;
+        if self.holding_breath {
+            self.breath_used += delta_seconds;
+            if self.breath_used >= BREATH_HOLD_SECONDS {
+                // 调试输出已禁用: println!("[瞄准镜] 屏息到底，开始喘气");
+                self.breath_used = BREATH_HOLD_SECONDS;
+                self.holding_breath = false;
+                self.winded = true;
+            }
+        } else {
+            self.breath_used = (self.breath_used - BREATH_RECOVERY_RATE * delta_seconds).max(0.0);
+            if self.winded && self.breath_used <= BREATH_HOLD_SECONDS * 0.5 {
+                self.winded = false;
+            }
+        }
+    }
+
+    /// 相机缩放倍率（越小视野越窄、放大倍数越高）
+    pub fn zoom_scale(&self) -> f32 {
+        ZOOM_LEVELS[self.zoom_level].0
+    }
+
+    pub fn magnification(&self) -> f32 {
+        1.0 / self.zoom_scale()
+    }
+
+    /// 瞄准速度倍率：倍率越高移动越慢、越精细
+    pub fn aim_speed_scale(&self) -> f32 {
+        ZOOM_LEVELS[self.zoom_level].1
+    }
+
+    /// 晃动倍率：综合移动、屏息和倍镜
+    pub fn sway_scale(&self) -> f32 {
+        let breath = if self.holding_breath {
+            HOLDING_SWAY_SCALE
+        } else if self.winded {
+            WINDED_SWAY_SCALE
+        } else {
+            1.0
+        };
+        (1.0 + MOVING_SWAY_GAIN * self.moving_sway) * breath * ZOOM_LEVELS[self.zoom_level].2
+    }
+
+    /// 剩余气息（0~1）
+    pub fn breath_left(&self) -> f32 {
+        1.0 - self.breath_used / BREATH_HOLD_SECONDS
+    }
+}
+
+/// 读取瞄准镜按键并更新状态（由 attacker_aim_system 在确认本机操控进攻方后调用）
+pub fn update_scope_from_input(scope: &mut ScopeState, keyboard_input: &ButtonInput<KeyCode>, moving: bool, delta_seconds: f32) {
+    scope.update(
+        moving,
+        keyboard_input.pressed(HOLD_BREATH_KEY),
+        keyboard_input.just_pressed(ZOOM_KEY),
+        delta_seconds,
+    );
+}
+
+/// 把倍镜应用到进攻方相机（只在缩放不同时写入，避免每帧触发变更检测）
+pub fn apply_scope_zoom_system(
+    scope: Res<ScopeState>,
+    mut camera_query: Query<(&Camera, &mut Projection), Without<DefenderCamera>>,
+) {
+    let target_scale = ATTACKER_CAMERA_SCALE * scope.zoom_scale();
+    for (camera, mut projection) in camera_query.iter_mut() {
+        // 跳过UI相机
+        if camera.order >= 1000 {
+            continue;
+        }
+        let Projection::Orthographic(ortho) = projection.as_ref() else { continue };
+        if (ortho.scale - target_scale).abs() < f32::EPSILON {
+            continue;
+        }
+        if let Projection::Orthographic(ortho) = projection.as_mut() {
+            ortho.scale = target_scale;
+        }
+    }
+}
+
+/// 回合切换时收起倍镜、恢复气息
+pub fn reset_scope_on_switch_system(mut scope: ResMut<ScopeState>) {
+    *scope = ScopeState::default();
+}
+
+/// 倍镜和屏息状态文字（进攻方视角）
+#[derive(Component)]
+pub struct ScopeText;
+
+/// 创建瞄准镜文字（放在武器文字下方）
+pub fn spawn_scope_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(String::new(), TextStyle { font, font_size: 16.0, color: Color::GRAY }),
+            style: Style { margin: UiRect::top(Val::Px(3.0)), ..default() },
+            ..default()
+        },
+        ScopeText,
+        crate::AttackerUI,
+    ));
+}
+
+/// 更新瞄准镜文字（只在内容变化时写入）
+pub fn update_scope_display_system(
+    scope: Res<ScopeState>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<ScopeText>>,
+) {
+    let breath = if scope.winded {
+        "喘气中".to_string()
+    } else if scope.holding_breath {
+        format!("屏息中 {:.0}%", scope.breath_left() * 100.0)
+    } else {
+        format!("气息 {:.0}%", scope.breath_left() * 100.0)
+    };
+    let summary = format!("倍镜 {:.1}×（Q 切换）  |  {}（按住 E 屏息）", scope.magnification(), breath);
+    for mut text in text_query.iter_mut() {
+        let Some(section) = text.sections.first_mut() else { continue };
+        if section.value == summary {
+            continue;
+        }
+        section.value = summary.clone();
+        section.style.font = font_resource.font.clone();
+    }
+}
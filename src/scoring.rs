@@ -0,0 +1,309 @@
+// 赛制与比分：记录双方累计伤害和淘汰次数，按赛制判定比赛胜负
+//
+// 赛制（rules::MatchFormat，随规则一起在 StartGame 中下发）：
+// - 经典淘汰：一方血量归零即结束
+// - 每人进攻 N 回合：回合用完后累计伤害多者获胜，打平进入加时赛，先拉开伤害差距者获胜
+// - 先淘汰 N 次：每个进攻回合开始时双方回满血，把防守方打到 0 血记一次淘汰
+// 本地模式和联机主机负责判定；客户端的比分由 RoundInfoSync 同步，比赛结束由主机的 GameOver 消息通知。
+
+use bevy::prelude::*;
+use crate::game_rules::GameRules;
+use crate::gameplay::{GameOverDelay, Health, RoundInfo};
+use crate::{FontResource, PlayerId, RoomInfo};
+use bevy_sniper_duel::rules::MatchFormat;
+pub use bevy_sniper_duel::protocol::MatchScore;
+
+/// 比赛结束到进入结算画面的延迟（秒）
+const GAME_OVER_DELAY_SECONDS: f32 = 2.0;
+
+/// 屏幕底部的比分文字
+#[derive(Component)]
+pub struct MatchScoreText;
+
+pub fn opponent(player_id: PlayerId) -> PlayerId {
+    match player_id {
+        PlayerId::Player1 => PlayerId::Player2,
+        PlayerId::Player2 => PlayerId::Player1,
+    }
+}
+
+/// 本机是否负责判定比赛胜负（本地模式或联机主机）
+pub fn is_match_authority(room_info: Option<&RoomInfo>) -> bool {
+    room_info.map(|r| !r.is_connected || r.is_host).unwrap_or(true)
+}
+
+/// 启动游戏结束延迟，延迟结束后由 game_over_delay_system 进入结算画面
+pub fn start_game_over(game_over_delay: &mut GameOverDelay, winner_id: PlayerId) {
+    // 调试输出已禁用: println!("[比分] 比赛结束，获胜者: {:?}", winner_id);
+    game_over_delay.timer = Some(Timer::from_seconds(GAME_OVER_DELAY_SECONDS, TimerMode::Once));
+    game_over_delay.winner_id = Some(winner_id);
+    game_over_delay.loser_id = Some(opponent(winner_id));
+}
+
+fn health_of(round_info: &RoundInfo, player_id: PlayerId) -> f32 {
+    match player_id {
+        PlayerId::Player1 => round_info.p1_health,
+        PlayerId::Player2 => round_info.p2_health,
+    }
+}
+
+/// 累计伤害领先的一方（打平时返回 None）
+fn damage_leader(score: &MatchScore) -> Option<PlayerId> {
+    if score.p1_damage > score.p2_damage {
+        Some(PlayerId::Player1)
+    } else if score.p2_damage > score.p1_damage {
+        Some(PlayerId::Player2)
+    } else {
+        None
+    }
+}
+
+/// 进攻回合结束时结算比分，返回比赛的获胜者（比赛继续时返回 None）
+pub fn end_attack_turn(round_info: &mut RoundInfo, format: MatchFormat) -> Option<PlayerId> {
+    let attacker = round_info.current_attacker;
+    let defender = opponent(attacker);
+    round_info.score.turns_played += 1;
+
+    match format {
+        MatchFormat::Elimination => None,
+        MatchFormat::AttackTurns { turns } => {
+            // 加时赛中伤害一拉开就由 match_decision_system 结束比赛
+            if round_info.score.overtime || round_info.score.turns_played < turns * 2 {
+                return None;
+            }
+            let leader = damage_leader(&round_info.score);
+            if leader.is_none() {
+                // 调试输出已禁用: println!("[比分] 常规回合打平，进入加时赛");
+                round_info.score.overtime = true;
+            }
+            leader
+        }
+        MatchFormat::FirstTo { eliminations } => {
+            if health_of(round_info, defender) > 0.0 {
+                return None;
+            }
+            round_info.score.add_elimination(attacker);
+            (round_info.score.eliminations(attacker) >= eliminations).then_some(attacker)
+        }
+    }
+}
+
+/// 回合进行中的胜负判定（本地模式和主机）
+/// 经典淘汰和回合制下血量归零直接判负，加时赛中伤害领先即获胜；
+/// 先淘汰 N 次赛制下防守方倒下后本回合提前结束，淘汰在回合结算时计入
+pub fn match_decision_system(
+    mut round_info: ResMut<RoundInfo>,
+    mut game_over_delay: ResMut<GameOverDelay>,
+    rules: Res<GameRules>,
+    room_info: Option<Res<RoomInfo>>,
+) {
+    if !is_match_authority(room_info.as_deref()) {
+        return;
+    }
+    if game_over_delay.timer.is_some() || game_over_delay.network_send_timer.is_some() {
+        return;
+    }
+
+    let eliminated = [PlayerId::Player1, PlayerId::Player2]
+        .into_iter()
+        .find(|player_id| health_of(&round_info, *player_id) <= 0.0);
+
+    match rules.match_format {
+        MatchFormat::FirstTo { .. } => {
+            if eliminated.is_some() && round_info.bullets_left > 0 {
+                round_info.bullets_left = 0;
+            }
+        }
+        MatchFormat::Elimination | MatchFormat::AttackTurns { .. } => {
+            if let Some(loser_id) = eliminated {
+                start_game_over(&mut game_over_delay, opponent(loser_id));
+            } else if let Some(leader) = damage_leader(&round_info.score).filter(|_| round_info.score.overtime) {
+                start_game_over(&mut game_over_delay, leader);
+            }
+        }
+    }
+}
+
+/// 回合切换时按赛制回满血（主机和客户端各自执行，规则相同结果一致）
+pub fn reset_health_on_switch_system(
+    mut round_info: ResMut<RoundInfo>,
+    mut health_query: Query<&mut Health, With<PlayerId>>,
+    rules: Res<GameRules>,
+) {
+    if !rules.match_format.resets_health() {
+        return;
+    }
+    round_info.p1_health = rules.player_hp;
+    round_info.p2_health = rules.player_hp;
+    for mut health in health_query.iter_mut() {
+        health.0 = rules.player_hp;
+    }
+}
+
+/// 比分摘要（HUD 和结算画面共用）
+pub fn score_summary(format: MatchFormat, score: &MatchScore) -> String {
+    let damage = format!("伤害 玩家1 {:.0} : {:.0} 玩家2", score.p1_damage, score.p2_damage);
+    match format {
+        MatchFormat::Elimination => format!("{}  |  {}", format.label(), damage),
+        MatchFormat::AttackTurns { turns } => {
+            let stage = if score.overtime {
+                "加时赛".to_string()
+            } else {
+                format!("第 {}/{} 回合", (score.turns_played + 1).min(turns * 2), turns * 2)
+            };
+            format!("{}  |  {}  |  {}", format.label(), stage, damage)
+        }
+        MatchFormat::FirstTo { .. } => format!(
+            "{}  |  淘汰 玩家1 {} : {} 玩家2  |  {}",
+            format.label(),
+            score.p1_eliminations,
+            score.p2_eliminations,
+            damage
+        ),
+    }
+}
+
+/// 创建比分文字（屏幕底部居中，盖在双方视角的UI之上）
+pub fn spawn_score_text(parent: &mut ChildBuilder, font: Handle<Font>, rules: &GameRules) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            bottom: Val::Px(55.0),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        z_index: ZIndex::Global(10000),
+        ..default()
+    }).with_children(|bottom| {
+        bottom.spawn((
+            TextBundle::from_section(
+                score_summary(rules.match_format, &MatchScore::default()),
+                TextStyle {
+                    font,
+                    font_size: 22.0,
+                    color: Color::rgb(1.0, 0.9, 0.5),
+                },
+            ),
+            MatchScoreText,
+        ));
+    });
+}
+
+/// 更新比分文字（只在内容变化时写入）
+pub fn update_score_display_system(
+    round_info: Res<RoundInfo>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<MatchScoreText>>,
+    mut last_summary: Local<String>,
+) {
+    let summary = score_summary(rules.match_format, &round_info.score);
+    if *last_summary == summary && !font_resource.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        if let Some(section) = text.sections.first_mut() {
+            section.value = summary.clone();
+            section.style.font = font_resource.font.clone();
+        }
+    }
+    *last_summary = summary;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_info(attacker: PlayerId) -> RoundInfo {
+        RoundInfo {
+            bullets_left: 3,
+            round_timer: Timer::from_seconds(30.0, TimerMode::Once),
+            current_attacker: attacker,
+            p1_health: 100.0,
+            p2_health: 100.0,
+            bullets_fired_this_round: 0,
+            bullets_hit_defender: 0,
+            is_switching: false,
+            score: MatchScore::default(),
+            wind: Vec2::ZERO,
+        }
+    }
+
+    /// 结束当前进攻回合并换对方进攻
+    fn play_turn(round_info: &mut RoundInfo, format: MatchFormat) -> Option<PlayerId> {
+        let winner = end_attack_turn(round_info, format);
+        round_info.current_attacker = opponent(round_info.current_attacker);
+        winner
+    }
+
+    #[test]
+    fn attack_turns_leader_wins_after_regular_turns() {
+        let format = MatchFormat::AttackTurns { turns: 2 };
+        let mut round_info = round_info(PlayerId::Player1);
+        round_info.score.add_damage(PlayerId::Player2, 40.0);
+        for _ in 0..3 {
+            assert_eq!(play_turn(&mut round_info, format), None);
+        }
+        assert_eq!(play_turn(&mut round_info, format), Some(PlayerId::Player2));
+        assert_eq!(round_info.score.turns_played, 4);
+        assert!(!round_info.score.overtime);
+    }
+
+    #[test]
+    fn attack_turns_tie_enters_overtime() {
+        let format = MatchFormat::AttackTurns { turns: 2 };
+        let mut round_info = round_info(PlayerId::Player1);
+        round_info.score.add_damage(PlayerId::Player1, 30.0);
+        round_info.score.add_damage(PlayerId::Player2, 30.0);
+        for _ in 0..3 {
+            assert_eq!(play_turn(&mut round_info, format), None);
+            assert!(!round_info.score.overtime);
+        }
+        // 常规回合打完仍然打平：不分胜负，进入加时赛
+        assert_eq!(play_turn(&mut round_info, format), None);
+        assert!(round_info.score.overtime);
+        // 加时赛中回合结束不判胜负（伤害一拉开由 match_decision_system 结束比赛），回合数不设上限
+        round_info.score.add_damage(PlayerId::Player1, 10.0);
+        for _ in 0..10 {
+            assert_eq!(play_turn(&mut round_info, format), None);
+        }
+        assert_eq!(round_info.score.turns_played, 14);
+    }
+
+    #[test]
+    fn first_to_counts_eliminations() {
+        let format = MatchFormat::FirstTo { eliminations: 2 };
+        let mut round_info = round_info(PlayerId::Player1);
+        // 防守方没有倒下不计淘汰
+        assert_eq!(play_turn(&mut round_info, format), None);
+        assert_eq!(round_info.score.eliminations(PlayerId::Player1), 0);
+
+        // 玩家2进攻时淘汰玩家1一次
+        round_info.p1_health = 0.0;
+        assert_eq!(play_turn(&mut round_info, format), None);
+        assert_eq!(round_info.score.eliminations(PlayerId::Player2), 1);
+
+        // 玩家1淘汰玩家2两次后获胜
+        round_info.p1_health = 100.0;
+        round_info.p2_health = 0.0;
+        assert_eq!(play_turn(&mut round_info, format), None);
+        assert_eq!(round_info.score.eliminations(PlayerId::Player1), 1);
+        round_info.p2_health = 100.0;
+        assert_eq!(play_turn(&mut round_info, format), None);
+        round_info.p2_health = 0.0;
+        assert_eq!(play_turn(&mut round_info, format), Some(PlayerId::Player1));
+        assert_eq!(round_info.score.eliminations(PlayerId::Player1), 2);
+        assert_eq!(round_info.score.eliminations(PlayerId::Player2), 1);
+        assert_eq!(round_info.score.turns_played, 5);
+    }
+
+    #[test]
+    fn elimination_format_is_not_decided_at_turn_end() {
+        let mut round_info = round_info(PlayerId::Player1);
+        round_info.p2_health = 0.0;
+        assert_eq!(end_attack_turn(&mut round_info, MatchFormat::Elimination), None);
+        assert_eq!(round_info.score.eliminations(PlayerId::Player1), 0);
+        assert_eq!(round_info.score.turns_played, 1);
+    }
+}
@@ -0,0 +1,165 @@
+// 防守方体力
+//
+// 防守方有一个体力池（上限 stamina_max）：按住左 Shift 冲刺时按 sprint_multiplier 倍速移动并持续消耗体力，
+// 侧躲一次消耗 dodge_stamina（体力不够时按 K 只会下蹲），下蹲结束站起来消耗 stand_stamina；
+// 站着不动、也没有在躲避时按 stamina_regen_per_second 恢复。体力用完后不能冲刺。
+// 联机时体力由防守方本机计算，随 DefenderState 以固定频率发给进攻方（不单独发消息，进攻方不能从消息的时机看出体力变化），
+// 进攻方按对方的侧躲、站起来、超出步行速度的位移和站着不动的时间自己推算体力，报告的体力不能高于推算值（见 anti_cheat.rs）。
+
+use bevy::prelude::*;
+use crate::game_rules::GameRules;
+use crate::gameplay::{DodgeAction, RoundInfo};
+use crate::{DefenderUI, PlayerId, PlayerRole, RoomInfo};
+
+/// 冲刺的按键（按住）
+pub const SPRINT_KEY: KeyCode = KeyCode::ShiftLeft;
+/// 体力条的大小
+const BAR_SIZE: Vec2 = Vec2::new(160.0, 10.0);
+/// 体力不足以侧躲时体力条的颜色
+const LOW_COLOR: Color = Color::rgb(0.9, 0.3, 0.2);
+/// 体力足够侧躲时体力条的颜色
+const READY_COLOR: Color = Color::rgb(0.3, 0.8, 0.9);
+
+/// 玩家的体力（只有防守方消耗和恢复）
+#[derive(Component, Debug, Clone)]
+pub struct Stamina {
+    pub current: f32,
+    pub sprinting: bool,          // 本帧是否在冲刺（由 defender_move_system 设置）
+    last_position: Option<Vec2>,  // 上一帧的位置（判断是否站着不动）
+    last_action: DodgeAction,     // 上一帧的动作（判断侧躲开始和站起来）
+}
+
+impl Stamina {
+    /// 满体力
+    pub fn full(rules: &GameRules) -> Self {
+        Self { current: rules.stamina_max, sprinting: false, last_position: None, last_action: DodgeAction::None }
+    }
+
+    /// 体力是否够侧躲一次
+    pub fn can_side_dodge(&self, rules: &GameRules) -> bool {
+        self.current >= rules.dodge_stamina
+    }
+
+    /// 还有体力时才能冲刺
+    pub fn can_sprint(&self) -> bool {
+        self.current > 0.0
+    }
+}
+
+/// 体力条的填充部分（防守方视角）
+#[derive(Component)]
+pub struct StaminaBarFill;
+
+/// 体力由本机计算的玩家（联机时对方的体力来自 DefenderState）
+fn is_simulated_locally(player_id: PlayerId, room_info: Option<&RoomInfo>) -> bool {
+    match room_info.filter(|room| room.is_connected) {
+        Some(room) => player_id == if room.is_host { PlayerId::Player1 } else { PlayerId::Player2 },
+        None => true,
+    }
+}
+
+/// 防守方体力的消耗和恢复：冲刺按时间消耗，侧躲和站起来一次性消耗，站着不动时恢复
+pub fn stamina_system(
+    time: Res<Time>,
+    rules: Res<GameRules>,
+    room_info: Option<Res<RoomInfo>>,
+    mut query: Query<(&mut Stamina, &Transform, &DodgeAction, &PlayerRole, &PlayerId)>,
+) {
+    let delta = time.delta_seconds();
+    for (mut stamina, transform, dodge_action, role, player_id) in query.iter_mut() {
+        if !matches!(role, PlayerRole::Defender) || !is_simulated_locally(*player_id, room_info.as_deref()) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let moved = stamina.last_position.is_some_and(|last| last != position);
+        let previous = std::mem::replace(&mut stamina.last_action, *dodge_action);
+        stamina.last_position = Some(position);
+
+        let mut change = 0.0;
+        if previous != *dodge_action {
+            match (previous, *dodge_action) {
+                (_, DodgeAction::SideLeft | DodgeAction::SideRight) => change -= rules.dodge_stamina,
+                (DodgeAction::Crouch, DodgeAction::None) => change -= rules.stand_stamina,
+                _ => {}
+            }
+        }
+        if stamina.sprinting && moved {
+            change -= rules.sprint_stamina_per_second * delta;
+        } else if !moved && *dodge_action == DodgeAction::None {
+            change += rules.stamina_regen_per_second * delta;
+        }
+        if change != 0.0 {
+            stamina.current = (stamina.current + change).clamp(0.0, rules.stamina_max);
+        }
+    }
+}
+
+/// 换回合时双方恢复满体力
+pub fn reset_stamina_on_switch(rules: Res<GameRules>, mut query: Query<&mut Stamina>) {
+    for mut stamina in query.iter_mut() {
+        *stamina = Stamina::full(&rules);
+    }
+}
+
+/// 创建体力条（放在动作冷却提示上方）
+pub fn spawn_stamina_bar(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(104.0),
+                left: Val::Percent(50.0),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(9999), // 与防守方其他UI一样最后渲染
+            transform: Transform::from_xyz(-100.0, 0.0, 0.0),
+            ..default()
+        },
+        DefenderUI,
+    )).with_children(|row| {
+        row.spawn(TextBundle::from_section(
+            "体力（按住左 Shift 冲刺）",
+            TextStyle { font, font_size: 16.0, color: Color::GRAY },
+        ));
+        row.spawn(NodeBundle {
+            style: Style { width: Val::Px(BAR_SIZE.x), height: Val::Px(BAR_SIZE.y), ..default() },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        }).with_children(|bar| {
+            bar.spawn((
+                NodeBundle {
+                    style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), ..default() },
+                    background_color: READY_COLOR.into(),
+                    ..default()
+                },
+                StaminaBarFill,
+            ));
+        });
+    });
+}
+
+/// 更新体力条：显示当前防守方的体力，不够侧躲时变成红色
+pub fn update_stamina_bar_system(
+    rules: Res<GameRules>,
+    round_info: Res<RoundInfo>,
+    player_query: Query<(&PlayerId, &Stamina)>,
+    mut bar_query: Query<(&mut Style, &mut BackgroundColor), With<StaminaBarFill>>,
+) {
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+    let Some((_, stamina)) = player_query.iter().find(|(id, _)| **id == defender) else {
+        return;
+    };
+    let width = Val::Percent(stamina.current / rules.stamina_max * 100.0);
+    let color = if stamina.can_side_dodge(&rules) { READY_COLOR } else { LOW_COLOR };
+    for (mut style, mut background) in bar_query.iter_mut() {
+        if style.width != width {
+            style.width = width;
+        }
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
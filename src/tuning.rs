@@ -0,0 +1,641 @@
+// 调参面板：对局中实时调整平衡数值
+//
+// 游戏中按 F2 打开/关闭面板，拖动滑条修改伤害、瞄准速度、移动速度和躲避冷却。
+// 修改先保存在 TuningPanel::pending 中，下一次回合切换（OnEnter(RoundState::Switching)）时
+// 才写入 GameRules，并在屏幕上提示改动了哪些数值。
+// 联机时只有主机可以调参：主机决定换边、发出 SwitchRoles 之前先把修改发给客户端（RulesUpdate），
+// 双方都把这份规则排进 TuningPanel::queued，到各自的回合切换时才生效，两边总在同一回合换规则。
+// RulesUpdate 没有确认，RoundInfoSync 中带着主机当前规则的指纹：客户端的规则（和排队中的规则）都对不上时
+// 说明 RulesUpdate 全部丢了，客户端定期发 RulesRequest，主机重发当前规则，客户端收到后立即换上。
+// 面板中可以输入预设名，把当前数值保存为 assets/rules/<预设名>.ron，之后用 --rules 加载。
+
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::ReceivedCharacter;
+use crate::game_rules::{self, GameRules};
+use crate::gameplay::ActionCooldown;
+use crate::network_game::NetworkManager;
+use crate::{FontResource, RoomInfo};
+use bevy_sniper_duel::protocol::NetworkMessage;
+
+/// 打开/关闭面板的按键
+const TOGGLE_KEY: KeyCode = KeyCode::F2;
+/// 预设名最大长度
+const MAX_PRESET_NAME_LEN: usize = 24;
+/// 规则更新提示的显示时间（秒）
+const NOTICE_SECONDS: f32 = 4.0;
+/// 主机下发规则的次数（与 StartGame 一样多发几次，防止丢包）
+const RULES_UPDATE_REPEAT: usize = 3;
+/// 客户端规则与主机不一致时重新请求的间隔（秒）
+const RULES_REQUEST_INTERVAL: f64 = 1.0;
+
+/// 面板中可调的数值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunableField {
+    DamageHead,
+    DamageTorso,
+    DamageLegs,
+    AimSpeed,
+    PlayerMoveSpeed,
+    DodgeCooldown,
+}
+
+impl TunableField {
+    pub const ALL: [TunableField; 6] = [
+        TunableField::DamageHead,
+        TunableField::DamageTorso,
+        TunableField::DamageLegs,
+        TunableField::AimSpeed,
+        TunableField::PlayerMoveSpeed,
+        TunableField::DodgeCooldown,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TunableField::DamageHead => "头部伤害",
+            TunableField::DamageTorso => "躯干伤害",
+            TunableField::DamageLegs => "腿部伤害",
+            TunableField::AimSpeed => "瞄准速度",
+            TunableField::PlayerMoveSpeed => "移动速度",
+            TunableField::DodgeCooldown => "躲避冷却",
+        }
+    }
+
+    /// 滑条范围和步长（都在 GameRules::validate 允许的范围内）
+    fn range(&self) -> (f32, f32, f32) {
+        match self {
+            TunableField::DamageHead | TunableField::DamageTorso | TunableField::DamageLegs => (0.0, 200.0, 5.0),
+            TunableField::AimSpeed | TunableField::PlayerMoveSpeed => (100.0, 600.0, 10.0),
+            TunableField::DodgeCooldown => (0.0, 15.0, 0.5),
+        }
+    }
+
+    pub fn get(&self, rules: &GameRules) -> f32 {
+        match self {
+            TunableField::DamageHead => rules.damage_head,
+            TunableField::DamageTorso => rules.damage_torso,
+            TunableField::DamageLegs => rules.damage_legs,
+            TunableField::AimSpeed => rules.aim_speed,
+            TunableField::PlayerMoveSpeed => rules.player_move_speed,
+            TunableField::DodgeCooldown => rules.dodge_cooldown_seconds,
+        }
+    }
+
+    fn set(&self, rules: &mut GameRules, value: f32) {
+        match self {
+            TunableField::DamageHead => rules.damage_head = value,
+            TunableField::DamageTorso => rules.damage_torso = value,
+            TunableField::DamageLegs => rules.damage_legs = value,
+            TunableField::AimSpeed => rules.aim_speed = value,
+            TunableField::PlayerMoveSpeed => rules.player_move_speed = value,
+            TunableField::DodgeCooldown => rules.dodge_cooldown_seconds = value,
+        }
+    }
+
+    /// 滑条上的相对位置（0~1）对应的数值，按步长取整
+    fn value_at(&self, fraction: f32) -> f32 {
+        let (min, max, step) = self.range();
+        let raw = min + fraction.clamp(0.0, 1.0) * (max - min);
+        ((raw / step).round() * step).clamp(min, max)
+    }
+
+    /// 数值在滑条上的相对位置（0~1）
+    fn fraction(&self, rules: &GameRules) -> f32 {
+        let (min, max, _) = self.range();
+        ((self.get(rules) - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    pub fn format(&self, rules: &GameRules) -> String {
+        let value = self.get(rules);
+        match self {
+            TunableField::DodgeCooldown => format!("{:.1}秒", value),
+            _ => format!("{:.0}", value),
+        }
+    }
+}
+
+/// 调参面板状态
+#[derive(Resource)]
+pub struct TuningPanel {
+    pub pending: Option<GameRules>, // 尚未生效的修改（下一回合生效）
+    pub queued: Option<GameRules>,  // 联机时已经发给（或收到自）对方、下一次回合切换时生效的规则
+    pub preset_name: String,        // 保存预设用的名称
+    pub naming: bool,               // 是否正在输入预设名
+    pub status: Option<String>,     // 最近一次操作的结果（保存成功/失败等）
+}
+
+impl Default for TuningPanel {
+    fn default() -> Self {
+        Self {
+            pending: None,
+            queued: None,
+            preset_name: "custom".to_string(),
+            naming: false,
+            status: None,
+        }
+    }
+}
+
+impl TuningPanel {
+    /// 面板上显示的数值：有未生效的修改时显示修改后的数值
+    fn shown<'a>(&'a self, rules: &'a GameRules) -> &'a GameRules {
+        self.pending.as_ref().unwrap_or(rules)
+    }
+
+    fn status_line(&self, rules: &GameRules) -> String {
+        if let Some(status) = &self.status {
+            status.clone()
+        } else if self.pending.as_ref().is_some_and(|pending| pending != rules) {
+            "修改将在下一回合开始时生效".to_string()
+        } else {
+            "拖动滑条调整数值".to_string()
+        }
+    }
+
+    fn name_line(&self) -> String {
+        format!("预设名: {}{}", self.preset_name, if self.naming { "_" } else { "" })
+    }
+}
+
+/// 规则在回合切换时更新（本地和主机应用面板中的修改、客户端应用收到的 RulesUpdate 时发送）
+#[derive(Event, Debug)]
+pub struct RulesAppliedEvent {
+    pub previous: GameRules,
+}
+
+/// 客户端：主机在 RoundInfoSync 中报告的当前规则指纹
+#[derive(Resource, Default)]
+pub struct HostRulesHash(pub Option<u64>);
+
+/// 面板根节点
+#[derive(Component)]
+pub struct TuningPanelUI;
+
+/// 滑条（可点击/拖动的轨道）
+#[derive(Component)]
+pub struct TuningSlider(pub TunableField);
+
+/// 滑条的填充部分
+#[derive(Component)]
+pub struct TuningSliderFill(pub TunableField);
+
+/// 面板上需要刷新的文本
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuningText {
+    Value(TunableField), // 数值
+    Name,                // 预设名
+    Status,              // 状态
+}
+
+/// 面板按钮
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuningButton {
+    Discard,    // 撤销未生效的修改
+    NameField,  // 预设名输入框
+    SavePreset, // 保存为预设
+}
+
+/// 规则更新提示
+#[derive(Component)]
+pub struct RulesNotice {
+    timer: Timer,
+}
+
+fn value_line(field: TunableField, panel: &TuningPanel, rules: &GameRules) -> String {
+    let shown = panel.shown(rules);
+    if field.get(shown) != field.get(rules) {
+        format!("{}（当前 {}）", field.format(shown), field.format(rules))
+    } else {
+        field.format(shown)
+    }
+}
+
+fn spawn_tuning_panel(commands: &mut Commands, font: Handle<Font>, panel: &TuningPanel, rules: &GameRules) {
+    let text_style = TextStyle { font: font.clone(), font_size: 18.0, color: Color::WHITE };
+    let title_style = TextStyle { font: font.clone(), font_size: 22.0, color: Color::YELLOW };
+    let hint_style = TextStyle { font: font.clone(), font_size: 16.0, color: Color::GRAY };
+    let shown = panel.shown(rules);
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(80.0),
+                right: Val::Px(20.0),
+                width: Val::Px(420.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            z_index: ZIndex::Global(250), // 在对局UI之上、游戏结束界面之下
+            ..default()
+        },
+        TuningPanelUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("调参面板（F2 关闭）", title_style));
+
+        for field in TunableField::ALL {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            }).with_children(|row| {
+                row.spawn(TextBundle {
+                    text: Text::from_section(field.label(), text_style.clone()),
+                    style: Style { width: Val::Px(80.0), ..default() },
+                    ..default()
+                });
+                row.spawn((
+                    NodeBundle {
+                        style: Style { width: Val::Px(160.0), height: Val::Px(14.0), ..default() },
+                        background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                        ..default()
+                    },
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    TuningSlider(field),
+                )).with_children(|track| {
+                    track.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(field.fraction(shown) * 100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::rgb(1.0, 0.84, 0.0).into(),
+                            ..default()
+                        },
+                        TuningSliderFill(field),
+                    ));
+                });
+                row.spawn((
+                    TextBundle::from_section(value_line(field, panel, rules), text_style.clone()),
+                    TuningText::Value(field),
+                ));
+            });
+        }
+
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(8.0),
+                margin: UiRect::top(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        }).with_children(|buttons| {
+            let button_style = Style {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            };
+            buttons.spawn((
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                    ..default()
+                },
+                TuningButton::Discard,
+            )).with_children(|button| {
+                button.spawn(TextBundle::from_section("撤销修改", text_style.clone()));
+            });
+            buttons.spawn((
+                ButtonBundle {
+                    style: Style { min_width: Val::Px(150.0), ..button_style.clone() },
+                    background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                    ..default()
+                },
+                TuningButton::NameField,
+            )).with_children(|button| {
+                button.spawn((TextBundle::from_section(panel.name_line(), text_style.clone()), TuningText::Name));
+            });
+            buttons.spawn((
+                ButtonBundle {
+                    style: button_style,
+                    background_color: Color::rgb(0.2, 0.5, 0.2).into(),
+                    ..default()
+                },
+                TuningButton::SavePreset,
+            )).with_children(|button| {
+                button.spawn(TextBundle::from_section("保存预设", text_style.clone()));
+            });
+        });
+
+        parent.spawn((TextBundle::from_section(panel.status_line(rules), hint_style), TuningText::Status));
+    });
+}
+
+fn spawn_notice(commands: &mut Commands, font: Handle<Font>, message: String) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            z_index: ZIndex::Global(260),
+            ..default()
+        },
+        RulesNotice { timer: Timer::from_seconds(NOTICE_SECONDS, TimerMode::Once) },
+    )).with_children(|parent| {
+        parent.spawn(TextBundle {
+            text: Text::from_section(message, TextStyle { font, font_size: 24.0, color: Color::YELLOW }),
+            style: Style { padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)), ..default() },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            ..default()
+        });
+    });
+}
+
+/// 把面板上显示的数值保存为预设
+fn save_shown_rules(panel: &mut TuningPanel, rules: &GameRules) {
+    let shown = panel.shown(rules).clone();
+    panel.naming = false;
+    panel.status = Some(match game_rules::save_preset(&panel.preset_name, &shown) {
+        Ok(path) => format!("已保存预设: {}", path.display()),
+        Err(e) => {
+            eprintln!("[调参] 保存预设失败: {}", e);
+            format!("保存失败: {}", e)
+        }
+    });
+}
+
+/// 按 F2 打开/关闭面板（联机时只有主机可以打开）
+pub fn toggle_tuning_panel_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<TuningPanel>,
+    rules: Res<GameRules>,
+    room_info: Res<RoomInfo>,
+    font_resource: Res<FontResource>,
+    panel_query: Query<Entity, With<TuningPanelUI>>,
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    if !panel_query.is_empty() {
+        for entity in panel_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        panel.naming = false;
+        return;
+    }
+    if room_info.is_connected && !room_info.is_host {
+        spawn_notice(&mut commands, font_resource.font.clone(), "联机时只有主机可以调参".to_string());
+        return;
+    }
+    panel.status = None;
+    spawn_tuning_panel(&mut commands, font_resource.font.clone(), &panel, &rules);
+}
+
+/// 按住滑条拖动修改数值
+pub fn tuning_slider_system(
+    mut panel: ResMut<TuningPanel>,
+    rules: Res<GameRules>,
+    slider_query: Query<(&Interaction, &RelativeCursorPosition, &TuningSlider)>,
+) {
+    for (interaction, cursor, slider) in slider_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        let field = slider.0;
+        let value = field.value_at(position.x);
+        if field.get(panel.shown(&rules)) == value {
+            continue;
+        }
+        let pending = panel.pending.get_or_insert_with(|| rules.clone());
+        field.set(pending, value);
+        if panel.pending.as_ref() == Some(&*rules) {
+            panel.pending = None;
+        }
+        panel.status = None;
+    }
+}
+
+/// 面板按钮：撤销修改、编辑预设名、保存预设
+pub fn tuning_button_system(
+    mut panel: ResMut<TuningPanel>,
+    rules: Res<GameRules>,
+    button_query: Query<(&Interaction, &TuningButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            TuningButton::Discard => {
+                panel.pending = None;
+                panel.status = Some("已撤销未生效的修改".to_string());
+            }
+            TuningButton::NameField => {
+                panel.naming = !panel.naming;
+            }
+            TuningButton::SavePreset => save_shown_rules(&mut panel, &rules),
+        }
+    }
+}
+
+/// 输入预设名（点击输入框后生效，回车保存，Esc 取消）
+pub fn tuning_name_input_system(
+    mut panel: ResMut<TuningPanel>,
+    rules: Res<GameRules>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    if !panel.naming {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        for c in event.char.chars() {
+            if (c.is_ascii_alphanumeric() || c == '_' || c == '-') && panel.preset_name.len() < MAX_PRESET_NAME_LEN {
+                panel.preset_name.push(c);
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        panel.preset_name.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        save_shown_rules(&mut panel, &rules);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        panel.naming = false;
+    }
+}
+
+/// 更新面板上的滑条和文本（只在面板状态或规则变化时运行）
+pub fn update_tuning_panel_system(
+    panel: Res<TuningPanel>,
+    rules: Res<GameRules>,
+    mut fill_query: Query<(&TuningSliderFill, &mut Style)>,
+    mut text_query: Query<(&TuningText, &mut Text)>,
+) {
+    if !panel.is_changed() && !rules.is_changed() {
+        return;
+    }
+    let shown = panel.shown(&rules);
+    for (fill, mut style) in fill_query.iter_mut() {
+        style.width = Val::Percent(fill.0.fraction(shown) * 100.0);
+    }
+    for (tuning_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match tuning_text {
+            TuningText::Value(field) => value_line(*field, &panel, &rules),
+            TuningText::Name => panel.name_line(),
+            TuningText::Status => panel.status_line(&rules),
+        };
+    }
+}
+
+/// 联机主机决定换边时调用（在发送 SwitchRoles 之前）：把面板中的修改发给客户端并排进 queued，
+/// 双方在随后的回合切换中同时应用
+pub fn queue_pending_rules_for_switch(panel: &mut TuningPanel, rules: &GameRules, network_manager: &NetworkManager) {
+    let Some(pending) = panel.pending.take() else {
+        return;
+    };
+    panel.status = None;
+    if pending == *rules {
+        return;
+    }
+    for _ in 0..RULES_UPDATE_REPEAT {
+        crate::network_game::send_network_message(network_manager, NetworkMessage::RulesUpdate { rules: pending.clone() });
+    }
+    panel.queued = Some(pending);
+}
+
+/// 回合切换时应用新规则：本地对战应用面板中的修改，联机时应用换边前排进 queued 的规则
+pub fn apply_pending_rules_system(
+    mut panel: ResMut<TuningPanel>,
+    mut rules: ResMut<GameRules>,
+    room_info: Res<RoomInfo>,
+    mut applied_events: EventWriter<RulesAppliedEvent>,
+) {
+    let next = if room_info.is_connected {
+        panel.queued.take()
+    } else {
+        panel.status = None;
+        panel.pending.take()
+    };
+    let Some(next) = next else {
+        return;
+    };
+    if next == *rules {
+        return;
+    }
+    let previous = std::mem::replace(&mut *rules, next);
+    // 调试输出已禁用: println!("[调参] 新规则生效: {:?}", *rules);
+    applied_events.send(RulesAppliedEvent { previous });
+}
+
+/// 客户端：核对主机的规则指纹
+/// 主机已经换了规则而本机还在等回合切换时不用处理；排队的规则与主机一致但已经过了回合切换（重新请求来的）时立即生效；
+/// 都对不上说明 RulesUpdate 全部丢了，定期向主机请求
+pub fn check_host_rules_system(
+    time: Res<Time>,
+    (mut panel, mut rules): (ResMut<TuningPanel>, ResMut<GameRules>),
+    (room_info, round_info, host_rules): (Res<RoomInfo>, Res<crate::gameplay::RoundInfo>, Res<HostRulesHash>),
+    network_manager: Option<Res<NetworkManager>>,
+    mut applied_events: EventWriter<RulesAppliedEvent>,
+    mut last_request: Local<Option<f64>>,
+) {
+    if !room_info.is_connected || room_info.is_host {
+        return;
+    }
+    let Some(host_hash) = host_rules.0 else {
+        return;
+    };
+    if rules.fingerprint() == host_hash {
+        return;
+    }
+    if panel.queued.as_ref().is_some_and(|queued| queued.fingerprint() == host_hash) {
+        if round_info.is_switching {
+            return;
+        }
+        if let Some(queued) = panel.queued.take() {
+            eprintln!("[调参] 补上主机已经生效的规则");
+            let previous = std::mem::replace(&mut *rules, queued);
+            applied_events.send(RulesAppliedEvent { previous });
+        }
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    if last_request.is_some_and(|last| now - last < RULES_REQUEST_INTERVAL) {
+        return;
+    }
+    *last_request = Some(now);
+    if let Some(network_manager) = network_manager.as_ref() {
+        eprintln!("[调参] 本机规则与主机不一致，请求主机重发规则");
+        crate::network_game::send_network_message(network_manager, NetworkMessage::RulesRequest);
+    }
+}
+
+/// 规则更新后：同步玩家的躲避冷却，并在屏幕上提示改动的数值
+pub fn announce_rules_system(
+    mut commands: Commands,
+    mut applied_events: EventReader<RulesAppliedEvent>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut cooldown_query: Query<&mut ActionCooldown>,
+    notice_query: Query<Entity, With<RulesNotice>>,
+) {
+    let Some(event) = applied_events.read().last() else {
+        return;
+    };
+    for mut cooldown in cooldown_query.iter_mut() {
+        cooldown.cooldown_duration = rules.dodge_cooldown_seconds as f64;
+    }
+    let changes: Vec<String> = TunableField::ALL
+        .iter()
+        .filter(|field| field.get(&event.previous) != field.get(&rules))
+        .map(|field| format!("{} {} → {}", field.label(), field.format(&event.previous), field.format(&rules)))
+        .collect();
+    let message = if changes.is_empty() {
+        "规则已更新".to_string()
+    } else {
+        format!("规则已更新：{}", changes.join("，"))
+    };
+    for entity in notice_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_notice(&mut commands, font_resource.font.clone(), message);
+}
+
+/// 提示显示一段时间后消失
+pub fn rules_notice_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notice_query: Query<(Entity, &mut RulesNotice)>,
+) {
+    for (entity, mut notice) in notice_query.iter_mut() {
+        notice.timer.tick(time.delta());
+        if notice.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// 回到主菜单时关闭面板、丢弃未生效的修改
+pub fn cleanup_tuning_system(
+    mut commands: Commands,
+    (mut panel, mut host_rules): (ResMut<TuningPanel>, ResMut<HostRulesHash>),
+    panel_query: Query<Entity, With<TuningPanelUI>>,
+    notice_query: Query<Entity, With<RulesNotice>>,
+) {
+    for entity in panel_query.iter().chain(notice_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    let preset_name = std::mem::take(&mut panel.preset_name);
+    *panel = TuningPanel { preset_name, ..default() };
+    host_rules.0 = None;
+}
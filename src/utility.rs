@@ -0,0 +1,382 @@
+// 防守方道具：烟雾弹、假人和可部署护盾
+//
+// 防守方每个回合可以使用 utility_charges 次道具（对局规则），按 U 切换道具，按 L 在自己站的位置使用：
+// - 烟雾弹：在进攻方视角中遮住一片区域，持续 smoke_seconds 秒（防守方视角中是半透明的烟，能看到自己）
+// - 假人：和防守方一样的人形，站在墙后透过破洞能被看到，持续 decoy_seconds 秒，被子弹打中就消失
+// - 护盾：挡在躯干前的一小块护盾，吸收打在它上面的一发子弹后碎掉（头和腿仍然露在外面），持续到回合结束
+// 选择会一直保留，次数和场上的道具在换回合时清空。
+// 联机时使用方发送 DefenderUtility（重复发送几次，按 charge 去重），对方在自己的视角中生成同样的道具，
+// 进攻方按对局规则检查次数。护盾和假人被打中由双方各自按子弹落点判定（开火方在开枪时，接收方在收到 BulletSpawn 时）。
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use crate::game_rules::GameRules;
+use crate::gameplay::{hitbox_at, DefenderAI, DodgeAction, RoundInfo, ViewConfig, ViewLayer};
+use crate::network_game::NetworkManager;
+use crate::{FontResource, PlayerId, RoomInfo, PLAYER_SIZE};
+use bevy_sniper_duel::protocol::NetworkMessage;
+pub use bevy_sniper_duel::protocol::UtilityKind;
+
+/// 切换道具的按键
+const UTILITY_SWITCH_KEY: KeyCode = KeyCode::KeyU;
+/// 使用道具的按键
+const UTILITY_USE_KEY: KeyCode = KeyCode::KeyL;
+/// DefenderUtility 的发送次数（防止丢包，接收方按 charge 去重）
+const UTILITY_SEND_REPEAT: usize = 3;
+/// 烟雾遮住的区域
+const SMOKE_SIZE: Vec2 = Vec2::new(180.0, 180.0);
+/// 护盾大小（只挡住躯干）
+const SHIELD_SIZE: Vec2 = Vec2::new(60.0, 45.0);
+/// 护盾碎裂动画的时间（秒）
+const SHIELD_BREAK_SECONDS: f32 = 0.3;
+/// 烟雾和假人消失前逐渐变淡的时间（秒）
+const FADE_SECONDS: f32 = 1.0;
+/// 烟雾的 Z（两个视角都在墙和人物之上，在准星和激光之下）
+const SMOKE_Z: f32 = 2.5;
+/// 护盾的 Z（与掩体相同：进攻方视角挡在人前面，防守方视角画在人后面）
+const SHIELD_Z: f32 = 1.5;
+
+/// 本地防守方的道具选择和本回合已用次数
+#[derive(Resource, Default, Debug)]
+pub struct DefenderUtilities {
+    pub selected: UtilityKind,
+    pub used: u32,
+}
+
+/// 场上的道具（每个视角一份副本，假人的每个身体部位各是一个实体）
+#[derive(Component, Debug)]
+pub struct UtilityEffect {
+    pub kind: UtilityKind,
+    pub position: Vec2,       // 使用时防守方的位置
+    pub view_layer: ViewLayer,
+    pub timer: Timer,         // 剩余时间（护盾碎掉后换成碎裂动画的时间）
+    pub spent: bool,          // 护盾已经吸收过子弹
+    alpha: f32,               // 正常显示时的透明度（消失前按剩余时间变淡）
+}
+
+/// 道具名称、剩余次数和按键提示（防守方视角）
+#[derive(Component)]
+pub struct UtilityText;
+
+/// 本机要生成道具的视角：联机时只有自己的视角，本地模式两个视角都要
+fn local_view_layers(room_info: Option<&RoomInfo>, view_config: &ViewConfig) -> Vec<ViewLayer> {
+    if !room_info.is_some_and(|room| room.is_connected) {
+        vec![ViewLayer::AttackerView, ViewLayer::DefenderView]
+    } else if view_config.is_attacker_view {
+        vec![ViewLayer::AttackerView]
+    } else {
+        vec![ViewLayer::DefenderView]
+    }
+}
+
+/// 假人的外观与防守方相同（颜色和头像同 setup_game）
+fn player_look(player_id: PlayerId) -> (Color, &'static str) {
+    match player_id {
+        PlayerId::Player1 => (Color::rgb(0.2, 0.4, 1.0), "Statics/js.jpg"),
+        PlayerId::Player2 => (Color::rgb(0.2, 1.0, 0.4), "Statics/wmh.jpg"),
+    }
+}
+
+/// 在 position 处生成道具的各个视角副本
+fn spawn_utility(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    (kind, position, owner): (UtilityKind, Vec2, PlayerId),
+    view_layers: &[ViewLayer],
+    rules: &GameRules,
+) {
+    let seconds = match kind {
+        UtilityKind::Smoke => rules.smoke_seconds,
+        UtilityKind::Decoy => rules.decoy_seconds,
+        UtilityKind::Shield => rules.round_time_seconds,
+    };
+    for view_layer in view_layers {
+        let render_layer = match view_layer {
+            ViewLayer::AttackerView => RenderLayers::layer(0),
+            ViewLayer::DefenderView => RenderLayers::layer(1),
+        };
+        let effect = |alpha: f32| UtilityEffect {
+            kind,
+            position,
+            view_layer: *view_layer,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            spent: false,
+            alpha,
+        };
+        match kind {
+            UtilityKind::Smoke => {
+                // 进攻方视角几乎不透明，防守方视角只是淡淡的一层
+                let alpha = if *view_layer == ViewLayer::AttackerView { 0.97 } else { 0.35 };
+                commands.spawn((
+                    effect(alpha),
+                    SpriteBundle {
+                        sprite: Sprite { color: Color::rgba(0.6, 0.6, 0.62, alpha), custom_size: Some(SMOKE_SIZE), ..default() },
+                        transform: Transform::from_translation(position.extend(SMOKE_Z)),
+                        ..default()
+                    },
+                    render_layer,
+                ));
+            }
+            UtilityKind::Shield => {
+                commands.spawn((
+                    effect(0.9),
+                    SpriteBundle {
+                        sprite: Sprite { color: Color::rgba(0.45, 0.55, 0.65, 0.9), custom_size: Some(SHIELD_SIZE), ..default() },
+                        transform: Transform::from_translation(position.extend(SHIELD_Z)),
+                        ..default()
+                    },
+                    render_layer,
+                ));
+            }
+            UtilityKind::Decoy => {
+                // 身体比例同 spawn_humanoid_sprite；防守方视角中是半透明的虚影，方便和自己区分
+                let (color, head_path) = player_look(owner);
+                let (alpha, z) = match view_layer {
+                    ViewLayer::AttackerView => (1.0, 1.0),
+                    ViewLayer::DefenderView => (0.4, 2.0),
+                };
+                let (width, height) = (PLAYER_SIZE.x, PLAYER_SIZE.y);
+                let (head_height, torso_height, legs_height) = (height * 0.3, height * 0.5, height * 0.2);
+                let parts = [
+                    (Vec2::new(width * 0.8, head_height), (height - head_height) / 2.0, Some(head_path), Color::WHITE),
+                    (Vec2::new(width * 0.9, torso_height), -(height - torso_height) / 2.0 + head_height / 2.0, None, color),
+                    (Vec2::new(width * 0.7, legs_height), -(height - legs_height) / 2.0, None, color * 0.8),
+                ];
+                for (size, offset_y, image, part_color) in parts {
+                    let mut bundle = SpriteBundle {
+                        sprite: Sprite { color: part_color.with_a(alpha), custom_size: Some(size), ..default() },
+                        transform: Transform::from_translation(Vec3::new(position.x, position.y + offset_y, z)),
+                        ..default()
+                    };
+                    if let Some(path) = image {
+                        bundle.texture = asset_server.load(path);
+                    }
+                    commands.spawn((effect(alpha), bundle, render_layer));
+                }
+            }
+        }
+    }
+}
+
+/// 玩家自己操作的防守方实体（不含电脑防守方和身体部位）
+type HumanDefenderFilter = (Without<DefenderAI>, Without<crate::gameplay::HumanoidPart>);
+
+/// 本回合是否还能使用道具
+fn can_use_utility(round_info: &RoundInfo) -> bool {
+    !round_info.is_switching && !round_info.round_timer.finished()
+}
+
+/// 防守方：U 切换道具，L 在自己的位置使用（联机时通知对方）
+pub fn defender_utility_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut utilities: ResMut<DefenderUtilities>,
+    (round_info, rules, view_config): (Res<RoundInfo>, Res<GameRules>, Res<ViewConfig>),
+    defender_query: Query<(&Transform, &PlayerId), HumanDefenderFilter>,
+    (asset_server, room_info): (Res<AssetServer>, Option<Res<RoomInfo>>),
+    network_manager: Option<Res<NetworkManager>>,
+) {
+    if keyboard_input.just_pressed(UTILITY_SWITCH_KEY) {
+        utilities.selected = utilities.selected.next();
+    }
+    if !keyboard_input.just_pressed(UTILITY_USE_KEY) || !can_use_utility(&round_info) || utilities.used >= rules.utility_charges {
+        return;
+    }
+    // 电脑防守方不用道具；联机时只有本机是防守方时才能用
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+    let Some((transform, _)) = defender_query.iter().find(|(_, id)| **id == defender) else {
+        return;
+    };
+    let is_network_mode = room_info.as_ref().is_some_and(|room| room.is_connected);
+    if is_network_mode {
+        let local_player = if room_info.as_ref().is_some_and(|room| room.is_host) { PlayerId::Player1 } else { PlayerId::Player2 };
+        if local_player != defender || view_config.is_attacker_view {
+            return;
+        }
+    }
+
+    let kind = utilities.selected;
+    let position = transform.translation.truncate();
+    let charge = utilities.used;
+    utilities.used += 1;
+    let view_layers = local_view_layers(room_info.as_deref(), &view_config);
+    spawn_utility(&mut commands, &asset_server, (kind, position, defender), &view_layers, &rules);
+    // 调试输出已禁用: println!("[道具] {:?} 在 {:?} 使用了{}（第 {} 次）", defender, position, kind.label(), charge + 1);
+
+    if let Some(network_manager) = network_manager.as_ref().filter(|_| is_network_mode) {
+        for _ in 0..UTILITY_SEND_REPEAT {
+            crate::network_game::send_network_message(
+                network_manager,
+                NetworkMessage::DefenderUtility { player_id: defender, kind, position: position.to_array(), charge },
+            );
+        }
+    }
+}
+
+/// 接收对方防守方使用的道具，在自己的视角中生成
+pub fn handle_remote_utility_system(
+    mut commands: Commands,
+    network_manager: Res<NetworkManager>,
+    (room_info, view_config): (Res<RoomInfo>, Res<ViewConfig>),
+    (round_info, rules): (Res<RoundInfo>, Res<GameRules>),
+    mut anti_cheat: ResMut<crate::anti_cheat::AntiCheatMonitor>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    if !room_info.is_connected {
+        return;
+    }
+    let now = time.elapsed_seconds_f64();
+    anti_cheat.sync_round(round_info.current_attacker, now);
+    let peer = network_manager.remote_addr.lock().ok().and_then(|addr| *addr);
+    let local_player = if network_manager.is_host { PlayerId::Player1 } else { PlayerId::Player2 };
+    let defender = crate::scoring::opponent(round_info.current_attacker);
+
+    let Ok(mut queue) = network_manager.message_queue.lock() else {
+        return;
+    };
+    let mut messages_to_keep = Vec::new();
+    for msg in queue.drain(..) {
+        match msg {
+            NetworkMessage::DefenderUtility { player_id, kind, position, charge } => {
+                // 只接受对方作为当前防守方使用的道具（换回合前的迟到消息直接丢弃）
+                if player_id != defender || player_id == local_player || !can_use_utility(&round_info) {
+                    continue;
+                }
+                if anti_cheat.check_utility(&rules, now, charge, peer) {
+                    let view_layers = local_view_layers(Some(&room_info), &view_config);
+                    spawn_utility(&mut commands, &asset_server, (kind, Vec2::from(position), player_id), &view_layers, &rules);
+                }
+            }
+            _ => messages_to_keep.push(msg),
+        }
+    }
+    queue.extend(messages_to_keep);
+}
+
+/// 子弹打到了道具上：没碎的护盾吸收这一发（返回 true，防守方不受伤害），被打中的假人消失
+/// 调用方先确认子弹能打到墙后（没被墙和掩体挡住）
+pub fn absorb_shot(landing_pos: Vec2, effects: &mut Query<&mut UtilityEffect>) -> bool {
+    let shield = effects
+        .iter()
+        .find(|effect| {
+            let offset = (landing_pos - effect.position).abs();
+            effect.kind == UtilityKind::Shield && !effect.spent && offset.cmple(SHIELD_SIZE / 2.0).all()
+        })
+        .map(|effect| effect.position);
+    let decoy = effects
+        .iter()
+        .filter(|effect| effect.kind == UtilityKind::Decoy)
+        .map(|effect| effect.position)
+        .find(|position| hitbox_at(landing_pos, *position, &DodgeAction::None).is_some());
+
+    for mut effect in effects.iter_mut() {
+        match effect.kind {
+            UtilityKind::Shield if Some(effect.position) == shield => {
+                effect.spent = true;
+                effect.timer = Timer::from_seconds(SHIELD_BREAK_SECONDS, TimerMode::Once);
+            }
+            UtilityKind::Decoy if Some(effect.position) == decoy => {
+                let duration = effect.timer.duration();
+                effect.timer.set_elapsed(duration);
+            }
+            _ => {}
+        }
+    }
+    shield.is_some()
+}
+
+/// 进攻方视角中这个位置是否被烟雾遮住（电脑进攻方判断能否透过破洞看到防守方）
+pub fn in_smoke<'a>(position: Vec2, effects: impl IntoIterator<Item = &'a UtilityEffect>) -> bool {
+    effects.into_iter().any(|effect| {
+        effect.kind == UtilityKind::Smoke
+            && effect.view_layer == ViewLayer::AttackerView
+            && (position - effect.position).abs().cmple(SMOKE_SIZE / 2.0).all()
+    })
+}
+
+/// 进攻方视角中假人的位置（电脑进攻方会把看到的假人当成防守方）
+pub fn decoy_positions<'a>(effects: impl IntoIterator<Item = &'a UtilityEffect>) -> Vec<Vec2> {
+    effects
+        .into_iter()
+        .filter(|effect| effect.kind == UtilityKind::Decoy && effect.view_layer == ViewLayer::AttackerView)
+        .map(|effect| effect.position)
+        .collect()
+}
+
+/// 道具计时：烟雾和假人到时间前逐渐变淡，碎掉的护盾闪白后消失
+pub fn utility_lifetime_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut UtilityEffect, &mut Sprite)>,
+) {
+    for (entity, mut effect, mut sprite) in query.iter_mut() {
+        effect.timer.tick(time.delta());
+        if effect.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let remaining = effect.timer.remaining_secs();
+        if effect.spent {
+            sprite.color = Color::rgba(1.0, 1.0, 1.0, effect.alpha * remaining / SHIELD_BREAK_SECONDS);
+        } else {
+            sprite.color.set_a(effect.alpha * (remaining / FADE_SECONDS).min(1.0));
+        }
+    }
+}
+
+/// 换回合时清除场上的道具，重新计算次数
+pub fn reset_utilities_on_switch(
+    mut commands: Commands,
+    mut utilities: ResMut<DefenderUtilities>,
+    query: Query<Entity, With<UtilityEffect>>,
+) {
+    utilities.used = 0;
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 创建道具文字（放在防守方的动作冷却提示下方）
+pub fn spawn_utility_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(String::new(), TextStyle { font: font.clone(), font_size: 20.0, color: Color::WHITE }),
+                TextSection::new(String::new(), TextStyle { font, font_size: 16.0, color: Color::GRAY }),
+            ]),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                left: Val::Percent(50.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(9999), // 与防守方其他UI一样最后渲染
+            transform: Transform::from_xyz(-100.0, 0.0, 0.0),
+            ..default()
+        },
+        UtilityText,
+        crate::DefenderUI,
+    ));
+}
+
+/// 更新道具文字（只在内容变化时写入）
+pub fn update_utility_display_system(
+    utilities: Res<DefenderUtilities>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<UtilityText>>,
+) {
+    let remaining = rules.utility_charges.saturating_sub(utilities.used);
+    let summary = format!("道具: {}  剩余 {}/{}  ", utilities.selected.label(), remaining, rules.utility_charges);
+    let hint = if remaining > 0 { "U 切换 · L 使用" } else { "U 切换" };
+    for mut text in text_query.iter_mut() {
+        if text.sections.len() < 2 || (text.sections[0].value == summary && text.sections[1].value == hint) {
+            continue;
+        }
+        text.sections[0].value = summary.clone();
+        text.sections[1].value = hint.to_string();
+        text.sections[0].style.font = font_resource.font.clone();
+        text.sections[1].style.font = font_resource.font.clone();
+    }
+}
@@ -0,0 +1,117 @@
+// 武器数值：三把狙击枪相对对局规则的倍率，以及按规则换算后的数值
+//
+// 联机时双方按 BulletSpawn 中的武器各自换算弹匣、伤害和穿透，消息校验也要用到所有武器中的最大值，
+// 因此这部分编译进库，fuzz 目标与游戏本体使用同一份数值（配装、破墙范围和瞄准镜晃动见 weapons.rs）。
+
+use crate::protocol::WeaponKind;
+use crate::rules::{GameRules, MAX_BULLETS_PER_ROUND};
+
+/// 武器相对对局规则的倍率
+struct WeaponDef {
+    magazine_bonus: i32,     // 相对每回合子弹数的增减
+    cooldown_scale: f32,
+    damage_scale: [f32; 3],  // 头部、躯干、腿部
+    bricks_destroyed: usize,
+    scope_sway: f32,
+    bullet_speed_scale: f32,
+    penetration: usize,      // 能打穿的完好砖块数
+    wall_damage: u32,        // 每发对弹孔内砖块造成的耐久伤害
+}
+
+fn definition(kind: WeaponKind) -> WeaponDef {
+    match kind {
+        WeaponKind::BoltAction => WeaponDef {
+            magazine_bonus: 0,
+            cooldown_scale: 1.0,
+            damage_scale: [1.0, 1.0, 1.0],
+            bricks_destroyed: 3,
+            scope_sway: 4.0,
+            bullet_speed_scale: 1.0,
+            penetration: 1,
+            wall_damage: 1,
+        },
+        WeaponKind::SemiAuto => WeaponDef {
+            magazine_bonus: 2,
+            cooldown_scale: 0.4,
+            damage_scale: [0.8, 0.75, 0.75],
+            bricks_destroyed: 2,
+            scope_sway: 8.0,
+            bullet_speed_scale: 1.0,
+            penetration: 0,
+            wall_damage: 1,
+        },
+        WeaponKind::AntiMateriel => WeaponDef {
+            magazine_bonus: -1,
+            cooldown_scale: 2.5,
+            damage_scale: [1.5, 1.5, 1.5],
+            bricks_destroyed: 9,
+            scope_sway: 14.0,
+            bullet_speed_scale: 1.4,
+            penetration: 3,
+            wall_damage: 3,
+        },
+    }
+}
+
+/// 武器数值（按当前对局规则换算后的结果）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub magazine_size: i32,
+    pub cooldown_seconds: f32,
+    pub damage_head: f32,
+    pub damage_torso: f32,
+    pub damage_legs: f32,
+    pub bricks_destroyed: usize,  // 一发最多打碎的砖块数
+    pub scope_sway: f32,          // 瞄准镜晃动幅度（像素）
+    pub bullet_speed: f32,
+    pub penetration: usize,       // 能打穿的完好砖块数，每穿一块伤害递减
+    pub wall_damage: u32,         // 每发对弹孔内砖块造成的耐久伤害
+}
+
+impl Weapon {
+    pub fn of(kind: WeaponKind, rules: &GameRules) -> Self {
+        let def = definition(kind);
+        Self {
+            kind,
+            magazine_size: (rules.bullets_per_round + def.magazine_bonus).clamp(1, MAX_BULLETS_PER_ROUND),
+            cooldown_seconds: rules.shoot_cooldown_seconds * def.cooldown_scale,
+            damage_head: rules.damage_head * def.damage_scale[0],
+            damage_torso: rules.damage_torso * def.damage_scale[1],
+            damage_legs: rules.damage_legs * def.damage_scale[2],
+            bricks_destroyed: def.bricks_destroyed,
+            scope_sway: def.scope_sway,
+            bullet_speed: rules.bullet_speed * def.bullet_speed_scale,
+            penetration: def.penetration,
+            wall_damage: def.wall_damage,
+        }
+    }
+
+    pub fn max_damage(&self) -> f32 {
+        self.damage_head.max(self.damage_torso).max(self.damage_legs)
+    }
+
+    /// 穿过相当于 bricks 块砖的完好砖块后剩余的伤害比例；超过穿透力时子弹被挡住，返回 None
+    /// （不同材质相当于几块砖见 materials::BrickSpec::penetration_cost）
+    /// 穿透力为 N 时每穿一块砖损失 1/(N+1) 的伤害，但这一发打碎的最前面那块砖（相当于 broken 块）不损失伤害，
+    /// 所以栓动步枪打碎一块砖命中仍是经典玩法的满伤害
+    pub fn penetration_damage_scale(&self, bricks: usize, broken: usize) -> Option<f32> {
+        if bricks > self.penetration {
+            return None;
+        }
+        Some(1.0 - bricks.saturating_sub(broken) as f32 / (self.penetration + 1) as f32)
+    }
+}
+
+/// 所有武器中的最大值（消息校验、子弹图标数量用）
+pub fn max_magazine_size(rules: &GameRules) -> i32 {
+    WeaponKind::ALL.iter().map(|kind| Weapon::of(*kind, rules).magazine_size).max().unwrap_or(rules.bullets_per_round)
+}
+
+pub fn max_bullet_speed(rules: &GameRules) -> f32 {
+    WeaponKind::ALL.iter().map(|kind| Weapon::of(*kind, rules).bullet_speed).fold(rules.bullet_speed, f32::max)
+}
+
+pub fn max_damage(rules: &GameRules) -> f32 {
+    WeaponKind::ALL.iter().map(|kind| Weapon::of(*kind, rules).max_damage()).fold(rules.max_damage(), f32::max)
+}
@@ -0,0 +1,188 @@
+// 武器与配装：进攻方在每个进攻回合开火前按 Tab 切换狙击枪
+//
+// 武器数值以对局规则为基准换算（倍率表见 weapon_stats.rs），栓动步枪与规则完全一致（经典玩法，打碎挡在前面的一块砖后照样满伤害命中）：
+// - 半自动步枪：弹匣大、射击间隔短，但单发伤害低，爆头也打不死满血的对手，打不穿砖
+// - 反器材步枪：弹匣小、射击间隔长、晃动大，但伤害高、子弹快，能在墙上打出大洞，可以连穿几块砖，
+//   一发就能打碎混凝土
+// 选择会一直保留到玩家再次切换；开出第一枪后本回合不能再换。
+// 联机时进攻方切换后发送 WeaponSelect，BulletSpawn 中也带着武器，
+// 接收方据此计算弹匣、破墙范围，并按这把武器的弹匣和射击间隔做反作弊检查。
+
+use bevy::prelude::*;
+use crate::game_rules::GameRules;
+use crate::gameplay::{HitboxType, RoundInfo};
+use crate::{FontResource, PlayerId, RoomInfo};
+use bevy_sniper_duel::protocol::NetworkMessage;
+pub use bevy_sniper_duel::protocol::WeaponKind;
+// 武器数值（联机校验也要用到）定义在 weapon_stats.rs
+pub use bevy_sniper_duel::weapon_stats::{max_magazine_size, Weapon};
+
+/// 切换武器的按键
+const WEAPON_SWITCH_KEY: KeyCode = KeyCode::Tab;
+
+/// 武器数值中与游戏世界有关的部分（命中部位、砖块尺寸、瞄准镜）
+pub trait WeaponEffects {
+    fn damage(&self, hitbox_type: HitboxType) -> f32;
+    /// 破墙半径：打碎 3 块砖时为 1.5 块砖宽，更多砖块时按面积放大
+    fn hole_radius(&self) -> f32;
+    /// 瞄准镜的自然晃动（缓慢的椭圆轨迹）
+    fn idle_sway(&self, elapsed_seconds: f32) -> Vec2;
+}
+
+impl WeaponEffects for Weapon {
+    fn damage(&self, hitbox_type: HitboxType) -> f32 {
+        match hitbox_type {
+            HitboxType::Head => self.damage_head,
+            HitboxType::Torso => self.damage_torso,
+            HitboxType::Legs => self.damage_legs,
+        }
+    }
+
+    fn hole_radius(&self) -> f32 {
+        crate::BRICK_WIDTH * 1.5 * (self.bricks_destroyed as f32 / 3.0).sqrt().max(1.0)
+    }
+
+    fn idle_sway(&self, elapsed_seconds: f32) -> Vec2 {
+        Vec2::new((elapsed_seconds * 1.3).sin(), (elapsed_seconds * 2.1).sin() * 0.6) * self.scope_sway
+    }
+}
+
+/// 双方当前选择的武器
+#[derive(Resource, Default, Debug)]
+pub struct Loadout {
+    pub p1: WeaponKind,
+    pub p2: WeaponKind,
+}
+
+impl Loadout {
+    pub fn get(&self, player_id: PlayerId) -> WeaponKind {
+        match player_id {
+            PlayerId::Player1 => self.p1,
+            PlayerId::Player2 => self.p2,
+        }
+    }
+
+    pub fn set(&mut self, player_id: PlayerId, weapon: WeaponKind) {
+        match player_id {
+            PlayerId::Player1 => self.p1 = weapon,
+            PlayerId::Player2 => self.p2 = weapon,
+        }
+    }
+
+    /// 当前进攻方的武器数值
+    pub fn attacker_weapon(&self, round_info: &RoundInfo, rules: &GameRules) -> Weapon {
+        Weapon::of(self.get(round_info.current_attacker), rules)
+    }
+}
+
+/// 武器名称和切换提示（进攻方视角）
+#[derive(Component)]
+pub struct WeaponText;
+
+/// 本回合是否还能换枪：开火前、回合进行中
+fn can_switch_weapon(round_info: &RoundInfo) -> bool {
+    round_info.bullets_fired_this_round == 0 && !round_info.is_switching && !round_info.round_timer.finished()
+}
+
+/// 配装：进攻方开火前按 Tab 切换武器，弹匣随之更换
+pub fn weapon_select_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut loadout: ResMut<Loadout>,
+    mut round_info: ResMut<RoundInfo>,
+    rules: Res<GameRules>,
+    bot_query: Query<&PlayerId, With<crate::gameplay::AttackerAI>>,
+    room_info: Option<Res<RoomInfo>>,
+    network_manager: Option<Res<crate::network_game::NetworkManager>>,
+) {
+    if !keyboard_input.just_pressed(WEAPON_SWITCH_KEY) || !can_switch_weapon(&round_info) {
+        return;
+    }
+    let attacker = round_info.current_attacker;
+    // 电脑进攻方固定使用栓动步枪
+    if bot_query.iter().any(|id| *id == attacker) {
+        return;
+    }
+    let is_network_mode = room_info.as_ref().is_some_and(|r| r.is_connected);
+    if is_network_mode {
+        let local_player = if room_info.as_ref().is_some_and(|r| r.is_host) { PlayerId::Player1 } else { PlayerId::Player2 };
+        if local_player != attacker {
+            return;
+        }
+    }
+
+    let weapon = Weapon::of(loadout.get(attacker).next(), &rules);
+    loadout.set(attacker, weapon.kind);
+    round_info.bullets_left = weapon.magazine_size;
+    // 调试输出已禁用: println!("[配装] {:?} 切换为 {}", attacker, weapon.kind.label());
+
+    if let Some(network_manager) = network_manager.as_ref().filter(|_| is_network_mode) {
+        crate::network_game::send_network_message(
+            network_manager,
+            NetworkMessage::WeaponSelect { player_id: attacker, weapon: weapon.kind },
+        );
+    }
+}
+
+/// 对方切换武器：开火前按新武器的弹匣重置子弹数
+pub fn apply_remote_weapon(loadout: &mut Loadout, round_info: &mut RoundInfo, rules: &GameRules, player_id: PlayerId, weapon: WeaponKind) {
+    if loadout.get(player_id) == weapon {
+        return;
+    }
+    loadout.set(player_id, weapon);
+    if player_id == round_info.current_attacker && round_info.bullets_fired_this_round == 0 {
+        round_info.bullets_left = Weapon::of(weapon, rules).magazine_size;
+    }
+}
+
+/// 回合切换后按新进攻方的武器装填弹匣（在 switch_roles_system 重置子弹数之后运行）
+pub fn reload_on_switch_system(
+    loadout: Res<Loadout>,
+    mut round_info: ResMut<RoundInfo>,
+    rules: Res<GameRules>,
+) {
+    round_info.bullets_left = loadout.attacker_weapon(&round_info, &rules).magazine_size;
+}
+
+/// 创建武器文字（放在进攻方的子弹图标下方）
+pub fn spawn_weapon_text(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent.spawn((
+        TextBundle {
+            text: Text::from_sections([
+                TextSection::new(String::new(), TextStyle { font: font.clone(), font_size: 20.0, color: Color::WHITE }),
+                TextSection::new(String::new(), TextStyle { font, font_size: 16.0, color: Color::GRAY }),
+            ]),
+            style: Style { margin: UiRect::top(Val::Px(5.0)), ..default() },
+            ..default()
+        },
+        WeaponText,
+        crate::AttackerUI,
+    ));
+}
+
+/// 更新武器文字（只在内容变化时写入）
+pub fn update_weapon_display_system(
+    loadout: Res<Loadout>,
+    round_info: Res<RoundInfo>,
+    rules: Res<GameRules>,
+    font_resource: Res<FontResource>,
+    mut text_query: Query<&mut Text, With<WeaponText>>,
+) {
+    let weapon = loadout.attacker_weapon(&round_info, &rules);
+    let summary = format!(
+        "武器: {}  弹匣 {} · 射击间隔 {:.1} 秒 · 穿透 {} 块砖\n",
+        weapon.kind.label(),
+        weapon.magazine_size,
+        weapon.cooldown_seconds,
+        weapon.penetration
+    );
+    let hint = if can_switch_weapon(&round_info) { "开火前按 Tab 切换武器" } else { "" };
+    for mut text in text_query.iter_mut() {
+        if text.sections.len() < 2 || (text.sections[0].value == summary && text.sections[1].value == hint) {
+            continue;
+        }
+        text.sections[0].value = summary.clone();
+        text.sections[1].value = hint.to_string();
+        text.sections[0].style.font = font_resource.font.clone();
+        text.sections[1].style.font = font_resource.font.clone();
+    }
+}